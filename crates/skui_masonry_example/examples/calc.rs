@@ -387,6 +387,10 @@ impl CustomPropertyBuilder for MyCaclProperty {
 }
 
 fn main() {
+    //CI / no-display runs : render through the test harness and exit
+    if skui_masonry_example::snapshot::run_headless_from_env( || build_calc().erased() ) {
+        return;
+    }
     let window_size = LogicalSize::new(223., 300.);
 
     let window_attributes = Window::default_attributes()
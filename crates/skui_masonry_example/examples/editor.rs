@@ -85,6 +85,10 @@ fn file_path() -> PathBuf {
 }
 
 fn main() {
+    //CI / no-display runs : render through the test harness and exit
+    if skui_masonry_example::snapshot::run_headless_from_env( || make_widget_tree().erased() ) {
+        return;
+    }
     let window_size = LogicalSize::new(400.0, 400.0);
     let window_attributes = Window::default_attributes()
         .with_title("To-do list")
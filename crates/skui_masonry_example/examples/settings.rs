@@ -0,0 +1,291 @@
+//! Settings dialog exercising the custom-component, binding, action-routing
+//! and styling subsystems together : tabbed pages in an `IndexedStack`, form
+//! rows built from `Children()` slots, `${..:-fallback}` bindings, pseudo-class
+//! styles and `skui_actions!` typed routing from declared `action:` names.
+
+// #![cfg_attr(not(test), windows_subsystem = "windows")]
+
+use masonry::core::{ErasedAction, NewWidget, Properties, Property, Widget, WidgetId, WidgetTag};
+use masonry::dpi::LogicalSize;
+use masonry::peniko::color::AlphaColor;
+use masonry::theme::default_property_set;
+use masonry::widgets::{Button, ButtonPress, IndexedStack, Label, TextAction, TextInput};
+use masonry_winit::app::{AppDriver, DriverCtx, NewWindow, WindowId};
+use masonry_winit::winit::window::Window;
+use skui::{Component, Parameters, TokenAndSpan, Value, SKUI};
+use skui_masonry_example::{skui_actions, CustomPropertyBuilder, DefaultWidgetBuilder, RootWidgetBuilder};
+use skui_masonry_example::actions::declared_action_names;
+use skui_masonry_example::params::ParamsStack;
+
+const PAGES_TAG: WidgetTag<IndexedStack> = WidgetTag::named("pages");
+const STATUS_TAG: WidgetTag<Label> = WidgetTag::named("status");
+
+const TAB_GENERAL: usize = 0;
+const TAB_AUDIO: usize = 1;
+const TAB_ABOUT: usize = 2;
+const CONFIRM_PAGE: usize = 3;
+
+skui_actions! {
+    enum SettingsAction {
+        "tab_general" => TabGeneral(ButtonPress),
+        "tab_audio" => TabAudio(ButtonPress),
+        "tab_about" => TabAbout(ButtonPress),
+        "theme" => ToggleTheme(ButtonPress),
+        "save" => Save(ButtonPress),
+        "confirm" => Confirm(ButtonPress),
+        "cancel" => Cancel(ButtonPress),
+        "name" => NameChanged(TextAction),
+        "language" => LanguageChanged(TextAction),
+    }
+}
+
+// The declared `action:` name, attached to each widget as a masonry property
+// so the driver can resolve the acting widget back to its name.
+#[derive(Clone, Debug, Default)]
+struct ActionName(String);
+
+impl Property for ActionName {
+    fn static_default() -> &'static Self {
+        static DEFAULT: ActionName = ActionName(String::new());
+        &DEFAULT
+    }
+}
+
+struct ActionNameBuilder;
+impl CustomPropertyBuilder for ActionNameBuilder {
+    fn build_properties<'a>(props: &mut Properties, c: &Component<'a>, _skui: &SKUI<'a>) {
+        if let Some( Value::String(name) ) = c.properties.get("action") {
+            props.insert( ActionName(name.to_string()) );
+        }
+    }
+}
+
+struct SettingsState {
+    window_id: WindowId,
+    active_tab: usize,
+    user_name: String,
+    language: String,
+    dark: bool,
+}
+
+impl AppDriver for SettingsState {
+    fn on_action(
+        &mut self,
+        window_id: WindowId,
+        ctx: &mut DriverCtx<'_, '_>,
+        widget_id: WidgetId,
+        action: ErasedAction,
+    ) {
+        debug_assert_eq!(window_id, self.window_id, "unknown window");
+
+        //resolve the acting widget back to its declared `action:` name
+        let name = {
+            let Some(source) = ctx.render_root(window_id).get_widget(widget_id) else { return };
+            if let Some(button) = source.downcast::<Button>() {
+                button.get_prop::<ActionName>().0.clone()
+            } else if let Some(input) = source.downcast::<TextInput>() {
+                input.get_prop::<ActionName>().0.clone()
+            } else {
+                return;
+            }
+        };
+        if name.is_empty() { return }
+
+        match SettingsAction::from_erased(&name, action) {
+            Some(SettingsAction::TabGeneral(_)) => self.show_page(ctx, TAB_GENERAL, None),
+            Some(SettingsAction::TabAudio(_)) => self.show_page(ctx, TAB_AUDIO, None),
+            Some(SettingsAction::TabAbout(_)) => self.show_page(ctx, TAB_ABOUT, None),
+            Some(SettingsAction::ToggleTheme(_)) => {
+                //a full live restyle would go through `runtime::StyleInvalidator`;
+                //here the switch only records the preference
+                self.dark = !self.dark;
+                let status = format!("Theme: {}", if self.dark { "dark" } else { "light" });
+                self.set_status(ctx, &status);
+            }
+            Some(SettingsAction::Save(_)) => {
+                //"dialog" : the confirmation pane lives in the same stack
+                let tab = self.active_tab;
+                self.show_page(ctx, CONFIRM_PAGE, Some("Apply these settings?"));
+                self.active_tab = tab;
+            }
+            Some(SettingsAction::Confirm(_)) => {
+                let status = format!("Saved: name={:?} language={:?}", self.user_name, self.language);
+                let tab = self.active_tab;
+                self.show_page(ctx, tab, Some(&status));
+            }
+            Some(SettingsAction::Cancel(_)) => {
+                let tab = self.active_tab;
+                self.show_page(ctx, tab, Some("Discarded changes."));
+            }
+            Some(SettingsAction::NameChanged(TextAction::Changed(text))) => {
+                self.user_name = text;
+            }
+            Some(SettingsAction::LanguageChanged(TextAction::Changed(text))) => {
+                self.language = text;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl SettingsState {
+    fn show_page(&mut self, ctx: &mut DriverCtx<'_, '_>, page: usize, status: Option<&str>) {
+        if page < CONFIRM_PAGE {
+            self.active_tab = page;
+        }
+        let render_root = ctx.render_root(self.window_id);
+        render_root.edit_widget_with_tag(PAGES_TAG, |mut pages| {
+            IndexedStack::set_active_child(&mut pages, page);
+        });
+        if let Some(status) = status {
+            render_root.edit_widget_with_tag(STATUS_TAG, |mut label| {
+                Label::set_text(&mut label, status);
+            });
+        }
+    }
+
+    fn set_status(&self, ctx: &mut DriverCtx<'_, '_>, status: &str) {
+        ctx.render_root(self.window_id).edit_widget_with_tag(STATUS_TAG, |mut label| {
+            Label::set_text(&mut label, status);
+        });
+    }
+}
+
+pub fn make_widget_tree() -> NewWidget<impl Widget + ?Sized> {
+    let src = r#"
+let spacing = 8
+let label_size = 13
+
+.title { font-size: 20 }
+.hint { font-size: 12; color: #9aabb8 }
+.section { padding: 10; gap: 8 }
+.tab_button { background-color: #26303a; border-width: 1; border-color: transparent }
+.tab_button:hover { border-color: white }
+.save_button { background-color: #008ddd }
+.save_button:hover { background-color: #5cc4ff }
+.danger { background-color: #aa3333 }
+.danger:hover { background-color: #d05050 }
+FormRow Label { font-size: label_size }
+
+FormRow:
+    Flex(Horizontal) {
+        gap: spacing
+        Label( ${0} )
+        Children()
+    }
+
+Section:
+    Flex(axis=Vertical, cross_axis_alignment=Start) .section {
+        Label( ${title:-Untitled} ) .title
+        Children()
+    }
+
+GeneralPage:
+    Section(title="General") {
+        FormRow("User name") { TextInput("guest") { action: "name" } }
+        FormRow("Language") { TextInput("en") { action: "language" } }
+        Button("Save") .save_button { action: "save" }
+    }
+
+AudioPage:
+    Section(title="Audio") {
+        FormRow("Volume") { Slider(0, 100, 40) }
+        FormRow("Mute") { Checkbox("mute all sounds") }
+    }
+
+AboutPage:
+    Section(title="About") {
+        Prose("skui settings example. Components, bindings, actions and styles all come from the embedded document above.")
+    }
+
+ConfirmPage:
+    Section() {
+        Label("Apply these settings?")
+        Flex(Horizontal) {
+            gap: spacing
+            Button("Apply") .save_button { action: "confirm" }
+            Button("Cancel") .danger { action: "cancel" }
+        }
+    }
+
+Main:
+    Flex(Vertical) {
+        padding: spacing
+        Flex(Horizontal) .tabs {
+            gap: spacing
+            Button("General") .tab_button { action: "tab_general" }
+            Button("Audio") .tab_button { action: "tab_audio" }
+            Button("About") .tab_button { action: "tab_about" }
+            FlexSpace(1)
+            Button("Theme") .tab_button { action: "theme" }
+        }
+        FlexItem( IndexedStack(0) #pages {
+            Item( GeneralPage() )
+            Item( AudioPage() )
+            Item( AboutPage() )
+            Item( ConfirmPage() )
+        }, 1.0 )
+        Label("Ready.") #status .hint
+    }
+    "#;
+    build_widget(src)
+}
+
+fn build_widget(src:&str) -> NewWidget<impl Widget + ?Sized> {
+    let tks = TokenAndSpan::new(src);
+    match SKUI::parse(&tks) {
+        Ok(skui) => {
+            //every `action:` in the document must have a typed variant
+            for name in declared_action_names(&skui) {
+                debug_assert!( SettingsAction::NAMES.contains(&name), "no variant for action {name:?}" );
+            }
+            let parameters = Parameters::empty();
+            let Some(params_stack) = ParamsStack::new_main(&parameters, &skui)
+            else { return NewWidget::new( Label::new( "Can't find Main component." ) ).erased() };
+            match DefaultWidgetBuilder::<ActionNameBuilder>::build_widget( &params_stack ) {
+                Ok(widget) => widget.erased(),
+                Err(e) => NewWidget::new( Label::new( format!("{e:#?}") ) ).erased()
+            }
+        }
+        Err( e ) => {
+            let text = format!("{e:#?}\n{}", tks.render_error_from_span(src, e.span.clone(),3));
+            NewWidget::new( Label::new( text ) ).erased()
+        }
+    }
+}
+
+fn main() {
+    //CI / no-display runs : render through the test harness and exit
+    if skui_masonry_example::snapshot::run_headless_from_env( || make_widget_tree().erased() ) {
+        return;
+    }
+    let window_size = LogicalSize::new(480.0, 420.0);
+    let window_attributes = Window::default_attributes()
+        .with_title("Settings")
+        .with_resizable(true)
+        .with_min_inner_size(window_size);
+    let driver = SettingsState {
+        window_id: WindowId::next(),
+        active_tab: TAB_GENERAL,
+        user_name: "guest".to_string(),
+        language: "en".to_string(),
+        dark: true,
+    };
+    let event_loop = masonry_winit::app::EventLoop::with_user_event()
+        .build()
+        .unwrap();
+    masonry_winit::app::run_with(
+        event_loop,
+        vec![
+            NewWindow::new_with_id(
+                driver.window_id,
+                window_attributes,
+                make_widget_tree().erased(),
+            )
+                .with_base_color(AlphaColor::from_rgb8(2, 6, 23)),
+        ],
+        driver,
+        default_property_set(),
+    )
+        .unwrap();
+}
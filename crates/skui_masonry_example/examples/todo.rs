@@ -129,6 +129,10 @@ fn build_widget(src:&str) -> NewWidget<impl Widget + ?Sized> {
 }
 
 fn main() {
+    //CI / no-display runs : render through the test harness and exit
+    if skui_masonry_example::snapshot::run_headless_from_env( || make_widget_tree().erased() ) {
+        return;
+    }
     let window_size = LogicalSize::new(400.0, 400.0);
     let window_attributes = Window::default_attributes()
         .with_title("To-do list")
@@ -0,0 +1,177 @@
+use std::path::Path;
+use skui::{Component, Parameters, Value, SKUI};
+
+// Accessibility snapshot testing. Flattens the accessibility-relevant facts a
+// document declares - roles, accessible names (`aria-label:` or the widget's
+// text), ids and focus order - into a stable text outline and compares it
+// against a golden file, so regressions in what reaches the AccessKit tree
+// are caught without a display server. Custom components are expanded through
+// their definitions like the widget builder would, including `Children()`
+// slots and `${..}` bindings in name position.
+
+// AccessKit role the built widget reports for a component tag.
+fn role(name:&str) -> &'static str {
+    match name {
+        "Button" => "button",
+        "Label" | "Prose" | "VariableLabel" => "label",
+        "TextInput" | "TextArea" => "text-input",
+        "Checkbox" => "check-box",
+        "Slider" => "slider",
+        "ProgressBar" => "progress-indicator",
+        "Portal" => "scroll-view",
+        _ => "group",
+    }
+}
+
+fn focusable(name:&str) -> bool {
+    matches!(name, "Button" | "TextInput" | "TextArea" | "Checkbox" | "Slider")
+}
+
+// One custom-component expansion : the invocation's arguments (for `${..}`
+// lookups inside the definition) and its children (spliced at `Children()`).
+struct Frame<'a> {
+    args: &'a Parameters<'a>,
+    slots: &'a [Component<'a>],
+}
+
+// `aria-label:` wins; otherwise the widget's own text parameter is the name,
+// matching how the builder fills the access node. Bindings resolve against
+// the innermost invocation, like a one-level `ParamsStack`.
+fn accessible_name<'a>(c:&'a Component<'a>, frames:&[Frame<'a>]) -> Option<&'a str> {
+    let value = c.properties.get("aria-label").or_else( || c.params.get(0, "text") )?;
+    match value {
+        Value::Relative(keys) => frames.last()
+            .and_then( |f| f.args.get_as_rk(keys) )
+            .and_then( |v| v.as_str() ),
+        Value::RelativeOr(keys, fallback) => frames.last()
+            .and_then( |f| f.args.get_as_rk(keys) )
+            .and_then( |v| v.as_str() )
+            .or_else( || fallback.as_str() ),
+        v => v.as_str(),
+    }
+}
+
+// One line per access node : `role "name" #id focus=N`, indented by depth.
+pub fn access_outline(skui:&SKUI) -> String {
+    let mut out = String::new();
+    let mut focus = 0usize;
+    if let Some(main) = skui.get_main_component() {
+        walk(skui, &main.component, 0, &mut vec!["Main"], &mut vec![], &mut focus, &mut out);
+    }
+    out
+}
+
+fn walk<'a>(skui:&'a SKUI<'a>, c:&'a Component<'a>, depth:usize, expanding:&mut Vec<&'a str>, frames:&mut Vec<Frame<'a>>, focus:&mut usize, out:&mut String) {
+    //custom component invocation : outline its definition with the call site
+    //on the frame stack
+    if let Some(rc) = skui.get_root_component(c.name) {
+        if !expanding.contains(&c.name) {
+            expanding.push(c.name);
+            frames.push( Frame { args: &c.params, slots: &c.children } );
+            walk(skui, &rc.component, depth, expanding, frames, focus, out);
+            frames.pop();
+            expanding.pop();
+            return;
+        }
+    }
+
+    //slot : splice the invocation's children, resolving their bindings
+    //against the enclosing context
+    if c.name == "Children" {
+        if let Some(frame) = frames.pop() {
+            for child in frame.slots.iter() {
+                walk(skui, child, depth, expanding, frames, focus, out);
+            }
+            frames.push(frame);
+        }
+        return;
+    }
+
+    out.push_str( &"  ".repeat(depth) );
+    out.push_str( role(c.name) );
+    if let Some(name) = accessible_name(c, frames) {
+        out.push_str( &format!(" {name:?}") );
+    }
+    if let Some(id) = c.id {
+        out.push_str( &format!(" #{id}") );
+    }
+    if focusable(c.name) {
+        *focus += 1;
+        out.push_str( &format!(" focus={focus}") );
+    }
+    out.push('\n');
+
+    for child in c.children.iter() {
+        walk(skui, child, depth + 1, expanding, frames, focus, out);
+    }
+}
+
+// Compare an outline against its golden file. A missing golden is created on
+// first run; `SKUI_BLESS=1` rewrites it instead of failing on a mismatch.
+pub fn check_access_snapshot(outline:&str, golden:&Path) -> Result<(), String> {
+    let bless = std::env::var("SKUI_BLESS").is_ok_and( |v| v != "0" );
+    if !bless {
+        if let Ok(expected) = std::fs::read_to_string(golden) {
+            if expected == outline {
+                return Ok(());
+            }
+            return Err( format!(
+                "access snapshot mismatch for {golden:?} (SKUI_BLESS=1 to rewrite)\n--- golden\n{expected}--- actual\n{outline}"
+            ) );
+        }
+    }
+    std::fs::write(golden, outline).map_err( |e| format!("can't write {golden:?}: {e}") )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use skui::TokenAndSpan;
+
+    #[test]
+    fn outline() {
+        let src = r#"
+            Row:
+            Flex(Horizontal) {
+                Label( ${0} )
+                Children()
+            }
+            Main:
+            Flex(Vertical) {
+                Row("Name") { TextInput("guest") #name_input }
+                Button("Save") #save { aria-label: "Save settings" }
+                ProgressBar(0.5)
+            }
+        "#;
+        let tks = TokenAndSpan::new(src);
+        let skui = SKUI::parse(&tks).unwrap();
+        let outline = access_outline(&skui);
+        println!("{outline}");
+
+        //binding in name position resolves against the invocation, the slot
+        //child lands under the expanded definition
+        assert!( outline.contains("label \"Name\"") );
+        assert!( outline.contains("text-input \"guest\" #name_input focus=1") );
+        //aria-label overrides the button text
+        assert!( outline.contains("button \"Save settings\" #save focus=2") );
+        assert!( outline.contains("progress-indicator") );
+    }
+
+    #[test]
+    fn golden_roundtrip() {
+        let src = "Main:\nButton(\"Ok\") #ok\n";
+        let tks = TokenAndSpan::new(src);
+        let skui = SKUI::parse(&tks).unwrap();
+        let outline = access_outline(&skui);
+
+        let golden = std::env::temp_dir().join("skui_a11y_golden_roundtrip.txt");
+        let _ = std::fs::remove_file(&golden);
+        //first run creates, second run matches
+        check_access_snapshot(&outline, &golden).unwrap();
+        check_access_snapshot(&outline, &golden).unwrap();
+        //a drifted outline is rejected with both versions in the message
+        let err = check_access_snapshot("group\n", &golden).unwrap_err();
+        assert!( err.contains("mismatch") );
+        let _ = std::fs::remove_file(&golden);
+    }
+}
@@ -0,0 +1,102 @@
+use skui::{Component, SKUI};
+
+// Typed action routing. Components opt in with an `action: "name"` property
+// (`Button("Save") { action: "save" }`); the driver resolves the acting
+// widget back to its declared name and `skui_actions!` turns name + erased
+// payload into one enum variant, so `on_action` bodies become a plain `match`
+// instead of chained `is::<T>()` / downcast pairs.
+
+// Every `action:` name declared in the document, in tree order. Duplicates
+// are kept : two widgets may route to the same action.
+pub fn declared_action_names<'a>(skui:&SKUI<'a>) -> Vec<&'a str> {
+    let mut out = vec![];
+    for rc in skui.components.iter() {
+        collect(&rc.component, &mut out);
+    }
+    out
+}
+
+fn collect<'a>(c:&Component<'a>, out:&mut Vec<&'a str>) {
+    if let Some(name) = c.properties.get("action").and_then( |v| v.as_str() ) {
+        out.push(name);
+    }
+    c.children.iter().for_each( |child| collect(child, out) );
+}
+
+// `action:` name declared on the component carrying the given `#id`.
+pub fn action_name<'a>(skui:&SKUI<'a>, id:&str) -> Option<&'a str> {
+    skui.find_by_id(id)?.properties.get("action").and_then( |v| v.as_str() )
+}
+
+// Generate a typed action enum from the declared names :
+//
+//     skui_actions! {
+//         pub enum TodoAction {
+//             "add" => Add(ButtonPress),
+//             "edit" => Edit(TextAction),
+//         }
+//     }
+//
+// gives `TodoAction::NAMES` (validate against `declared_action_names` at
+// startup so the DSL and the enum cannot drift apart) and
+// `TodoAction::from_erased(name, action)` which returns `None` when the name
+// is unknown or the erased payload is not the declared type.
+#[macro_export]
+macro_rules! skui_actions {
+    ( $(#[$meta:meta])* $vis:vis enum $name:ident {
+        $( $action:literal => $variant:ident ( $payload:ty ) ),+ $(,)?
+    } ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $( $variant($payload), )+
+        }
+
+        impl $name {
+            $vis const NAMES: &'static [&'static str] = &[ $( $action ),+ ];
+
+            $vis fn from_erased(name:&str, action: masonry::core::ErasedAction) -> Option<Self> {
+                $(
+                    if name == $action {
+                        return action.downcast::<$payload>().ok().map( |a| Self::$variant(*a) );
+                    }
+                )+
+                let _ = action;
+                None
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use skui::TokenAndSpan;
+
+    skui_actions! {
+        pub enum TestAction {
+            "add" => Add(masonry::widgets::ButtonPress),
+            "edit" => Edit(masonry::widgets::TextAction),
+        }
+    }
+
+    #[test]
+    fn declared_names() {
+        let src = r#"
+            Main:
+            Flex() {
+                Button("Add") #add_btn { action: "add" }
+                TextInput("") { action: "edit" }
+                Label("no action")
+            }
+        "#;
+        let tks = TokenAndSpan::new(src);
+        let skui = SKUI::parse(&tks).unwrap();
+
+        //document declarations line up with the generated enum
+        assert_eq!( declared_action_names(&skui), TestAction::NAMES );
+
+        //id -> declared name, the driver side of the lookup
+        assert_eq!( action_name(&skui, "add_btn"), Some("add") );
+        assert_eq!( action_name(&skui, "missing"), None );
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+// FLIP (First-Last-Invert-Play) helpers for layout transitions. The driver
+// captures widget rects before a patch, captures again after, and applies the
+// inverted delta as a transform that eases back to identity — so moves and
+// resizes from hot reload or data updates glide instead of jumping.
+
+// (x, y, width, height) in window coordinates.
+pub type Rect = (f64, f64, f64, f64);
+
+// Widget rects at one point in time, keyed by widget id. Only identified
+// widgets can animate : anonymous ones have no stable key across builds.
+#[derive(Debug, Clone, Default)]
+pub struct LayoutCapture {
+    rects: HashMap<String, Rect>,
+}
+
+impl LayoutCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, id:&str, rect:Rect) {
+        self.rects.insert(id.to_string(), rect);
+    }
+
+    pub fn get(&self, id:&str) -> Option<Rect> {
+        self.rects.get(id).copied()
+    }
+}
+
+// The inverted transform that places a widget back at its old rect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlipDelta {
+    pub target: String,
+    pub dx: f64,
+    pub dy: f64,
+    pub sx: f64,
+    pub sy: f64,
+}
+
+// Deltas for every id present in both captures whose rect changed. Widgets
+// that appeared or disappeared are not FLIP material — fade them instead.
+pub fn flip_deltas(before:&LayoutCapture, after:&LayoutCapture) -> Vec<FlipDelta> {
+    let mut deltas: Vec<FlipDelta> = before.rects.iter()
+        .filter_map( |(id, &(bx,by,bw,bh))| {
+            let (ax,ay,aw,ah) = after.get(id)?;
+            if (bx,by,bw,bh) == (ax,ay,aw,ah) { return None }
+            Some( FlipDelta {
+                target: id.clone(),
+                dx: bx - ax,
+                dy: by - ay,
+                sx: if ah > 0.0 && aw > 0.0 { bw / aw } else { 1.0 },
+                sy: if ah > 0.0 && aw > 0.0 { bh / ah } else { 1.0 },
+            })
+        })
+        .collect();
+    deltas.sort_by( |a,b| a.target.cmp(&b.target) );
+    deltas
+}
+
+// One running transition. `sample(now_elapsed)` returns the transform to apply
+// this frame; `None` once the animation has played out.
+#[derive(Debug, Clone)]
+pub struct FlipAnimation {
+    pub delta: FlipDelta,
+    pub duration: Duration,
+}
+
+impl FlipAnimation {
+    pub fn new(delta:FlipDelta, duration:Duration) -> Self {
+        Self { delta, duration }
+    }
+
+    // ease-out cubic : fast start, settles gently
+    fn ease(t:f64) -> f64 {
+        let inv = 1.0 - t;
+        1.0 - inv * inv * inv
+    }
+
+    pub fn sample(&self, elapsed:Duration) -> Option<FlipDelta> {
+        if elapsed >= self.duration { return None }
+        let t = Self::ease( elapsed.as_secs_f64() / self.duration.as_secs_f64() );
+        //interpolate from the full inverted delta back to identity
+        let remain = 1.0 - t;
+        Some( FlipDelta {
+            target: self.delta.target.clone(),
+            dx: self.delta.dx * remain,
+            dy: self.delta.dy * remain,
+            sx: 1.0 + (self.delta.sx - 1.0) * remain,
+            sy: 1.0 + (self.delta.sy - 1.0) * remain,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deltas() {
+        let mut before = LayoutCapture::new();
+        before.record("moved", (0.0, 0.0, 100.0, 20.0));
+        before.record("grown", (0.0, 40.0, 100.0, 20.0));
+        before.record("still", (0.0, 80.0, 100.0, 20.0));
+        before.record("gone", (0.0, 120.0, 100.0, 20.0));
+
+        let mut after = LayoutCapture::new();
+        after.record("moved", (50.0, 10.0, 100.0, 20.0));
+        after.record("grown", (0.0, 40.0, 200.0, 40.0));
+        after.record("still", (0.0, 80.0, 100.0, 20.0));
+        after.record("new", (0.0, 120.0, 100.0, 20.0));
+
+        let deltas = flip_deltas(&before, &after);
+        assert_eq!( deltas.len(), 2 ); //unchanged/appeared/disappeared skipped
+        assert_eq!( deltas[0], FlipDelta { target: "grown".into(), dx: 0.0, dy: 0.0, sx: 0.5, sy: 0.5 } );
+        assert_eq!( deltas[1], FlipDelta { target: "moved".into(), dx: -50.0, dy: -10.0, sx: 1.0, sy: 1.0 } );
+    }
+
+    #[test]
+    fn playback() {
+        let delta = FlipDelta { target: "moved".into(), dx: -50.0, dy: -10.0, sx: 1.0, sy: 1.0 };
+        let anim = FlipAnimation::new(delta, Duration::from_millis(200));
+
+        //starts at the full inverted offset, eases toward identity
+        let start = anim.sample(Duration::ZERO).unwrap();
+        assert_eq!( (start.dx, start.dy), (-50.0, -10.0) );
+        let mid = anim.sample(Duration::from_millis(100)).unwrap();
+        assert!( mid.dx > start.dx && mid.dx < 0.0 );
+        //finished : caller drops the transform entirely
+        assert!( anim.sample(Duration::from_millis(200)).is_none() );
+    }
+}
@@ -0,0 +1,72 @@
+use std::future::Future;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+// Async build support. Builders that need I/O (Image-from-URL, Markdown-from-
+// file, ..) build a placeholder widget immediately, tag it with an id from
+// `next_placeholder_id`, and register a task here. The driver polls
+// `drain_completed` each frame and patches the tagged widget with the
+// produced data through `edit_widget_with_tag`.
+
+pub struct PendingTasks<T> {
+    tx: UnboundedSender<(String,T)>,
+    rx: UnboundedReceiver<(String,T)>,
+    seq: u64,
+}
+
+impl <T:Send + 'static> PendingTasks<T> {
+    pub fn new() -> Self {
+        let (tx,rx) = unbounded_channel();
+        Self { tx, rx, seq: 0 }
+    }
+
+    // Widget id for the placeholder so the completed task can find it again
+    pub fn next_placeholder_id(&mut self) -> String {
+        self.seq += 1;
+        format!("async-{}", self.seq)
+    }
+
+    // Runs on the current tokio runtime; the result is queued for the driver.
+    pub fn spawn(&self, target:&str, fut:impl Future<Output=T> + Send + 'static) {
+        let tx = self.tx.clone();
+        let target = target.to_string();
+        tokio::spawn( async move {
+            let result = fut.await;
+            //receiver dropped means the ui is gone, nothing to patch
+            let _ = tx.send( (target, result) );
+        });
+    }
+
+    // Completed tasks since the last poll : (placeholder id, produced data)
+    pub fn drain_completed(&mut self) -> Vec<(String,T)> {
+        let mut out = Vec::new();
+        while let Ok(item) = self.rx.try_recv() {
+            out.push(item);
+        }
+        out
+    }
+}
+
+impl <T:Send + 'static> Default for PendingTasks<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholder_then_patch() {
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        rt.block_on( async {
+            let mut pending = PendingTasks::new();
+            let id = pending.next_placeholder_id();
+            assert_eq!( id, "async-1" );
+            pending.spawn( &id, async { "loaded".to_string() } );
+            tokio::task::yield_now().await;
+            let done = pending.drain_completed();
+            assert_eq!( done, vec![ ("async-1".to_string(), "loaded".to_string()) ] );
+        });
+    }
+}
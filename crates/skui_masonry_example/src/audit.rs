@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
+use skui::{Component, Parameters, SKUI};
+use crate::options::{push_diagnostic, BuildDiagnostic};
+
+// Parameter usage auditing : while enabled, every successful map lookup in
+// `ParamsStack` marks the (parameter list, key) pair as read. After the build,
+// `report_unused` walks the document and pushes an `UnusedParam` diagnostic
+// for each named parameter no builder ever touched — which is almost always a
+// typo like `placehodler=` silently falling back to a default.
+//
+// Positional parameters are read by index and are not audited; a misspelled
+// key can only occur in named form.
+
+thread_local! {
+    //parameter lists are identified by address : the document outlives the build
+    static CONSUMED: RefCell<Option<HashSet<(usize, String)>>> = const { RefCell::new(None) };
+}
+
+pub fn enable() {
+    CONSUMED.with( |c| *c.borrow_mut() = Some(HashSet::new()) );
+}
+
+pub fn is_enabled() -> bool {
+    CONSUMED.with( |c| c.borrow().is_some() )
+}
+
+pub(crate) fn record_consumed(params:&Parameters, key:&str) {
+    CONSUMED.with( |c| {
+        if let Some(set) = c.borrow_mut().as_mut() {
+            set.insert( (params as *const Parameters as usize, key.to_string()) );
+        }
+    });
+}
+
+// Stop auditing and surface one `UnusedParam` diagnostic per untouched named
+// parameter in `doc`. Returns how many were reported.
+pub fn report_unused(doc:&SKUI) -> usize {
+    let Some(consumed) = CONSUMED.with( |c| c.borrow_mut().take() ) else { return 0 };
+    let mut count = 0;
+    for rc in doc.components.iter() {
+        check_component(&rc.component, &consumed, &mut count);
+    }
+    count
+}
+
+fn check_component(c:&Component, consumed:&HashSet<(usize,String)>, count:&mut usize) {
+    if let Parameters::Map(map) = &c.params {
+        let ptr = &c.params as *const Parameters as usize;
+        for key in map.keys() {
+            if !consumed.contains( &(ptr, key.to_string()) ) {
+                push_diagnostic( BuildDiagnostic::UnusedParam {
+                    component: c.name.to_string(),
+                    key: key.to_string(),
+                });
+                *count += 1;
+            }
+        }
+    }
+    c.children.iter().for_each( |child| check_component(child, consumed, count) );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::ParamsStack;
+    use crate::options::take_diagnostics;
+
+    #[test]
+    fn unused_params_reported() {
+        let src = r#"
+            Main:
+            Card(title="hi", titel="typo")
+            Card:
+            Label(${title})
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+        let params = skui::Parameters::empty();
+
+        enable();
+        //simulate what the builders read during a build
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        let label = stack.new_stack(stack.component);
+        assert_eq!( label.get(0, "").and_then( |v| v.as_str() ), Some("hi") );
+
+        let n = report_unused(&doc);
+        assert_eq!( n, 1 );
+        let diags = take_diagnostics();
+        assert!( diags.iter().any( |d| matches!( d,
+            BuildDiagnostic::UnusedParam { component, key } if component == "Card" && key == "titel" ) ) );
+        assert!( !is_enabled() );
+    }
+}
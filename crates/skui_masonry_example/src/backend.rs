@@ -0,0 +1,77 @@
+use skui::{Style, SKUI};
+use crate::params::ParamsStack;
+use crate::Error;
+
+// Backend-agnostic widget construction. The masonry integration stays the
+// primary target; this narrow trait is enough to drive a second renderer
+// (terminal preview, egui, ..) from the same documents.
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ContainerAxis {
+    Horizontal,
+    Vertical,
+}
+
+pub trait Backend {
+    type Node;
+
+    fn create_label(&mut self, text:&str) -> Self::Node;
+    fn create_button(&mut self, label:&str) -> Self::Node;
+    fn create_container(&mut self, axis:ContainerAxis, children:Vec<Self::Node>) -> Self::Node;
+    // matched style rules arrive in document order
+    fn apply_style(&mut self, node:&mut Self::Node, style:&Style);
+
+    // leaf components the backend has no equivalent for (Slider, Canvas, ..)
+    fn create_placeholder(&mut self, name:&str) -> Self::Node {
+        self.create_label(name)
+    }
+}
+
+// Generic document walk over the same ParamsStack the masonry builder uses,
+// so parameter resolution (relative values, tr(), defaults) behaves identically.
+pub fn build_node<'a,B:Backend>(backend:&mut B, params_stack:&ParamsStack<'a>) -> Result<B::Node, Error> {
+    let c = params_stack.component;
+    let mut node = match c.name {
+        "Label" | "Prose" => {
+            let text = params_stack.get_text(0, "text").unwrap_or_default();
+            backend.create_label(&text)
+        }
+        "Button" => {
+            let text = params_stack.get_text(0, "text").unwrap_or_default();
+            backend.create_button(&text)
+        }
+        "Flex" | "Grid" | "Main" => {
+            let axis = match params_stack.get(0, "axis").and_then( |v| v.as_str() ) {
+                Some("horizontal") => ContainerAxis::Horizontal,
+                _ => ContainerAxis::Vertical,
+            };
+            let mut children = Vec::new();
+            for child in params_stack.children() {
+                children.push( build_node(backend, &params_stack.new_stack(child))? );
+            }
+            backend.create_container(axis, children)
+        }
+        name @ _ => {
+            // unwrap single-child wrappers, everything else becomes a placeholder
+            let mut iter = params_stack.children();
+            match (iter.next(), iter.next()) {
+                (Some(only), None) => build_node(backend, &params_stack.new_stack(only))?,
+                _ => backend.create_placeholder(name),
+            }
+        }
+    };
+
+    let mut parents = vec![];
+    if let Some(main) = params_stack.skui.get_main_component() {
+        main.component.find(&mut parents, c);
+        for style in params_stack.skui.get_styles(parents.as_slice(), c) {
+            backend.apply_style(&mut node, style);
+        }
+    }
+    Ok( node )
+}
+
+pub fn build_root<'a,B:Backend>(backend:&mut B, params:&'a skui::Parameters<'a>, skui:&'a SKUI<'a>) -> Result<B::Node, Error> {
+    let params_stack = ParamsStack::new_main(params, skui).ok_or(Error::RootComponentNotFound)?;
+    build_node(backend, &params_stack)
+}
@@ -180,7 +180,7 @@ pub fn build_widget<'a>(comp:&'a Component, skui:&'a SKUI,caller_params:Option<&
                             grid = grid.with(item_comp, params);
                         }
                         _ => {
-                            return Err(Error::GridChildMustBeItem)
+                            return Err( Error::GridChildMustBeItem { component: "Grid".into(), item: "GridItem" } )
                         }
                     }
                 }
@@ -199,7 +199,7 @@ pub fn build_widget<'a>(comp:&'a Component, skui:&'a SKUI,caller_params:Option<&
                             grid = grid.with(item_comp, params);
                         }
                         _ => {
-                            return Err(Error::GridChildMustBeItem)
+                            return Err( Error::GridChildMustBeItem { component: "Grid".into(), item: "GridItem" } )
                         }
                     }
                 }
@@ -224,7 +224,7 @@ pub fn build_widget<'a>(comp:&'a Component, skui:&'a SKUI,caller_params:Option<&
                         indexed_stack = indexed_stack.with(comp);
                     }
                     _ => {
-                        return Err(Error::GridChildMustBeItem)
+                        return Err( Error::GridChildMustBeItem { component: "IndexedStack".into(), item: "Item" } )
                     }
                 }
             }
@@ -288,7 +288,7 @@ pub fn build_widget<'a>(comp:&'a Component, skui:&'a SKUI,caller_params:Option<&
             } else if comp.children.len() == 2 {
                 (&comp.children[0], &comp.children[1])
             } else {
-                return Err(Error::ExactlyTwoChildRequired)
+                return Err( Error::ExactlyTwoChildRequired { component: "Split".into() } )
             };
             let split = Split::new(
                 build_widget(&first, skui, None)?,
@@ -0,0 +1,246 @@
+use masonry::kurbo::{BezPath, CircleSegment, Point, Rect, Size};
+use masonry::peniko::color::AlphaColor;
+use masonry::peniko::Fill;
+use masonry::vello::Scene;
+use masonry::widgets::Canvas;
+use skui::Value;
+use crate::params::{ParamsStack, ValueConvError};
+use crate::{Error, RootWidgetBuilder, WidgetBuilder};
+
+// Declarative charts : `BarChart(${series})`, `LineChart(${series})`,
+// `PieChart(${series})` render into a Canvas. Series values come from the
+// binding stack (array of numbers); colors cycle through a fixed palette.
+
+const PALETTE: [(u8,u8,u8);6] = [
+    (66, 133, 244), (219, 68, 55), (244, 180, 0),
+    (15, 157, 88), (171, 71, 188), (0, 172, 193),
+];
+
+fn series_color(i:usize) -> AlphaColor<masonry::peniko::color::Srgb> {
+    let (r,g,b) = PALETTE[i % PALETTE.len()];
+    AlphaColor::from_rgb8(r, g, b)
+}
+
+fn series_values<'a>(params_stack:&ParamsStack<'a>) -> Result<Vec<f64>, Error> {
+    let values = params_stack.get(0, "series")
+        .and_then( |v| v.as_array() )
+        .ok_or_else( || ValueConvError::MandatoryParamMissing.specific(params_stack.fn_name, params_stack.component.name, 0, "series") )?;
+    Ok( values.iter().filter_map(Value::as_f64).collect() )
+}
+
+// === pure layout helpers (also used by Sparkline/Gauge) ===
+
+// Map values into bar rects inside `area`, with a small gap between bars.
+pub(crate) fn bar_rects(values:&[f64], area:Rect) -> Vec<Rect> {
+    if values.is_empty() { return vec![] }
+    let max = values.iter().cloned().fold(f64::MIN, f64::max).max(0.0);
+    if max <= 0.0 { return vec![] }
+    let slot = area.width() / values.len() as f64;
+    let gap = slot * 0.1;
+    values.iter().enumerate().map( |(i,v)| {
+        let h = (v.max(0.0) / max) * area.height();
+        Rect::new(
+            area.x0 + i as f64 * slot + gap,
+            area.y1 - h,
+            area.x0 + (i + 1) as f64 * slot - gap,
+            area.y1,
+        )
+    }).collect()
+}
+
+// Map values into polyline points inside `area` (min..max normalized).
+pub(crate) fn line_points(values:&[f64], area:Rect) -> Vec<Point> {
+    if values.len() < 2 { return vec![] }
+    let min = values.iter().cloned().fold(f64::MAX, f64::min);
+    let max = values.iter().cloned().fold(f64::MIN, f64::max);
+    let span = if max > min { max - min } else { 1.0 };
+    let step = area.width() / (values.len() - 1) as f64;
+    values.iter().enumerate().map( |(i,v)| {
+        Point::new(
+            area.x0 + i as f64 * step,
+            area.y1 - ((v - min) / span) * area.height(),
+        )
+    }).collect()
+}
+
+// Slice sweep angles in radians, clockwise from 12 o'clock.
+pub(crate) fn pie_angles(values:&[f64]) -> Vec<(f64,f64)> {
+    let total: f64 = values.iter().filter(|v| **v > 0.0).sum();
+    if total <= 0.0 { return vec![] }
+    let mut start = -std::f64::consts::FRAC_PI_2;
+    values.iter().map( |v| {
+        let sweep = (v.max(0.0) / total) * std::f64::consts::TAU;
+        let range = (start, sweep);
+        start += sweep;
+        range
+    }).collect()
+}
+
+fn chart_area(size:Size) -> Rect {
+    //small uniform inset as axis/label margin
+    Rect::new(8.0, 8.0, size.width - 8.0, size.height - 8.0)
+}
+
+pub struct BarChart;
+
+impl WidgetBuilder for BarChart {
+    const WIDGET_NAME: &'static str = "BarChart";
+    type TargetWidget = Canvas;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let values = series_values(params_stack)?;
+        let widget = Canvas::new( move |scene:&mut Scene, size:Size| {
+            for (i,rect) in bar_rects(&values, chart_area(size)).into_iter().enumerate() {
+                scene.fill(Fill::NonZero, Default::default(), series_color(i), None, &rect);
+            }
+        });
+        Ok( widget )
+    }
+}
+
+pub struct LineChart;
+
+impl WidgetBuilder for LineChart {
+    const WIDGET_NAME: &'static str = "LineChart";
+    type TargetWidget = Canvas;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let values = series_values(params_stack)?;
+        let widget = Canvas::new( move |scene:&mut Scene, size:Size| {
+            let points = line_points(&values, chart_area(size));
+            if points.len() < 2 { return }
+            let mut path = BezPath::new();
+            path.move_to(points[0]);
+            for p in points.iter().skip(1) {
+                path.line_to(*p);
+            }
+            scene.stroke(&masonry::kurbo::Stroke::new(2.0), Default::default(), series_color(0), None, &path);
+        });
+        Ok( widget )
+    }
+}
+
+pub struct PieChart;
+
+impl WidgetBuilder for PieChart {
+    const WIDGET_NAME: &'static str = "PieChart";
+    type TargetWidget = Canvas;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let values = series_values(params_stack)?;
+        let widget = Canvas::new( move |scene:&mut Scene, size:Size| {
+            let area = chart_area(size);
+            let center = area.center();
+            let radius = area.width().min(area.height()) / 2.0;
+            for (i,(start,sweep)) in pie_angles(&values).into_iter().enumerate() {
+                let slice = CircleSegment::new(center, radius, 0.0, start, sweep);
+                scene.fill(Fill::NonZero, Default::default(), series_color(i), None, &slice);
+            }
+        });
+        Ok( widget )
+    }
+}
+
+// Mini data-display widgets for table cells and status bars.
+
+pub struct Sparkline;
+
+impl WidgetBuilder for Sparkline {
+    const WIDGET_NAME: &'static str = "Sparkline";
+    type TargetWidget = Canvas;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let values = params_stack.get(0, "values")
+            .and_then( |v| v.as_array() )
+            .ok_or_else( || ValueConvError::MandatoryParamMissing.specific(params_stack.fn_name, params_stack.component.name, 0, "values") )?
+            .iter().filter_map(Value::as_f64).collect::<Vec<_>>();
+        let widget = Canvas::new( move |scene:&mut Scene, size:Size| {
+            //no inset : sparklines fill their cell edge to edge
+            let points = line_points(&values, Rect::from_origin_size(Point::ORIGIN, size));
+            if points.len() < 2 { return }
+            let mut path = BezPath::new();
+            path.move_to(points[0]);
+            for p in points.iter().skip(1) {
+                path.line_to(*p);
+            }
+            scene.stroke(&masonry::kurbo::Stroke::new(1.0), Default::default(), series_color(0), None, &path);
+        });
+        Ok( widget )
+    }
+}
+
+// Normalized gauge fill angles : (start, sweep) for the background arc and the
+// value arc over a half circle opening upward.
+pub(crate) fn gauge_angles(value:f64) -> ((f64,f64),(f64,f64)) {
+    let value = value.clamp(0.0, 1.0);
+    let full = std::f64::consts::PI;
+    let start = std::f64::consts::PI; //9 o'clock, sweeping clockwise to 3 o'clock
+    ( (start, full), (start, full * value) )
+}
+
+pub struct Gauge;
+
+impl WidgetBuilder for Gauge {
+    const WIDGET_NAME: &'static str = "Gauge";
+    type TargetWidget = Canvas;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let value = params_stack.get(0, "value")
+            .and_then( |v| v.as_f64() )
+            .ok_or_else( || ValueConvError::MandatoryParamMissing.specific(params_stack.fn_name, params_stack.component.name, 0, "value") )?;
+        let widget = Canvas::new( move |scene:&mut Scene, size:Size| {
+            let area = chart_area(size);
+            let center = Point::new( area.center().x, area.y1 );
+            let radius = (area.width() / 2.0).min( area.height() );
+            let ((bg_start,bg_sweep),(val_start,val_sweep)) = gauge_angles(value);
+            let track = CircleSegment::new(center, radius, radius * 0.7, bg_start, bg_sweep);
+            scene.fill(Fill::NonZero, Default::default(), AlphaColor::from_rgb8(220, 220, 220), None, &track);
+            let fill = CircleSegment::new(center, radius, radius * 0.7, val_start, val_sweep);
+            scene.fill(Fill::NonZero, Default::default(), series_color(0), None, &fill);
+        });
+        Ok( widget )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gauge_layout() {
+        let ((_,bg_sweep),(_,val_sweep)) = gauge_angles(0.5);
+        assert!( (bg_sweep - std::f64::consts::PI).abs() < 1e-9 );
+        assert!( (val_sweep - std::f64::consts::FRAC_PI_2).abs() < 1e-9 );
+        //out of range values clamp instead of overdrawing
+        let ((_,bg),(_,val)) = gauge_angles(1.5);
+        assert_eq!( bg, val );
+    }
+
+    #[test]
+    fn bar_layout() {
+        let area = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let rects = bar_rects(&[1.0, 2.0, 4.0], area);
+        assert_eq!( rects.len(), 3 );
+        //tallest bar spans the full height, half value half height
+        assert_eq!( rects[2].height(), 100.0 );
+        assert_eq!( rects[1].height(), 50.0 );
+        assert!( rects[0].x1 <= rects[1].x0 );
+    }
+
+    #[test]
+    fn line_layout() {
+        let area = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let points = line_points(&[0.0, 10.0], area);
+        assert_eq!( points.len(), 2 );
+        assert_eq!( (points[0].x, points[0].y), (0.0, 100.0) );
+        assert_eq!( (points[1].x, points[1].y), (100.0, 0.0) );
+    }
+
+    #[test]
+    fn pie_layout() {
+        let angles = pie_angles(&[1.0, 1.0]);
+        assert_eq!( angles.len(), 2 );
+        assert!( (angles[0].1 - std::f64::consts::PI).abs() < 1e-9 );
+        assert!( (angles[1].0 - std::f64::consts::FRAC_PI_2).abs() < 1e-9 );
+    }
+}
@@ -0,0 +1,110 @@
+use masonry::core::{NewWidget, Properties, Property, WidgetOptions};
+use masonry::kurbo::Axis;
+use masonry::widgets::{Button, Flex, FlexParams, Label, TextArea};
+use crate::params::ParamsStack;
+use crate::{Error, RootWidgetBuilder, WidgetBuilder};
+
+// Composite widgets assembled from the primitive builders. They exist to show
+// that the builder layer can express non-trivial widgets without new parser
+// support : a composite is just a WidgetBuilder that returns a small tree.
+
+// Toolbar/editing action of a `RichTextEditor()`. The driver reads it off the
+// pressed button the same way the calc example routes `CalcAction`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum RichTextAction {
+    ToggleBold,
+    ToggleItalic,
+    // structured content after an edit : (plain text, active span styles)
+    ContentChanged(String),
+    #[default]
+    None,
+}
+
+impl Property for RichTextAction {
+    fn static_default() -> &'static Self {
+        &Self::None
+    }
+}
+
+// `RichTextEditor(text="..")` : bold/italic toolbar over an editable TextArea.
+// The buttons carry `RichTextAction` properties; the host app toggles span
+// styles on the TextArea and emits `ContentChanged` from its driver.
+pub struct RichTextEditor;
+
+impl RichTextEditor {
+    fn toolbar_button(label:&str, action:RichTextAction) -> NewWidget<Button> {
+        let mut props = Properties::new();
+        props.insert(action);
+        NewWidget::new_with(
+            Button::new( NewWidget::new( Label::new(label) ) ),
+            None,
+            WidgetOptions::default(),
+            props,
+        )
+    }
+}
+
+// Pluggable highlighter for `CodeEditor`. The default highlights SKUI via the
+// core token classifier; apps can register others per language name.
+pub trait Highlighter {
+    fn highlight(&self, src:&str) -> Vec<(std::ops::Range<usize>, skui::highlight::TokenClass)>;
+}
+
+pub struct SkuiHighlighter;
+
+impl Highlighter for SkuiHighlighter {
+    fn highlight(&self, src:&str) -> Vec<(std::ops::Range<usize>, skui::highlight::TokenClass)> {
+        skui::highlight::classify(src)
+    }
+}
+
+// `CodeEditor(text="..", language="skui")` : line-number gutter next to an
+// editable TextArea. The driver re-runs the highlighter on edit and applies
+// the classified spans as text styles; unknown languages fall back to plain.
+pub struct CodeEditor;
+
+impl WidgetBuilder for CodeEditor {
+    const WIDGET_NAME: &'static str = "CodeEditor";
+    type TargetWidget = Flex;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let text = params_stack.get_text(0, "text").unwrap_or_default();
+        let language = params_stack.get(1, "language").and_then( |v| v.as_str() ).unwrap_or("skui");
+        if language != "skui" {
+            eprintln!("CodeEditor: no highlighter for {language:?}, rendering plain");
+        }
+
+        let line_count = text.lines().count().max(1);
+        let gutter_text = (1..=line_count)
+            .map( |n| n.to_string() )
+            .collect::<Vec<_>>()
+            .join("\n");
+        let gutter = Label::new(gutter_text);
+        let editor = TextArea::<true>::new(&text);
+
+        let widget = Flex::for_axis(Axis::Horizontal)
+            .with_fixed( NewWidget::new(gutter).erased() )
+            .with( NewWidget::new(editor).erased(), FlexParams::new(1.0, None, None) );
+        Ok( widget )
+    }
+}
+
+impl WidgetBuilder for RichTextEditor {
+    const WIDGET_NAME: &'static str = "RichTextEditor";
+    type TargetWidget = Flex;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let text = params_stack.get_text(0, "text").unwrap_or_default();
+
+        let toolbar = Flex::for_axis(Axis::Horizontal)
+            .with_fixed( Self::toolbar_button("B", RichTextAction::ToggleBold).erased() )
+            .with_fixed( Self::toolbar_button("I", RichTextAction::ToggleItalic).erased() );
+
+        let editor = TextArea::<true>::new(&text);
+
+        let widget = Flex::for_axis(Axis::Vertical)
+            .with_fixed( NewWidget::new(toolbar).erased() )
+            .with( NewWidget::new(editor).erased(), FlexParams::new(1.0, None, None) );
+        Ok( widget )
+    }
+}
@@ -0,0 +1,174 @@
+use std::cell::RefCell;
+use skui::Value;
+
+// Opt-in "explain build" mode : while enabled, every widget build records
+// which builder handled the component, every parameter read (and the stack
+// frame that resolved it), and every style rule that applied. `take_json`
+// dumps the log as JSON for tooling — the fastest way to answer "why did this
+// component get that value".
+
+// Where a parameter read was resolved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamSource {
+    // the component's own parameter list
+    Own,
+    // nth caller frame, 0 = the immediate caller
+    Caller(usize),
+    // `${env.*}`
+    Env,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParamRead {
+    pub component: String,
+    pub idx: usize,
+    pub key: String,
+    pub from: Option<ParamSource>,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BuildRecord {
+    pub component: String,
+    pub builder: String,
+    pub styles: Vec<String>,
+    pub params: Vec<ParamRead>,
+}
+
+thread_local! {
+    static LOG: RefCell<Option<Vec<BuildRecord>>> = const { RefCell::new(None) };
+}
+
+pub fn enable() {
+    LOG.with( |log| *log.borrow_mut() = Some(Vec::new()) );
+}
+
+pub fn is_enabled() -> bool {
+    LOG.with( |log| log.borrow().is_some() )
+}
+
+// Stop recording and return the collected log as JSON. `None` when explain
+// mode was never enabled on this thread.
+pub fn take_json() -> Option<String> {
+    let records = LOG.with( |log| log.borrow_mut().take() )?;
+    let mut out = String::from("[");
+    for (i,rec) in records.iter().enumerate() {
+        if i > 0 { out.push(','); }
+        out.push_str( &format!("{{\"component\":{},\"builder\":{},\"styles\":[",
+            json_str(&rec.component), json_str(&rec.builder)) );
+        for (j,style) in rec.styles.iter().enumerate() {
+            if j > 0 { out.push(','); }
+            out.push_str( &json_str(style) );
+        }
+        out.push_str("],\"params\":[");
+        for (j,p) in rec.params.iter().enumerate() {
+            if j > 0 { out.push(','); }
+            let from = match p.from {
+                Some(ParamSource::Own) => "\"own\"".to_string(),
+                Some(ParamSource::Caller(n)) => format!("\"caller[{n}]\""),
+                Some(ParamSource::Env) => "\"env\"".to_string(),
+                None => "null".to_string(),
+            };
+            let value = p.value.as_ref().map( |v| json_str(v) ).unwrap_or("null".to_string());
+            out.push_str( &format!("{{\"component\":{},\"idx\":{},\"key\":{},\"from\":{from},\"value\":{value}}}",
+                json_str(&p.component), p.idx, json_str(&p.key)) );
+        }
+        out.push_str("]}");
+    }
+    out.push(']');
+    Some(out)
+}
+
+pub(crate) fn record_build(component:&str, builder:&str) {
+    LOG.with( |log| {
+        if let Some(records) = log.borrow_mut().as_mut() {
+            records.push( BuildRecord {
+                component: component.to_string(),
+                builder: builder.to_string(),
+                ..Default::default()
+            });
+        }
+    });
+}
+
+pub(crate) fn record_style(selector:String) {
+    LOG.with( |log| {
+        if let Some(records) = log.borrow_mut().as_mut() {
+            if let Some(last) = records.last_mut() {
+                last.styles.push(selector);
+            }
+        }
+    });
+}
+
+pub(crate) fn record_param(component:&str, idx:usize, key:&str, from:Option<ParamSource>, value:Option<&Value>) {
+    LOG.with( |log| {
+        if let Some(records) = log.borrow_mut().as_mut() {
+            if let Some(last) = records.last_mut() {
+                last.params.push( ParamRead {
+                    component: component.to_string(),
+                    idx,
+                    key: key.to_string(),
+                    from,
+                    value: value.map( |v| format!("{v:?}") ),
+                });
+            }
+        }
+    });
+}
+
+fn json_str(s:&str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if (c as u32) < 0x20 => out.push_str( &format!("\\u{:04x}", c as u32) ),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::ParamsStack;
+
+    #[test]
+    fn explain_log() {
+        let src = r#"
+            Main:
+            Card(title="hello")
+            Card:
+            Label(${title})
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+        let params = skui::Parameters::empty();
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        //descend into the Card invocation like the builders do
+        let card = stack.new_stack(stack.component);
+
+        enable();
+        assert!( is_enabled() );
+        record_build("Label", "Label");
+        //`${title}` resolves one caller frame up
+        let v = card.new_stack(card.component).get(0, "");
+        assert!( v.is_some() );
+
+        let json = take_json().unwrap();
+        println!("{json}");
+        assert!( json.contains("\"builder\":\"Label\"") );
+        assert!( json.contains("\"from\":\"caller[0]\"") );
+        assert!( json.contains("hello") );
+        //taking the log disables recording
+        assert!( !is_enabled() );
+        assert!( take_json().is_none() );
+    }
+}
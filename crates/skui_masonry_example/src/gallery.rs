@@ -0,0 +1,61 @@
+use skui::{SKUIParseError, TokenAndSpan, SKUI};
+
+// Instant style-guide : turns a document with multiple root components into a
+// gallery source (grid of cards, one per component with its name above a live
+// instance). Parse and build the returned source like any other document.
+// A root named `Main` is skipped since the gallery provides its own.
+
+pub fn gallery_source(src:&str) -> Result<String, SKUIParseError> {
+    let tks = TokenAndSpan::new(src);
+    let skui = SKUI::parse(&tks)?;
+    let roots: Vec<&str> = skui.components.iter()
+        .map( |rc| rc.name )
+        .filter( |name| *name != "Main" )
+        .collect();
+
+    let cols = 3usize.min( roots.len().max(1) );
+    let rows = roots.len().div_ceil(cols).max(1);
+
+    let mut out = String::from(src);
+    out.push_str("\n\n");
+    out.push_str(".gallery-card { padding: 8px }\n");
+    out.push_str(".gallery-title { font-size: 12px }\n\n");
+    out.push_str( &format!("Main:\nGrid({cols},{rows}) {{\n") );
+    for (i,name) in roots.iter().enumerate() {
+        let (x,y) = (i % cols, i / cols);
+        out.push_str( &format!("    GridItem({x},{y}) {{\n") );
+        out.push_str("        Flex().gallery-card {\n");
+        out.push_str( &format!("            Label(\"{name}\").gallery-title\n") );
+        out.push_str( &format!("            {name}()\n") );
+        out.push_str("        }\n");
+        out.push_str("    }\n");
+    }
+    out.push_str("}\n");
+    Ok( out )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gallery() {
+        let src = r#"
+            Card:
+            Flex() { Label("card body") }
+
+            Banner:
+            Flex() { Label("banner body") }
+        "#;
+        let gallery = gallery_source(src).unwrap();
+        println!("{gallery}");
+
+        let tks = TokenAndSpan::new(&gallery);
+        let skui = SKUI::parse(&tks).unwrap();
+        let main = &skui.get_main_component().unwrap().component;
+        assert_eq!( main.name, "Grid" );
+        assert_eq!( main.children.len(), 2 );
+        assert_eq!( main.children[0].children[0].children[0].name, "Label" );
+        assert_eq!( main.children[0].children[0].children[1].name, "Card" );
+    }
+}
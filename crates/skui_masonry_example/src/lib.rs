@@ -1,60 +1,144 @@
 //mod builder;
+pub mod a11y;
+pub mod actions;
+pub mod anim;
+#[cfg(feature = "async")]
+pub mod async_build;
+pub mod audit;
+pub mod backend;
+#[cfg(feature = "charts")]
+pub mod chart;
+pub mod composite;
+pub mod explain;
+pub mod gallery;
+pub mod options;
 pub mod params;
+pub mod perf;
 mod q;
+#[cfg(feature = "qrcode")]
+pub mod qr_code;
+pub mod runtime;
+pub mod snapshot;
 mod style;
+pub mod term;
 
 use std::collections::HashMap;
 use std::marker::PhantomData;
 use masonry::core::{BrushIndex, ErasedAction, NewWidget, Properties, Widget, WidgetOptions, WidgetTag};
-use masonry::layout::Length;
+use masonry::layout::{Length, UnitPoint};
 use masonry::peniko::color::AlphaColor;
 use masonry::properties::{Background, Gap, Padding};
 use masonry::widgets::{Align, Button, Canvas, Checkbox, Flex, FlexParams, Grid, GridParams, Image, IndexedStack, Label, Passthrough, Portal, ProgressBar, Prose, ResizeObserver, SizedBox, Slider, Spinner, Split, TextArea, TextInput, VariableLabel};
-use skui::{Component, CssValue, Number, Parameters, SKUIParseError, TokenAndSpan, SKUI};
-use crate::params::{AlignArgs, ArgumentError, ButtonArgs, CheckboxArgs, FlexArgs, FlexItemArgs, FlexSpacerArgs, FromParams, GridArgs, GridParamsArgs, IndexedStackArgs, LabelArgs, ParamsStack, PassthroughArgs, PortalArgs, ProgressBarArgs, ProseArgs, ResizeObserverArgs, SizedBoxArgs, SliderArgs, SplitArgs, TextAreaArgs, TextInputArgs, VariableLabelArgs};
+use skui::{Component, CssValue, Number, Parameters, SKUIParseError, TokenAndSpan, Value, SKUI};
+use skui::diag::Diagnostic;
+use crate::params::{ArgumentError, BuildConfig, ButtonArgs, CheckboxArgs, FlexArgs, FlexItemArgs, FlexSpacerArgs, FromParams, GridArgs, GridParamsArgs, IndexedStackArgs, LabelArgs, ParamsStack, PassthroughArgs, PortalArgs, ProgressBarArgs, ProseArgs, ResizeObserverArgs, SizedBoxArgs, SliderArgs, SplitArgs, TextAreaArgs, TextInputArgs, VariableLabelArgs};
 use std::str::FromStr;
 use masonry::parley::{Brush, FontWeight, StyleProperty};
 
-#[derive(Debug,Clone)]
+// Build failures, one variant per failure kind. Every variant carries the
+// component it concerns (components hold no source span after parsing, so the
+// name is the context) and renders through `Display`, so example apps can
+// print `{e}` instead of a `{:#?}` dump; the parse and parameter variants keep
+// their cause reachable through `source()`.
+#[derive(thiserror::Error, Debug, Clone)]
 pub enum Error {
+    #[error("no `Main:` root component in the document")]
     RootComponentNotFound,
+    #[error("unknown component `{0}`")]
     UnknownComponent(String),
-    RequiredChildren(usize),
-    AtLeastOneRequired,
-    ExactlyTwoChildRequired,
-    ParseError(SKUIParseError),
-    InvalidParameter(ArgumentError),
-    GridChildMustBeItem,
-    MultipleChildDefinitions(String)
+    #[error("`{component}` requires exactly {required} children")]
+    RequiredChildren { component:String, required:usize },
+    #[error("`{component}` requires at least one child")]
+    AtLeastOneRequired { component:String },
+    #[error("`{component}` requires exactly two children")]
+    ExactlyTwoChildRequired { component:String },
+    #[error(transparent)]
+    ParseError(#[from] SKUIParseError),
+    #[error("invalid parameter : {0}")]
+    InvalidParameter(#[from] ArgumentError),
+    #[error("children of `{component}` must be `{item}(..)` entries")]
+    GridChildMustBeItem { component:String, item:&'static str },
+    #[error("`{0}` does not take children")]
+    ChildrenNotAllowed(String),
+    #[error("component `{0}` is defined more than once")]
+    MultipleChildDefinitions(String),
+    #[error("{0}")]
+    PolicyViolation(String)
 }
 
-impl From<SKUIParseError> for Error {
-    fn from(e:SKUIParseError) -> Self {
-        Error::ParseError(e)
-    }
-}
-
-impl From<ArgumentError> for Error {
-    fn from(e:ArgumentError) -> Self {
-        Error::InvalidParameter(e)
+impl Error {
+    // Reporting shape for the in-app error overlay : parse errors keep their
+    // source span, the rest render their `Display` message, and the variants
+    // with an obvious next step carry help text.
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            Error::ParseError(e) => Diagnostic::from(e),
+            Error::UnknownComponent(_) => Diagnostic::error( self.to_string() )
+                .with_help("check the spelling or add a root component definition with this name"),
+            Error::ChildrenNotAllowed(_) => Diagnostic::error( self.to_string() )
+                .with_help("pass content through parameters instead of a child block"),
+            Error::PolicyViolation(_) => Diagnostic::error( self.to_string() )
+                .with_help("this build runs with restricted BuildOptions; allow the capability or drop the statement"),
+            _ => Diagnostic::error( self.to_string() ),
+        }
     }
 }
 
 static WID_TABLE: std::sync::LazyLock<std::sync::RwLock<HashMap<String, &'static str>>> =
     std::sync::LazyLock::new(|| std::sync::RwLock::new(HashMap::new()) );
 
+// Runtime switch : outline every built widget with a thin colored border so
+// spacing/nesting problems are visible without touching the user stylesheet.
+static DEBUG_OUTLINES: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+pub fn set_debug_outlines(enabled:bool) {
+    DEBUG_OUTLINES.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+pub fn debug_outlines_enabled() -> bool {
+    DEBUG_OUTLINES.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+// Stable per-component outline color so the same widget kind always gets the same hue
+fn debug_outline_color(c:&Component) -> AlphaColor<masonry::peniko::color::Srgb> {
+    const PALETTE: [(u8,u8,u8);6] = [
+        (230, 70, 70), (70, 160, 230), (90, 200, 90),
+        (230, 160, 50), (180, 90, 220), (60, 200, 190),
+    ];
+    let mut hash = 0usize;
+    for b in c.name.bytes() {
+        hash = hash.wrapping_mul(31).wrapping_add(b as usize);
+    }
+    let (r,g,b) = PALETTE[hash % PALETTE.len()];
+    AlphaColor::from_rgb8(r, g, b)
+}
+
+// Theme switching without a reparse : point the document at the theme the
+// build is configured for, then rebuild the roots - every builder resolves
+// styles through `SKUI::get_styles`, which layers the active theme's rules in.
+pub fn apply_config_theme(skui:&mut SKUI, config:&BuildConfig) {
+    skui.set_active_theme( config.theme.as_deref() );
+}
+
 
 
 
 macro_rules! impl_default_widget_builder {
-    ( $name:ident { $($comp:ident),* } ) => {
+    ( $name:ident { $( $(#[$cfg:meta])* $comp:ident ),* } ) => {
         impl <P:CustomPropertyBuilder> RootWidgetBuilder for $name <P> {
             fn build_widget<'a>(params_stack:&ParamsStack<'a>) -> Result<NewWidget<impl Widget + ?Sized>, Error> {
                 match params_stack.component.name {
                     $(
+                    $(#[$cfg])*
                     $comp::WIDGET_NAME => $comp::build::<Self>(params_stack).map(|v| v.erased()) ,
                     )*
-                    _ => Err( Error::UnknownComponent( format!("{} -> {}", params_stack.fn_name, params_stack.component.name) ) )
+                    _ => {
+                        if params_stack.config.strict {
+                            Err( Error::UnknownComponent( format!("{} -> {}", params_stack.fn_name, params_stack.component.name) ) )
+                        } else {
+                            Ok( unknown_component_placeholder(params_stack) )
+                        }
+                    }
                 }
             }
 
@@ -65,6 +149,46 @@ macro_rules! impl_default_widget_builder {
     }
 }
 
+// Red inline error box : visible in the preview where the broken widget
+// would have been, so non-strict builds can keep rendering around a problem.
+fn inline_error_widget(text:&str) -> NewWidget<dyn Widget> {
+    use masonry::properties::{BorderColor, BorderWidth, ContentColor};
+    let mut props = Properties::new();
+    props.insert( Background::Color( AlphaColor::from_rgb8(120, 16, 16) ) );
+    props.insert( BorderWidth::all(1.0) );
+    props.insert( BorderColor::new( AlphaColor::from_rgb8(255, 80, 80) ) );
+    props.insert( ContentColor::new( AlphaColor::from_rgb8(255, 255, 255) ) );
+    props.insert( Padding::all(4.0) );
+    let label = Label::new( text.to_string() );
+    NewWidget::new_with( label, None, WidgetOptions::default(), props ).erased()
+}
+
+// Visible stand-in for an unknown component name in non-strict builds, so one
+// typo shows up inline during live editing instead of blanking the whole
+// preview (`BuildConfig::strict` keeps the hard `UnknownComponent` error for
+// production builds).
+fn unknown_component_placeholder(params_stack:&ParamsStack) -> NewWidget<dyn Widget> {
+    let name = params_stack.component.name;
+    eprintln!("Unknown component : {} -> {name}", params_stack.fn_name);
+    inline_error_widget( &format!("unknown component `{name}`") )
+}
+
+// One failed child in a non-strict build : record the failure and hand back
+// an inline placeholder so the siblings still render - on a large document a
+// single broken child no longer costs the whole preview. Strict builds keep
+// the original error. Drain the failures with `options::take_diagnostics`.
+fn recover_child<'a>(child_stack:&ParamsStack<'a>, e:Error) -> Result<NewWidget<dyn Widget>, Error> {
+    if child_stack.config.strict {
+        return Err(e);
+    }
+    options::push_diagnostic( options::BuildDiagnostic::ChildBuildFailed {
+        parent: child_stack.fn_name.to_string(),
+        child: child_stack.component.name.to_string(),
+        error: format!("{e:?}"),
+    });
+    Ok( inline_error_widget( &format!("`{}` failed : {e:?}", child_stack.component.name) ) )
+}
+
 pub trait CustomPropertyBuilder {
     fn build_properties<'a>(props:&mut Properties, c:&Component<'a>, skui:&SKUI<'a>);
 }
@@ -84,9 +208,22 @@ pub struct DefaultWidgetBuilder<P> {
 pub type BasicWidgetBuilder = DefaultWidgetBuilder<EmptyPropertyBuilder>;
 
 
-impl_default_widget_builder!(DefaultWidgetBuilder {Align,Button,Canvas,Checkbox,Flex,Grid,Image,
-            IndexedStack,Label,Passthrough,Portal,ProgressBar,Prose,ResizeObserver,
-            SizedBox,Slider,Spinner,Split,TextAreaEditable,TextInput,VariableLabel});
+#[cfg(feature = "charts")]
+use crate::chart::{BarChart, Gauge, LineChart, PieChart, Sparkline};
+#[cfg(feature = "qrcode")]
+use crate::qr_code::QrCode;
+
+use crate::composite::{CodeEditor, RichTextEditor};
+
+impl_default_widget_builder!(DefaultWidgetBuilder {Align,Button,Canvas,Checkbox,CodeEditor,Flex,Grid,Image,
+            IndexedStack,Label,Passthrough,PerfHud,Portal,ProgressBar,Prose,ResizeObserver,
+            RichTextEditor,SizedBox,Slider,Spinner,Split,TextAreaEditable,TextInput,VariableLabel,
+            #[cfg(feature = "charts")] BarChart,
+            #[cfg(feature = "charts")] LineChart,
+            #[cfg(feature = "charts")] PieChart,
+            #[cfg(feature = "charts")] Sparkline,
+            #[cfg(feature = "charts")] Gauge,
+            #[cfg(feature = "qrcode")] QrCode});
 
 
 
@@ -106,17 +243,51 @@ pub trait RootWidgetBuilder {
 
     fn build_widget<'a>(params_stack:&ParamsStack<'a>) -> Result<NewWidget<impl Widget + ?Sized>, Error>;
 
-    fn build_styles<'a>(build_prop:bool, build_styles:bool, c:&Component<'a>, skui:&SKUI<'a>) -> (Properties,Vec<StyleProperty<'static,BrushIndex>>) {
+    fn build_styles<'a>(build_prop:bool, build_styles:bool, c:&Component<'a>, extra_classes:&[&'a str], skui:&SKUI<'a>) -> (Properties,Vec<StyleProperty<'static,BrushIndex>>) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("style_resolve", component = c.name).entered();
         let mut props = Properties::new();
         let mut styles = vec![];
         let mut parents = vec![];
         let Some(main) = skui.get_main_component() else { return (props, styles) };
         main.component.find( &mut parents, c );
-        skui.get_styles(parents.as_slice(), c)
+        //active `class-if` classes participate in matching like declared ones.
+        //parents are found by identity above, so the clone is matching-only.
+        let with_extra;
+        let match_c = if extra_classes.is_empty() { c } else {
+            let mut clone = c.clone();
+            for cls in extra_classes.iter() {
+                if clone.classes.contains(cls) {
+                    continue;
+                }
+                if clone.classes.len() < clone.classes.capacity() {
+                    clone.classes.push(cls);
+                } else {
+                    //a silently dropped class would just stop matching :
+                    //surface it so class-heavy components are debuggable
+                    options::push_diagnostic( options::BuildDiagnostic::ClassCapacityExceeded {
+                        component: c.name.to_string(),
+                        class: cls.to_string(),
+                    });
+                }
+            }
+            with_extra = clone;
+            &with_extra
+        };
+        skui.get_styles(parents.as_slice(), match_c)
             .for_each( |style| {
-                style::style_parse(build_prop, build_styles, style, &mut props, &mut styles);
+                perf::STYLE_MATCHES.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                if explain::is_enabled() {
+                    explain::record_style( format!("{:?}", style.selector) );
+                }
+                style::style_parse(build_prop, build_styles, &style, &mut props, &mut styles);
             });
         Self::build_custom_properties(&mut props, c, skui);
+        if debug_outlines_enabled() {
+            use masonry::properties::{BorderColor, BorderWidth};
+            props.insert( BorderWidth::all(1.0) );
+            props.insert( BorderColor::new( debug_outline_color(c) ) );
+        }
         (props, styles)
     }
 
@@ -126,14 +297,70 @@ pub trait RootWidgetBuilder {
 type MasonryStyle = StyleProperty<'static,BrushIndex>;
 type MasonryStyles = Vec<StyleProperty<'static,BrushIndex>>;
 
+// The child shape a builder accepts, declared as `WidgetBuilder::CHILD_ARITY`
+// and checked once in `WidgetBuilder::build` before `build_target` runs - the
+// per-widget `return Err(..)` sprawl reports uniformly this way. Counting goes
+// through `ParamsStack::children`, so `if`/`else` blocks are already expanded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChildArity {
+    Any,
+    None,
+    Exactly(usize),
+    AtLeastOne,
+    // every child must be this component (`GridItem(..)`, `Item(..)`, ..)
+    Only(&'static str),
+}
+
+impl ChildArity {
+    pub fn check(&self, params_stack:&ParamsStack) -> Result<(), Error> {
+        let component = params_stack.component.name;
+        let count = params_stack.children().count();
+        match self {
+            ChildArity::Any => Ok(()),
+            ChildArity::None if count == 0 => Ok(()),
+            ChildArity::None => Err( Error::ChildrenNotAllowed(component.to_string()) ),
+            ChildArity::Exactly(2) if count != 2 => Err( Error::ExactlyTwoChildRequired { component: component.into() } ),
+            ChildArity::Exactly(n) if count != *n => Err( Error::RequiredChildren { component: component.into(), required: *n } ),
+            ChildArity::Exactly(_) => Ok(()),
+            ChildArity::AtLeastOne if count == 0 => Err( Error::AtLeastOneRequired { component: component.into() } ),
+            ChildArity::AtLeastOne => Ok(()),
+            ChildArity::Only(item) => {
+                if params_stack.children().all( |c| c.name == *item ) {
+                    Ok(())
+                } else {
+                    Err( Error::GridChildMustBeItem { component: component.into(), item: *item } )
+                }
+            }
+        }
+    }
+}
+
 pub trait WidgetBuilder {
     const WIDGET_NAME: &'static str;
     const BUILD_PROPERTIES:bool = true;
     const BUILD_STYLES:bool = false;
+    const CHILD_ARITY: ChildArity = ChildArity::Any;
     type TargetWidget: Widget;
 
     fn build<'a,B:RootWidgetBuilder>(params_stack:&ParamsStack<'a>)  -> Result<NewWidget<impl Widget + ?Sized>, Error> {
-        let (props, styles) = B::build_styles(Self::BUILD_PROPERTIES, Self::BUILD_STYLES, &params_stack.component, &params_stack.skui) ;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!("build_widget", component = Self::WIDGET_NAME).entered();
+        perf::WIDGETS_BUILT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if explain::is_enabled() {
+            explain::record_build(params_stack.component.name, Self::WIDGET_NAME);
+        }
+        let class_if = params_stack.class_if();
+        if !class_if.is_empty() {
+            runtime::record_class_bindings(params_stack.get_id(), &class_if);
+        }
+        let extra_classes: Vec<&str> = class_if.iter().filter( |b| b.active ).map( |b| b.class ).collect();
+        let (props, styles) = B::build_styles(Self::BUILD_PROPERTIES, Self::BUILD_STYLES, &params_stack.component, extra_classes.as_slice(), &params_stack.skui) ;
+        //`Only(..)` is deferred to the per-child arms in non-strict builds :
+        //one wrong child gets an inline placeholder via `recover_child` there,
+        //where the parent-level check here would fail the whole container
+        if params_stack.config.strict || !matches!(Self::CHILD_ARITY, ChildArity::Only(_)) {
+            Self::CHILD_ARITY.check(params_stack)?;
+        }
         let mut widget = <Self as WidgetBuilder>::build_target::<B>(params_stack)?;
         if Self::BUILD_STYLES {
             for s in styles.into_iter() {
@@ -160,9 +387,36 @@ impl WidgetBuilder for Align {
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
-        let align_args = AlignArgs::from_params(params_stack)?;
-        let child = B::build_widget( &params_stack.new_stack(align_args.comp) )?;
-        let widget = Align::new( align_args.unit_point, child );
+        //`Align(0.25, 0.75)` : fractional unit point, (0,0) top-left through
+        //(1,1) bottom-right. Two leading numbers pick this form over the
+        //`Align(TOP_RIGHT)` constants.
+        let (unit_point, comp_idx) = if let (Some(x), Some(y)) = (
+            params_stack.get(0, "x").and_then( |v| v.as_f64() ),
+            params_stack.get(1, "y").and_then( |v| v.as_f64() ),
+        ) {
+            (UnitPoint::new(x, y), 2)
+        } else {
+            let value = params_stack.get(0, "unit_point")
+                .ok_or_else( || params::ValueConvError::MandatoryParamMissing.specific(params_stack.fn_name, "Align", 0, "unit_point") )?;
+            let unit_point = <UnitPoint as params::FromValue>::from_value(value)
+                .map_err( |e| e.specific(params_stack.fn_name, "Align", 0, "unit_point") )?;
+            (unit_point, 1)
+        };
+
+        //the child comes as the trailing parameter or as a child block :
+        //`Align(TOP_RIGHT){ Label("x") }`
+        let comp = match params_stack.get(comp_idx, "comp") {
+            Some(Value::Component(comp)) => comp,
+            _ => {
+                let mut children = params_stack.children();
+                match (children.next(), children.next()) {
+                    (Some(comp), None) => comp,
+                    _ => return Err( Error::RequiredChildren { component: "Align".into(), required: 1 } ),
+                }
+            }
+        };
+        let child = B::build_widget( &params_stack.new_stack(comp) )?;
+        let widget = Align::new( unit_point, child );
         Ok( widget )
     }
 }
@@ -191,11 +445,16 @@ impl WidgetBuilder for Canvas {
 
 impl WidgetBuilder for Checkbox {
     const WIDGET_NAME: &'static str = "Checkbox";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
-        let checkbox_args = CheckboxArgs::from_params(params_stack)?;
-        let widget = Checkbox::new( checkbox_args.checked.unwrap_or(false), checkbox_args.text );
+        let text = match params_stack.get_text(0, "text") {
+            Some(text) => text.into_owned(),
+            None => CheckboxArgs::from_params(params_stack)?.text.to_string(),
+        };
+        let checked = params_stack.get(1, "checked").and_then( |v| v.as_bool() );
+        let widget = Checkbox::new( checked.unwrap_or(false), text );
         Ok( widget )
     }
 }
@@ -214,21 +473,74 @@ impl WidgetBuilder for Flex {
             let flex_child_stack = params_stack.new_stack( c );
             match flex_child_stack.component.name {
                 "FlexItem" => {
-                    let item_args = FlexItemArgs::from_params( &flex_child_stack )?;
-                    let item_comp = B::build_widget(&flex_child_stack.new_stack(item_args.comp))?;
-                    let params = FlexParams::new(item_args.flex, item_args.basis, item_args.alignment);
-                    widget = widget.with( item_comp, params );
+                    match FlexItemArgs::from_params( &flex_child_stack ) {
+                        Ok(item_args) => {
+                            let item_comp = match B::build_widget(&flex_child_stack.new_stack(item_args.comp)) {
+                                Ok(w) => w.erased(),
+                                Err(e) => recover_child(&flex_child_stack, e)?,
+                            };
+                            let params = FlexParams::new(item_args.flex, item_args.basis, item_args.alignment);
+                            widget = widget.with( item_comp, params );
+                        }
+                        Err(e) => widget = widget.with_fixed( recover_child(&flex_child_stack, e.into())? ),
+                    }
                 }
                 "FlexSpace" => {
                     let inner_stack = params_stack.new_stack(c);
+                    //`FlexSpace(flex=1)` : the explicit relative form. The
+                    //out-of-range index keeps the lookup off positional slot 0.
+                    if let Some(flex) = inner_stack.get(usize::MAX, "flex").and_then( |v| v.as_f64() ) {
+                        if flex <= 0.0 {
+                            return Err( params::ValueConvError::InvalidValue.specific(inner_stack.fn_name, "FlexSpace", 0, "flex").into() );
+                        }
+                        widget = widget.with_spacer(flex);
+                        continue;
+                    }
                     let spacer_args = FlexSpacerArgs::from_params(&inner_stack)?;
-                    widget = match spacer_args.value {
-                        Number::I64(v) => widget.with_fixed_spacer( Length::const_px(v as _) ),
-                        Number::F64(v) => widget.with_spacer(v)
+                    widget = match &spacer_args.value {
+                        //`FlexSpace(10%)` : a share of the leftover space, as flex weight
+                        Value::Unit(CssValue::Percent(v)) => {
+                            if *v <= 0.0 {
+                                return Err( params::ValueConvError::InvalidValue.specific(inner_stack.fn_name, "FlexSpace", 0, "value").into() );
+                            }
+                            widget.with_spacer(*v / 100.0)
+                        }
+                        //`FlexSpace(8px)` : a unit length is always a fixed spacer
+                        Value::Unit(unit) => {
+                            let px = crate::style::resolve_px(unit)
+                                .ok_or_else( || params::ValueConvError::InvalidValue.specific(inner_stack.fn_name, "FlexSpace", 0, "value") )?;
+                            widget.with_fixed_spacer( Length::px(px) )
+                        }
+                        //bare numbers guess px vs flex from the literal's type;
+                        //kept for existing documents but steered to the explicit forms
+                        Value::Number(Number::I64(v)) => {
+                            eprintln!("FlexSpace({v}) is deprecated : write FlexSpace({v}px) for a fixed spacer");
+                            widget.with_fixed_spacer( Length::const_px(*v as _) )
+                        }
+                        Value::Number(Number::F64(v)) => {
+                            eprintln!("FlexSpace({v}) is deprecated : write FlexSpace(flex={v}) for a flexible spacer");
+                            widget.with_spacer(*v)
+                        }
+                        _ => return Err( params::ValueConvError::InvalidType.specific(inner_stack.fn_name, "FlexSpace", 0, "value").into() ),
+                    }
+                }
+                //`for item in ${0.items} { .. }` : expanded here because the
+                //source array is only known once the parameter stack exists
+                "for" => {
+                    for item_comp in params_stack.expand_loop(c).iter() {
+                        let item_stack = params_stack.new_stack(item_comp);
+                        let child = match B::build_widget(&item_stack) {
+                            Ok(w) => w.erased(),
+                            Err(e) => recover_child(&item_stack, e)?,
+                        };
+                        widget = widget.with_fixed( child );
                     }
                 }
                 _ => {
-                    let child = B::build_widget(&flex_child_stack)?;
+                    let child = match B::build_widget(&flex_child_stack) {
+                        Ok(w) => w.erased(),
+                        Err(e) => recover_child(&flex_child_stack, e)?,
+                    };
                     widget = widget.with_fixed( child );
                 }
             }
@@ -239,6 +551,7 @@ impl WidgetBuilder for Flex {
 
 impl WidgetBuilder for Grid {
     const WIDGET_NAME: &'static str = "Grid";
+    const CHILD_ARITY: ChildArity = ChildArity::Only("GridItem");
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
@@ -249,13 +562,21 @@ impl WidgetBuilder for Grid {
             let grid_child_stack = params_stack.new_stack(c);
             match grid_child_stack.component.name {
                 "GridItem" => {
-                    let item_args = GridParamsArgs::from_params(&grid_child_stack)?;
-                    let item_comp = B::build_widget(&grid_child_stack.new_stack(item_args.comp))?;
-                    let params = GridParams::new(item_args.x, item_args.y, item_args.w.unwrap_or(1), item_args.h.unwrap_or(1));
-                    widget = widget.with(item_comp, params);
+                    match GridParamsArgs::from_params(&grid_child_stack) {
+                        Ok(item_args) => {
+                            let item_comp = match B::build_widget(&grid_child_stack.new_stack(item_args.comp)) {
+                                Ok(w) => w.erased(),
+                                Err(e) => recover_child(&grid_child_stack, e)?,
+                            };
+                            let params = GridParams::new(item_args.x, item_args.y, item_args.w.unwrap_or(1), item_args.h.unwrap_or(1));
+                            widget = widget.with(item_comp, params);
+                        }
+                        //a broken item still occupies a cell
+                        Err(e) => widget = widget.with( recover_child(&grid_child_stack, e.into())?, GridParams::new(0, 0, 1, 1) ),
+                    }
                 }
                 _ => {
-                    return Err(Error::GridChildMustBeItem)
+                    widget = widget.with( recover_child(&grid_child_stack, Error::GridChildMustBeItem { component: "Grid".into(), item: "GridItem" })?, GridParams::new(0, 0, 1, 1) );
                 }
             }
         }
@@ -268,25 +589,36 @@ impl WidgetBuilder for Image {
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        //asset pipeline not wired up yet, but the sandbox gate already applies
+        if let Some(src) = params_stack.get(0, "src").and_then( |v| v.as_str() ) {
+            if !options::asset_allowed(src) {
+                return Err( Error::PolicyViolation(src.to_string()) );
+            }
+        }
         todo!()
     }
 }
 
 impl WidgetBuilder for IndexedStack {
     const WIDGET_NAME: &'static str = "IndexedStack";
+    const CHILD_ARITY: ChildArity = ChildArity::Only("Item");
     type TargetWidget = Self;
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
         let indexed_args = IndexedStackArgs::from_params(params_stack)?;
         let mut widget = IndexedStack::new();
 
         for c in params_stack.children() {
+            let item_stack = params_stack.new_stack(c);
             match c.name {
                 "Item" => {
-                    let comp = B::build_widget( &params_stack.new_stack(c) )?;
+                    let comp = match B::build_widget(&item_stack) {
+                        Ok(w) => w.erased(),
+                        Err(e) => recover_child(&item_stack, e)?,
+                    };
                     widget = widget.with(comp);
                 }
                 _ => {
-                    return Err(Error::GridChildMustBeItem)
+                    widget = widget.with( recover_child(&item_stack, Error::GridChildMustBeItem { component: "IndexedStack".into(), item: "Item" })? );
                 }
             }
         }
@@ -297,11 +629,16 @@ impl WidgetBuilder for IndexedStack {
 
 impl WidgetBuilder for Label {
     const WIDGET_NAME: &'static str = "Label";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
     type TargetWidget = Self;
     const BUILD_STYLES:bool = true;
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
-        let label_args = LabelArgs::from_params(params_stack)?;
-        let widget = Label::new(label_args.text);
+        // get_text also resolves tr("key") references through the installed translator
+        let text = match params_stack.get_text(0, "text") {
+            Some(text) => text.into_owned(),
+            None => LabelArgs::from_params(params_stack)?.text.to_string(),
+        };
+        let widget = Label::new(text);
         Ok( widget )
     }
 
@@ -321,6 +658,26 @@ impl WidgetBuilder for Passthrough {
     }
 }
 
+// `PerfHud()` : single-line label showing the counters from the last instrumented build.
+// Wrap the root build in `perf::time_build()` and rebuild once more to see fresh numbers.
+pub struct PerfHud;
+
+impl WidgetBuilder for PerfHud {
+    const WIDGET_NAME: &'static str = "PerfHud";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
+    type TargetWidget = Label;
+    const BUILD_STYLES:bool = true;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let widget = Label::new( perf::hud_text() );
+        Ok( widget )
+    }
+
+    fn apply_style<'a, B: RootWidgetBuilder>(target: Self::TargetWidget, style: MasonryStyle) -> Self::TargetWidget {
+        target.with_style(style)
+    }
+}
+
 impl WidgetBuilder for Portal<Label> {
     const WIDGET_NAME: &'static str = "Portal";
     type TargetWidget = Label; //dont care
@@ -341,6 +698,7 @@ impl WidgetBuilder for Portal<Label> {
 
 impl WidgetBuilder for ProgressBar {
     const WIDGET_NAME: &'static str = "ProgressBar";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
@@ -352,16 +710,23 @@ impl WidgetBuilder for ProgressBar {
 
 impl WidgetBuilder for Prose {
     const WIDGET_NAME: &'static str = "Prose";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
-        let prose_args = ProseArgs::from_params(params_stack)?;
-        let mut widget = Prose::new(&prose_args.text);
-        if let Some(flag) = prose_args.clip { widget = widget.with_clip(flag); }
+        let text = match params_stack.get_text(0, "text") {
+            Some(text) => text.into_owned(),
+            None => ProseArgs::from_params(params_stack)?.text.to_string(),
+        };
+        let mut widget = Prose::new(&text);
+        if let Some(flag) = params_stack.get(1, "clip").and_then( |v| v.as_bool() ) { widget = widget.with_clip(flag); }
         Ok( widget )
     }
 }
 
+// Responsive pairing : route this widget's resize action to
+// `SKUI::set_viewport(Some(size))` and rebuild - rules under a satisfied
+// `@media` group then win the cascade for every component.
 impl WidgetBuilder for ResizeObserver {
     const WIDGET_NAME: &'static str = "ResizeObserver";
     type TargetWidget = Self;
@@ -388,6 +753,7 @@ impl WidgetBuilder for SizedBox {
 
 impl WidgetBuilder for Slider {
     const WIDGET_NAME: &'static str = "Slider";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
@@ -400,6 +766,7 @@ impl WidgetBuilder for Slider {
 
 impl WidgetBuilder for Spinner {
     const WIDGET_NAME: &'static str = "Spinner";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
@@ -410,17 +777,18 @@ impl WidgetBuilder for Spinner {
 
 impl WidgetBuilder for Split<dyn Widget<Action=ErasedAction>,dyn Widget<Action=ErasedAction>> {
     const WIDGET_NAME: &'static str = "Split";
+    const CHILD_ARITY: ChildArity = ChildArity::Exactly(2);
     type TargetWidget = Label;
 
     fn build<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<NewWidget<impl Widget + ?Sized>, Error> {
         let args = SplitArgs::from_params(params_stack)?;
         let (first,second) = if let (Some(first),Some(second)) = (args.first, args.second) {
             (first,second)
-        } else if params_stack.children().count() == 2 {
+        } else {
+            //no `first`/`second` params : the declared arity applies to the children
+            Self::CHILD_ARITY.check(params_stack)?;
             let mut iter = params_stack.children();
             (iter.next().unwrap(), iter.next().unwrap() )
-        } else {
-            return Err(Error::ExactlyTwoChildRequired)
         };
         let widget = Split::new(
             B::build_widget( &params_stack.new_stack(first) )?.erased(),
@@ -428,7 +796,7 @@ impl WidgetBuilder for Split<dyn Widget<Action=ErasedAction>,dyn Widget<Action=E
         );
         let wid = params_stack.get_id().map( |id| { unsafe { B::get_widget_tag(id) } } );
         let wopts = WidgetOptions::default();
-        let (props, _styles) = B::build_styles(true,false,&params_stack.component,&params_stack.skui);
+        let (props, _styles) = B::build_styles(true,false,&params_stack.component,&[],&params_stack.skui);
         Ok( NewWidget::new_with(widget, wid, wopts, props).erased() )
     }
 
@@ -444,7 +812,7 @@ impl <const USER_EDITABLE:bool> WidgetBuilder for TextArea<USER_EDITABLE> {
 
     fn build<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<NewWidget<impl Widget + ?Sized>, Error> {
         let args = TextAreaArgs::from_params(params_stack)?;
-        let (props,styles) = B::build_styles(true,true,&params_stack.component, &params_stack.skui);
+        let (props,styles) = B::build_styles(true,true,&params_stack.component, &[], &params_stack.skui);
         if args.editable.unwrap_or(true) {
             let mut widget = TextArea::<true>::new(args.text.unwrap_or(""));
             let wid = params_stack.get_id().map( |id| { unsafe { B::get_widget_tag(id) } } );
@@ -471,6 +839,7 @@ impl <const USER_EDITABLE:bool> WidgetBuilder for TextArea<USER_EDITABLE> {
 
 impl WidgetBuilder for TextInput {
     const WIDGET_NAME: &'static str = "TextInput";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
@@ -485,6 +854,7 @@ impl WidgetBuilder for TextInput {
 
 impl WidgetBuilder for VariableLabel {
     const WIDGET_NAME: &'static str = "VariableLabel";
+    const CHILD_ARITY: ChildArity = ChildArity::None;
     type TargetWidget = Self;
 
     fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
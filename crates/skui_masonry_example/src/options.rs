@@ -0,0 +1,288 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use skui::FilterCall;
+
+// Resolved message argument (relative values are already looked up on the stack)
+#[derive(Debug,Clone)]
+pub enum TrArg {
+    Str(String),
+    Int(i64),
+    Float(f64),
+}
+
+impl std::fmt::Display for TrArg {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrArg::Str(s) => write!(f, "{s}"),
+            TrArg::Int(v) => write!(f, "{v}"),
+            TrArg::Float(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+// CLDR-style plural category. Derived from an integer `count` argument;
+// the default rule is the English one, translators can override per locale.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn from_count(n:i64) -> Self {
+        match n {
+            0 => PluralCategory::Zero,
+            1 | -1 => PluralCategory::One,
+            _ => PluralCategory::Other,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+// Application side localization hook. `tr("menu.save")` values resolve through this.
+pub trait Translator {
+    fn translate(&self, key:&str) -> Option<String>;
+
+    // Formatted message with arguments. The default falls back to the plain
+    // message and substitutes `{name}` placeholders; locale-aware translators
+    // can use the plural category to pick the right message variant.
+    fn translate_with(&self, key:&str, args:&[(&str,TrArg)], plural:Option<PluralCategory>) -> Option<String> {
+        let template = plural
+            .and_then( |p| self.translate( &format!("{key}.{}", p.suffix()) ) )
+            .or_else( || self.translate(key) )?;
+        let mut text = template;
+        for (name,arg) in args.iter() {
+            text = text.replace( &format!("{{{name}}}"), &arg.to_string() );
+        }
+        Some(text)
+    }
+}
+
+#[derive(Debug,Clone)]
+pub enum BuildDiagnostic {
+    MissingTranslation(String),
+    UnknownFilter(String),
+    // restricted mode refused an action instead of doing I/O (asset load, handler bind, ..)
+    PolicyViolation(String),
+    // named parameter passed but never read by any builder (likely a typo)
+    UnusedParam { component: String, key: String },
+    // non-strict build replaced a failed child with an inline placeholder
+    ChildBuildFailed { parent: String, child: String, error: String },
+    // an active `class-if` class could not join style matching because the
+    // component's class list is already at capacity
+    ClassCapacityExceeded { component: String, class: String },
+}
+
+// Display formatting step for interpolation values (`${0.price | currency("USD")}`)
+pub trait ValueFilter {
+    fn apply(&self, input:TrArg, args:&[&str]) -> TrArg;
+}
+
+impl <F> ValueFilter for F where F: Fn(TrArg,&[&str]) -> TrArg {
+    fn apply(&self, input:TrArg, args:&[&str]) -> TrArg {
+        self(input, args)
+    }
+}
+
+#[derive(Default)]
+pub struct BuildOptions {
+    pub translator: Option<Box<dyn Translator>>,
+    pub filters: HashMap<String, Box<dyn ValueFilter>>,
+    // sandbox for third party documents : no file/network asset loading and no
+    // handler binding unless explicitly whitelisted below
+    pub restricted: bool,
+    pub allowed_asset_prefixes: Vec<String>,
+    pub allowed_handlers: Vec<String>,
+}
+
+impl BuildOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn restricted() -> Self {
+        Self { restricted: true, ..Self::default() }
+    }
+
+    pub fn allow_asset_prefix(mut self, prefix:&str) -> Self {
+        self.allowed_asset_prefixes.push(prefix.to_string());
+        self
+    }
+
+    pub fn allow_handler(mut self, name:&str) -> Self {
+        self.allowed_handlers.push(name.to_string());
+        self
+    }
+
+    pub fn with_translator(mut self, translator:impl Translator + 'static) -> Self {
+        self.translator = Some(Box::new(translator));
+        self
+    }
+
+    pub fn with_filter(mut self, name:&str, filter:impl ValueFilter + 'static) -> Self {
+        self.filters.insert(name.to_string(), Box::new(filter));
+        self
+    }
+
+    // Make these options current for the following builds on this thread.
+    // Same pattern as WID_TABLE : the builder entry points are static trait fns
+    // so the options can't be threaded through every call.
+    pub fn install(self) {
+        CURRENT.with(|c| *c.borrow_mut() = self );
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<BuildOptions> = RefCell::new( BuildOptions::default() );
+    static DIAGNOSTICS: RefCell<Vec<BuildDiagnostic>> = RefCell::new( Vec::new() );
+}
+
+pub fn translate(key:&str, args:&[(&str,TrArg)]) -> String {
+    // plural category comes from an integer `count` argument when present
+    let plural = args.iter()
+        .find( |(name,_)| *name == "count" )
+        .and_then( |(_,arg)| if let TrArg::Int(n) = arg { Some(PluralCategory::from_count(*n)) } else { None } );
+    CURRENT.with(|c| {
+        match c.borrow().translator.as_ref().and_then( |t| t.translate_with(key, args, plural) ) {
+            Some(text) => text,
+            None => {
+                push_diagnostic( BuildDiagnostic::MissingTranslation(key.to_string()) );
+                key.to_string()
+            }
+        }
+    })
+}
+
+// Run a filter chain over a resolved value. Unknown names produce a diagnostic
+// and pass the value through unchanged so the preview keeps rendering.
+pub fn apply_filters(mut input:TrArg, filters:&[FilterCall]) -> TrArg {
+    for f in filters.iter() {
+        input = match builtin_filter(f.name, input.clone(), f.args.as_slice()) {
+            Some(v) => v,
+            None => {
+                let custom = CURRENT.with(|c| {
+                    c.borrow().filters.get(f.name).map( |vf| vf.apply(input.clone(), f.args.as_slice()) )
+                });
+                match custom {
+                    Some(v) => v,
+                    None => {
+                        push_diagnostic( BuildDiagnostic::UnknownFilter(f.name.to_string()) );
+                        input
+                    }
+                }
+            }
+        };
+    }
+    input
+}
+
+fn builtin_filter(name:&str, input:TrArg, args:&[&str]) -> Option<TrArg> {
+    let v = match name {
+        "upper" => TrArg::Str( input.to_string().to_uppercase() ),
+        "lower" => TrArg::Str( input.to_string().to_lowercase() ),
+        "round" => {
+            let digits = args.get(0).and_then( |a| a.parse::<u32>().ok() ).unwrap_or(0);
+            match input {
+                TrArg::Float(v) => {
+                    let m = 10f64.powi(digits as _);
+                    TrArg::Float( (v * m).round() / m )
+                }
+                v @ _ => v,
+            }
+        }
+        "currency" => {
+            let amount = match input {
+                TrArg::Int(v) => v as f64,
+                TrArg::Float(v) => v,
+                TrArg::Str(_) => return Some(input),
+            };
+            let symbol = match args.get(0).copied().unwrap_or("") {
+                "USD" => "$".to_string(),
+                "EUR" => "€".to_string(),
+                "GBP" => "£".to_string(),
+                "JPY" => "¥".to_string(),
+                "KRW" => "₩".to_string(),
+                code @ _ => format!("{code} "),
+            };
+            TrArg::Str( format!("{symbol}{amount:.2}") )
+        }
+        "date" => {
+            let epoch = match input {
+                TrArg::Int(v) => v,
+                _ => return Some(input),
+            };
+            let fmt = args.get(0).copied().unwrap_or("%Y-%m-%d");
+            TrArg::Str( format_epoch(epoch, fmt) )
+        }
+        _ => return None
+    };
+    Some(v)
+}
+
+// Minimal strftime over unix seconds (UTC). Supports %Y %m %d %H %M %S.
+fn format_epoch(epoch:i64, fmt:&str) -> String {
+    let days = epoch.div_euclid(86400);
+    let secs = epoch.rem_euclid(86400);
+    // civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe/1460 + doe/36524 - doe/146096) / 365;
+    let y = yoe + era * 400;
+    let doy = doe - (365*yoe + yoe/4 - yoe/100);
+    let mp = (5*doy + 2)/153;
+    let d = doy - (153*mp+2)/5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = if m <= 2 { y + 1 } else { y };
+
+    fmt.replace("%Y", &format!("{y:04}"))
+        .replace("%m", &format!("{m:02}"))
+        .replace("%d", &format!("{d:02}"))
+        .replace("%H", &format!("{:02}", secs/3600))
+        .replace("%M", &format!("{:02}", (secs/60)%60))
+        .replace("%S", &format!("{:02}", secs%60))
+}
+
+// Gate every asset path (Image source etc.) through this in restricted mode.
+// Returns false and records a `PolicyViolation` instead of touching the file/network.
+pub fn asset_allowed(path:&str) -> bool {
+    let allowed = CURRENT.with(|c| {
+        let opts = c.borrow();
+        if !opts.restricted { return true }
+        opts.allowed_asset_prefixes.iter().any( |p| path.starts_with(p.as_str()) )
+    });
+    if !allowed {
+        push_diagnostic( BuildDiagnostic::PolicyViolation( format!("asset load blocked: {path}") ) );
+    }
+    allowed
+}
+
+// Same gate for handler/callback names bound from the document
+pub fn handler_allowed(name:&str) -> bool {
+    let allowed = CURRENT.with(|c| {
+        let opts = c.borrow();
+        if !opts.restricted { return true }
+        opts.allowed_handlers.iter().any( |h| h == name )
+    });
+    if !allowed {
+        push_diagnostic( BuildDiagnostic::PolicyViolation( format!("handler binding blocked: {name}") ) );
+    }
+    allowed
+}
+
+pub fn push_diagnostic(d:BuildDiagnostic) {
+    DIAGNOSTICS.with(|v| v.borrow_mut().push(d) );
+}
+
+// Drain diagnostics collected since the last call (missing translation keys etc.)
+pub fn take_diagnostics() -> Vec<BuildDiagnostic> {
+    DIAGNOSTICS.with(|v| std::mem::take(&mut *v.borrow_mut()) )
+}
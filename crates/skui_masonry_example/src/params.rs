@@ -1,14 +1,19 @@
 use masonry::kurbo::Axis;
-use masonry::layout::UnitPoint;
+use masonry::layout::{Length, UnitPoint};
 use masonry::properties::types::{CrossAxisAlignment, MainAxisAlignment};
 use masonry::TextAlign;
 use masonry::widgets::{FlexBasis, InsertNewline};
-use skui::{Component, Number, Parameters, Value, SKUI};
+use masonry::peniko::color::{AlphaColor, Srgb};
+use skui::{Component, CssValue, FilterCall, Number, Parameters, Value, ValueKey, SKUI};
+use thiserror::Error;
 
-#[derive(Debug,Clone)]
+#[derive(Error,Debug,Clone)]
 pub enum ValueConvError {
+    #[error("the value has the wrong type")]
     InvalidType,
+    #[error("the value is out of range for this parameter")]
     InvalidValue,
+    #[error("a mandatory parameter is missing")]
     MandatoryParamMissing
 }
 
@@ -25,7 +30,7 @@ pub trait FromValue<'a>: Sized {
 
 impl <'a> FromValue<'a> for String {
     fn from_value(v:&'a Value) -> Result<String, ValueConvError> {
-        Ok( v.as_str().ok_or(ValueConvError::InvalidType)?.to_string() )
+        Ok( v.as_text().ok_or(ValueConvError::InvalidType)?.into_owned() )
     }
 }
 
@@ -56,6 +61,30 @@ impl <'a> FromValue<'a> for Number {
 }
 
 
+impl <'a> FromValue<'a> for AlphaColor<Srgb> {
+    fn from_value(v:&'a Value) -> Result<Self, ValueConvError> {
+        let css = match v {
+            Value::Color(color) => *color,
+            //named colors arrive as plain idents (`color=red`)
+            Value::Ident(name) => CssValue::Ident(name),
+            _ => return Err(ValueConvError::InvalidType),
+        };
+        crate::style::to_color_from_value(css).ok_or(ValueConvError::InvalidValue)
+    }
+}
+
+impl <'a> FromValue<'a> for Length {
+    fn from_value(v:&'a Value) -> Result<Length, ValueConvError> {
+        match v {
+            Value::Unit(unit) => crate::style::resolve_px(unit)
+                .map( Length::px )
+                .ok_or(ValueConvError::InvalidValue),
+            _ => Ok( Length::px( v.as_f64().ok_or(ValueConvError::InvalidType)? ) ),
+        }
+    }
+}
+
+
 impl <'a> FromValue<'a> for &'a Component<'a> {
     fn from_value(v:&'a Value) -> Result< Self, ValueConvError> {
         if let Value::Component(comp) = v {
@@ -78,6 +107,14 @@ macro_rules! impl_from_value {
         $(
         impl <'a> FromValue<'a>  for $name {
             fn from_value(v:&Value) -> Result<Self, ValueConvError> {
+                //unit-suffixed parameter (`width=200px`, `size=1.2em`) :
+                //absolute units resolve through the length context; `%` has no
+                //parent dimension in parameter position and is rejected
+                if let Value::Unit(unit) = v {
+                    return crate::style::resolve_px(unit)
+                        .map( |px| px as _ )
+                        .ok_or(ValueConvError::InvalidValue)
+                }
                 Ok( v.as_f64().ok_or(ValueConvError::InvalidType)? as _ )
             }
         }
@@ -129,15 +166,58 @@ impl_from_value!(FlexBasis { Auto, Zero} );
 impl_from_value!(TextAlign {Start,End,Left,Center,Right,Justify} );
 impl_from_value!(InsertNewline {OnEnter, OnShiftEnter, Never});
 
-#[derive(Debug,Clone)]
+// Which parameter of which component went bad, and in which root builder -
+// `err` stays reachable through `source()` for callers that walk the chain.
+#[derive(Error,Debug,Clone)]
+#[error("parameter {idx} (`{key}`) of `{comp}` in `{func}` : {err}")]
 pub struct ArgumentError {
     pub func:String,
     pub comp:String,
     pub idx:usize,
     pub key:&'static str,
+    #[source]
     pub err:ValueConvError,
 }
 
+// Ambient build-time values (theme tokens, user settings, platform flags).
+// Addressable as `${env.name}` from any depth, independent of the caller stack.
+pub type Env<'a> = Parameters<'a>;
+
+// Per-build configuration, threaded down the builder stack on `ParamsStack`.
+// Behavior that would otherwise become another type-level const or process
+// global lands here, so one build can be strict while another renders
+// third-party content leniently with its own locale and theme.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BuildConfig {
+    // fail the build on the first bad child/parameter instead of patching
+    // over it with placeholders and diagnostics
+    pub strict: bool,
+    // BCP-47 tag handed to the translator and number/date filters
+    pub locale: Option<String>,
+    // named theme this build resolves against
+    pub theme: Option<String>,
+    // build-time feature flags, e.g. gating experimental widgets
+    pub features: Vec<String>,
+}
+
+// Builds that do not pass a config run with the defaults.
+pub static DEFAULT_BUILD_CONFIG: BuildConfig = BuildConfig {
+    strict: false,
+    locale: None,
+    theme: None,
+    features: Vec::new(),
+};
+
+impl BuildConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn has_feature(&self, name:&str) -> bool {
+        self.features.iter().any( |f| f == name )
+    }
+}
+
 // Search for the value in the current parameter. If the value is “Relative”, search in the caller parameter.
 #[derive(Debug,Clone)]
 pub struct ParamsStack<'a> {
@@ -147,6 +227,11 @@ pub struct ParamsStack<'a> {
     pub wrap_classes : Option<&'a [&'a str]>,
     pub component: &'a Component<'a>,
     pub skui: &'a SKUI<'a>,
+    pub env: Option<&'a Env<'a>>,
+    // `Card() { .. }` : the invocation's children, spliced into the `Card:`
+    // definition wherever a `Children(..)` slot marker appears.
+    pub caller_children: Option<&'a [Component<'a>]>,
+    pub config: &'a BuildConfig,
 }
 
 
@@ -155,6 +240,14 @@ const MAIN_COMPONENT_NAME: &'static str = "Main";
 impl<'a> ParamsStack<'a> {
 
     pub fn new_main(param:&'a Parameters<'a>, skui:&'a SKUI<'a>) -> Option<Self> {
+        Self::new_main_with_env(param, None, skui)
+    }
+
+    pub fn new_main_with_env(param:&'a Parameters<'a>, env:Option<&'a Env<'a>>, skui:&'a SKUI<'a>) -> Option<Self> {
+        Self::new_main_with_config(param, env, &DEFAULT_BUILD_CONFIG, skui)
+    }
+
+    pub fn new_main_with_config(param:&'a Parameters<'a>, env:Option<&'a Env<'a>>, config:&'a BuildConfig, skui:&'a SKUI<'a>) -> Option<Self> {
         let main_comp = &skui.get_root_component(MAIN_COMPONENT_NAME)?.component;
         Some( Self {
             fn_name: MAIN_COMPONENT_NAME,
@@ -162,15 +255,43 @@ impl<'a> ParamsStack<'a> {
             params_stack:vec![param],
             wrap_id:None, //for extern caller
             wrap_classes:None, //for extern caller
-            skui
+            skui,
+            env,
+            caller_children:None,
+            config,
         } )
     }
 
+    // `${env.name}` escapes the caller stack entirely
+    fn env_lookup(&self, vkey:&'a [ValueKey<'a>]) -> Option<&'a Value<'a>> {
+        if let Some(ValueKey::Name("env")) = vkey.first() {
+            self.env?.get_as_rk(&vkey[1..])
+        } else {
+            None
+        }
+    }
+
+    pub fn resolve_rk(&self, vkey:&'a [ValueKey<'a>]) -> Option<&'a Value<'a>> {
+        self.env_lookup(vkey)
+            .or_else( || self.params_stack.iter().rev().find_map( |p| p.get_as_rk(vkey) ) )
+    }
+
+    // `${path:-fallback}` : the fallback stands in when the path doesn't resolve
+    fn resolve_or(&self, vkey:&'a [ValueKey<'a>], fallback:&'a Value<'a>) -> &'a Value<'a> {
+        self.resolve_rk(vkey).unwrap_or(fallback)
+    }
+
     pub fn new_stack(&self, comp:&'a Component<'a>) -> Self {
         //This component is caller root component
         if let Some(root_comp) = self.skui.get_root_component(comp.name) {
             let root_lookup_comp = &root_comp.component;
             let mut stack = self.params_stack.clone();
+            //`extends` : ancestor definitions' root params become fallback
+            //frames - the invocation overrides the parent's defaults, which
+            //override the grandparent's, and so on
+            for base in self.skui.extends_chain(comp.name).iter().skip(1).rev() {
+                stack.push(&base.component.params);
+            }
             stack.push(&comp.params);
             let wrap_classes = if comp.classes.len() > 0 {
                 Some(comp.classes.as_slice())
@@ -181,7 +302,11 @@ impl<'a> ParamsStack<'a> {
                 wrap_id : comp.id,
                 wrap_classes,
                 component : root_lookup_comp,
-                skui : self.skui
+                skui : self.skui,
+                env : self.env,
+                //the invocation's children become the definition's slot content
+                caller_children : (!comp.children.is_empty()).then( || comp.children.as_slice() ),
+                config : self.config,
             }
         } else {
             let stack = self.params_stack.clone();
@@ -191,7 +316,11 @@ impl<'a> ParamsStack<'a> {
                 wrap_id : None,
                 wrap_classes : None,
                 component: comp,
-                skui : self.skui
+                skui : self.skui,
+                env : self.env,
+                //still inside the same definition : slots keep resolving
+                caller_children : self.caller_children,
+                config : self.config,
             }
         }
 
@@ -206,37 +335,302 @@ impl<'a> ParamsStack<'a> {
     }
 
     pub fn get(&self, idx:usize, key:&'a str) -> Option<&'a Value<'a>> {
+        let (from, v) = self.get_traced(idx, key);
+        if crate::explain::is_enabled() {
+            crate::explain::record_param(self.component.name, idx, key, from, v);
+        }
+        v
+    }
+
+    // `get` plus which frame resolved the read, for explain-build mode.
+    fn get_traced(&self, idx:usize, key:&'a str) -> (Option<crate::explain::ParamSource>, Option<&'a Value<'a>>) {
+        use crate::explain::ParamSource;
         let mut curr_val:Option<&'a Value<'a>> = None;
 
-        for stack in std::iter::once(&self.component.params).chain( self.params_stack.iter().rev().copied() ) {
+        for (depth,stack) in std::iter::once(&self.component.params).chain( self.params_stack.iter().rev().copied() ).enumerate() {
+            let source = if depth == 0 { ParamSource::Own } else { ParamSource::Caller(depth - 1) };
             if let Some(Value::Relative( key)) = curr_val {
+                if let Some(v) = self.env_lookup( key.as_slice() ) {
+                    return (Some(ParamSource::Env), Some(v));
+                }
                 let value = stack.get_as_rk( key.as_slice() );
                 if let Some(v) = value {
-                    if let Value::Relative(_) = v {
-                        curr_val = value;
-                    } else {
-                        return value;
+                    if crate::audit::is_enabled() {
+                        if let Some(ValueKey::Name(name)) = key.first() {
+                            crate::audit::record_consumed(stack, name);
+                        }
+                    }
+                    match v {
+                        Value::Relative(_) => curr_val = value,
+                        Value::RelativeOr(vkey, fallback) => {
+                            return (Some(source), Some( self.resolve_or(vkey.as_slice(), fallback) ))
+                        }
+                        _ => return (Some(source), value),
                     }
                 } else {
-                    return value;
+                    //not in this frame : keep walking. Deeper frames include
+                    //`extends` fallback frames carrying parent defaults.
+                    continue;
                 }
             } else {
                 let v = stack.get(idx, key);
-                if let Some(Value::Relative(_)) = v {
-                    curr_val = v;
-                } else {
-                    return v
+                if v.is_some() && crate::audit::is_enabled() {
+                    crate::audit::record_consumed(stack, key);
+                }
+                match v {
+                    Some(Value::Relative(vkey)) => {
+                        if let Some(ev) = self.env_lookup( vkey.as_slice() ) {
+                            return (Some(ParamSource::Env), Some(ev));
+                        }
+                        curr_val = v;
+                    }
+                    //`${path:-fallback}` : never reports missing - the fallback
+                    //stands in when the remaining frames can't resolve the path
+                    Some(Value::RelativeOr(vkey, fallback)) => {
+                        return (Some(source), Some( self.resolve_or(vkey.as_slice(), fallback) ))
+                    }
+                    _ => return (v.map( |_| source ), v),
                 }
             }
         }
-        curr_val
+        //an unresolved binding never leaks as a value
+        if matches!(curr_val, Some(Value::Relative(_))) {
+            (None, None)
+        } else {
+            (None, curr_val)
+        }
     }
 
     pub fn children(&self) -> impl Iterator<Item=&'a Component<'a>> {
-        self.component.children.iter()
+        let mut out = Vec::new();
+        self.expand_conditionals(self.component.children.as_slice(), &mut out);
+        out.into_iter()
+    }
+
+    // `if`/`else` nodes are synthetic components (see the skui parser) : pick
+    // the live branch against the current parameter stack and splice its
+    // children in place. Conditions that do not resolve to a bool count as
+    // false, matching how `class-if` treats unresolved bindings.
+    fn expand_conditionals(&self, children:&'a [Component<'a>], out:&mut Vec<&'a Component<'a>>) {
+        for c in children {
+            //`Children()` / `Children("header")` : splice the caller's
+            //children here. Named slots pick the caller children declaring a
+            //matching `slot:` property; the unnamed slot takes the rest.
+            if c.name == "Children" {
+                let slot = c.params.get(0, "name").and_then( |v| v.as_str() );
+                let Some(caller) = self.caller_children else { continue };
+                for cc in caller.iter() {
+                    //a literal `Children` in the invocation would self-splice
+                    if cc.name == "Children" { continue }
+                    if cc.properties.get("slot").and_then( |v| v.as_str() ) == slot {
+                        //spliced content may itself hold conditionals
+                        self.expand_conditionals(std::slice::from_ref(cc), out);
+                    }
+                }
+                continue;
+            }
+            if c.name != "if" {
+                out.push(c);
+                continue;
+            }
+            let cond = match c.params.get(0, "") {
+                Some(Value::Relative(vkey)) => self.resolve_rk( vkey.as_slice() ).and_then( |v| v.as_bool() ),
+                Some(Value::RelativeOr(vkey, fallback)) => self.resolve_or( vkey.as_slice(), fallback ).as_bool(),
+                Some(v) => v.as_bool(),
+                None => None,
+            }.unwrap_or(false);
+            let (body, else_node) = match c.children.split_last() {
+                Some((last, rest)) if last.name == "else" => (rest, Some(last)),
+                _ => (c.children.as_slice(), None),
+            };
+            if cond {
+                self.expand_conditionals(body, out);
+            } else if let Some(else_node) = else_node {
+                self.expand_conditionals(else_node.children.as_slice(), out);
+            }
+        }
+    }
+
+    // `for item in ${0.items} { .. }` (a synthetic `for` node, see the core
+    // parser) : materialize the body once per element. Loop-variable
+    // references are substituted with the element's value up front, so the
+    // expanded children are ordinary components. A missing or non-array
+    // source expands to nothing.
+    pub fn expand_loop(&self, c:&'a Component<'a>) -> Vec<Component<'a>> {
+        let Some(Value::Ident(var)) = c.params.get(0, "") else { return vec![] };
+        let items = match c.params.get(1, "") {
+            Some(Value::Relative(vkey)) => {
+                match self.resolve_rk( vkey.as_slice() ).and_then( |v| v.as_array() ) {
+                    Some(items) => items.as_slice(),
+                    None => return vec![],
+                }
+            }
+            Some(Value::Array(items)) => items.as_slice(),
+            _ => return vec![],
+        };
+        let mut out = Vec::new();
+        for item in items.iter() {
+            for child in c.children.iter() {
+                out.push( substitute_loop_var(child, var, item) );
+            }
+        }
+        out
+    }
+
+    // Text parameter lookup that also resolves `tr("key")` references.
+    pub fn get_text(&self, idx:usize, key:&'a str) -> Option<std::borrow::Cow<'a, str>> {
+        match self.get(idx, key)? {
+            Value::Tr(tr) => {
+                // resolve relative args against the caller stack before translating
+                let args = tr.args.iter()
+                    .filter_map( |(name,value)| {
+                        let value = if let Value::Relative(vkey) = value {
+                            self.resolve_rk(vkey.as_slice())?
+                        } else { value };
+                        let arg = match value {
+                            Value::Number(Number::I64(v)) => crate::options::TrArg::Int(*v),
+                            Value::Number(Number::F64(v)) => crate::options::TrArg::Float(*v),
+                            v => crate::options::TrArg::Str( v.as_str()?.to_string() ),
+                        };
+                        Some( (*name, arg) )
+                    })
+                    .collect::<Vec<_>>();
+                Some( std::borrow::Cow::Owned( crate::options::translate(tr.key, args.as_slice()) ) )
+            }
+            Value::Filtered(vkey, filters) => {
+                Some( std::borrow::Cow::Owned( self.filtered_text(vkey.as_slice(), filters.as_slice())? ) )
+            }
+            //`"Hello, ${0.user}!"` : concatenate the segments; unresolvable
+            //bindings render as nothing rather than failing the whole text
+            Value::Interpolated(segments) => {
+                let mut out = String::new();
+                for seg in segments.iter() {
+                    match seg {
+                        Value::String(lit) => out.push_str( &skui::unescape(lit) ),
+                        //numbers appear after loop-variable substitution
+                        Value::Number(Number::I64(n)) => out.push_str( &n.to_string() ),
+                        Value::Number(Number::F64(n)) => out.push_str( &n.to_string() ),
+                        Value::Relative(vkey) => {
+                            if let Some(text) = self.filtered_text(vkey.as_slice(), &[]) {
+                                out.push_str(&text);
+                            }
+                        }
+                        Value::RelativeOr(vkey, fallback) => {
+                            if let Some(text) = self.filtered_text(vkey.as_slice(), &[]) {
+                                out.push_str(&text);
+                            } else if let Some(text) = fallback.as_text() {
+                                out.push_str(&text);
+                            }
+                        }
+                        Value::Filtered(vkey, filters) => {
+                            if let Some(text) = self.filtered_text(vkey.as_slice(), filters.as_slice()) {
+                                out.push_str(&text);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Some( std::borrow::Cow::Owned(out) )
+            }
+            //string literals decode their escapes here
+            v => v.as_text(),
+        }
+    }
+
+    // `${path | filter..}` resolved to display text (shared with interpolation).
+    fn filtered_text(&self, vkey:&'a [ValueKey<'a>], filters:&[FilterCall<'a>]) -> Option<String> {
+        let value = self.resolve_rk(vkey)?;
+        let input = match value {
+            Value::Number(Number::I64(v)) => crate::options::TrArg::Int(*v),
+            Value::Number(Number::F64(v)) => crate::options::TrArg::Float(*v),
+            v => crate::options::TrArg::Str( v.as_str()?.to_string() ),
+        };
+        Some( crate::options::apply_filters(input, filters).to_string() )
+    }
+
+    // `class-if: { selected: ${item.selected}, .. }` : conditional classes.
+    // Returns every entry with its current truth; bound entries keep the
+    // binding path so the runtime can re-toggle them when the value changes.
+    pub fn class_if(&self) -> Vec<ClassIf<'a>> {
+        let Some(Value::Map(map)) = self.component.properties.get("class-if")
+        else { return vec![] };
+        map.iter()
+            .map( |(&class,v)| match v {
+                Value::Bool(b) => ClassIf { class, active:*b, binding:None },
+                Value::Relative(vkey) => {
+                    let active = self.resolve_rk( vkey.as_slice() )
+                        .and_then( |v| v.as_bool() )
+                        .unwrap_or(false);
+                    ClassIf { class, active, binding:Some( vkey.as_slice() ) }
+                }
+                _ => {
+                    eprintln!("class-if `{class}` expects a bool or a binding, got {v:?}");
+                    ClassIf { class, active:false, binding:None }
+                }
+            })
+            .collect()
+    }
+}
+
+// Deep-copy a loop body component with `${var}` / `${var.path}` references
+// replaced by the element's (sub)value. Everything else clones as-is;
+// references to the caller stack stay Relative and resolve as usual.
+fn substitute_loop_var<'a>(c:&'a Component<'a>, var:&str, item:&'a Value<'a>) -> Component<'a> {
+    Component {
+        name: c.name,
+        params: match &c.params {
+            Parameters::Map(map) => Parameters::Map(
+                map.iter().map( |(k,v)| (*k, substitute_loop_value(v, var, item)) ).collect()
+            ),
+            Parameters::Args(list) => Parameters::Args(
+                list.iter().map( |v| substitute_loop_value(v, var, item) ).collect()
+            ),
+        },
+        id: c.id,
+        classes: c.classes.clone(),
+        children: c.children.iter().map( |ch| substitute_loop_var(ch, var, item) ).collect(),
+        properties: c.properties.iter().map( |(k,v)| (*k, substitute_loop_value(v, var, item)) ).collect(),
+    }
+}
+
+fn substitute_loop_value<'a>(v:&'a Value<'a>, var:&str, item:&'a Value<'a>) -> Value<'a> {
+    match v {
+        Value::Relative(vkey) if matches!(vkey.first(), Some(ValueKey::Name(n)) if *n == var) => {
+            if vkey.len() == 1 {
+                item.clone()
+            } else if let Some(sub) = item.get_as_rk(&vkey[1..]) {
+                sub.clone()
+            } else {
+                eprintln!("Can't find loop value : {:?}. From : {:?}", vkey, item);
+                Value::default()
+            }
+        }
+        //with an inline fallback a missing sub-path is not an error
+        Value::RelativeOr(vkey, fallback) if matches!(vkey.first(), Some(ValueKey::Name(n)) if *n == var) => {
+            if vkey.len() == 1 {
+                item.clone()
+            } else if let Some(sub) = item.get_as_rk(&vkey[1..]) {
+                sub.clone()
+            } else {
+                (**fallback).clone()
+            }
+        }
+        Value::Interpolated(segments) => Value::Interpolated( segments.iter().map( |s| substitute_loop_value(s, var, item) ).collect() ),
+        Value::Array(items) => Value::Array( items.iter().map( |i| substitute_loop_value(i, var, item) ).collect() ),
+        Value::Map(map) => Value::Map( map.iter().map( |(k,val)| (*k, substitute_loop_value(val, var, item)) ).collect() ),
+        Value::Component(c) => Value::Component( substitute_loop_var(c, var, item) ),
+        v => v.clone(),
     }
 }
 
+// One resolved `class-if` entry at build time.
+#[derive(Debug, Clone)]
+pub struct ClassIf<'a> {
+    pub class: &'a str,
+    pub active: bool,
+    pub binding: Option<&'a [ValueKey<'a>]>,
+}
+
 pub trait FromParams<'a> : Sized {
     fn from_params(params:&'a ParamsStack) -> Result<Self,ArgumentError>;
 }
@@ -285,7 +679,7 @@ impl_from_params!(ButtonArgs<'a>, MUST[text:&'a str]);
 impl_from_params!(CheckboxArgs<'a>, MUST[text:&'a str], OPTION [checked:bool] );
 impl_from_params!(FlexArgs, MUST [ axis: Axis ], OPTION [ main_axis_alignment: MainAxisAlignment,cross_axis_alignment: CrossAxisAlignment ] );
 impl_from_params!(FlexItemArgs <'a>, MUST[comp:&'a Component<'a>,flex:f64], OPTION[basis:FlexBasis,alignment:CrossAxisAlignment] );
-impl_from_params!(FlexSpacerArgs, MUST[value:Number]);
+impl_from_params!(FlexSpacerArgs<'a>, MUST[value:Value<'a>]);
 impl_from_params!(GridArgs, MUST[x:i32, y:i32] );
 impl_from_params!(GridParamsArgs<'a>, MUST[comp:&'a Component<'a>, x:i32, y:i32], OPTION[w:i32, h:i32] );
 impl_from_params!(IndexedStackArgs, MUST[index:usize]);
@@ -300,4 +694,269 @@ impl_from_params!(SliderArgs, MUST[min:f64,max:f64,value:f64], OPTION[step:f64]
 impl_from_params!(SplitArgs<'a>, OPTION[first:&'a Component<'a>,second:&'a Component<'a>] );
 impl_from_params!(TextAreaArgs<'a>, OPTION[text:&'a str,alignment:TextAlign,insert_newline:InsertNewline,hint:bool,editable:bool]);
 impl_from_params!(TextInputArgs<'a>, OPTION[placeholder:&'a str, text:&'a str,clip:bool,alignment:TextAlign] );
-impl_from_params!(VariableLabelArgs<'a>, MUST[text:&'a str], OPTION[weight:f32]);
\ No newline at end of file
+impl_from_params!(VariableLabelArgs<'a>, MUST[text:&'a str], OPTION[weight:f32]);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unit_parameter_conversion() {
+        //absolute units convert through the length context (16px base, 96 dpi)
+        assert_eq!( f64::from_value(&Value::Unit(CssValue::Px(200.0))).unwrap(), 200.0 );
+        assert_eq!( f64::from_value(&Value::Unit(CssValue::Em(1.5))).unwrap(), 24.0 );
+        assert_eq!( f64::from_value(&Value::Unit(CssValue::Pt(72.0))).unwrap(), 96.0 );
+        assert_eq!( Length::from_value(&Value::Unit(CssValue::Px(8.0))).unwrap(), Length::px(8.0) );
+        assert_eq!( Length::from_value(&Value::Number(Number::I64(8))).unwrap(), Length::px(8.0) );
+
+        //`%` has no parent dimension in parameter position
+        assert!( matches!(
+            f64::from_value(&Value::Unit(CssValue::Percent(50.0))),
+            Err(ValueConvError::InvalidValue)
+        ));
+    }
+
+    #[test]
+    fn interpolated_text() {
+        let src = r#"
+            Main:
+            Label("Hello, ${0.user}! You have ${0.count} items")
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+
+        let map = Value::Map( [
+            ("user", Value::String("Ann")),
+            ("count", Value::Number(Number::I64(3))),
+        ].into() );
+        let params = Parameters::Args( vec![map] );
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        assert_eq!( stack.get_text(0, "text").unwrap().as_ref(), "Hello, Ann! You have 3 items" );
+
+        //unresolvable bindings render as nothing
+        let params = Parameters::Args( vec![Value::Map( std::collections::HashMap::new() )] );
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        assert_eq!( stack.get_text(0, "text").unwrap().as_ref(), "Hello, ! You have  items" );
+    }
+
+    #[test]
+    fn build_config_threading() {
+        let src = r#"
+            Card:
+            Flex() { Label("inner") }
+
+            Main:
+            Card()
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+        let params = Parameters::empty();
+
+        //defaults without an explicit config
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        assert!( !stack.config.strict );
+        assert_eq!( *stack.config, DEFAULT_BUILD_CONFIG );
+
+        //an explicit config rides the stack through every nesting level
+        let config = BuildConfig {
+            strict: true,
+            locale: Some("de-DE".to_string()),
+            theme: Some("dark".to_string()),
+            features: vec!["charts".to_string()],
+        };
+        let stack = ParamsStack::new_main_with_config(&params, None, &config, &doc).unwrap();
+        let card = stack.new_stack(stack.component);
+        let label = card.new_stack( card.children().next().unwrap() );
+        assert!( label.config.strict );
+        assert_eq!( label.config.locale.as_deref(), Some("de-DE") );
+        assert!( label.config.has_feature("charts") );
+        assert!( !label.config.has_feature("tables") );
+    }
+
+    #[test]
+    fn extends_param_merge() {
+        let src = r#"
+            MyButton:
+            Button(${label}, kind="plain")
+
+            FancyButton extends MyButton:
+            Button(${label}, kind=${kind}) .fancy
+
+            Main:
+            FancyButton(label="go")
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+        let params = Parameters::empty();
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+
+        //the parent definition's root params back-fill what the invocation
+        //leaves unset
+        let fancy = stack.new_stack(stack.component);
+        assert_eq!( fancy.component.name, "Button" );
+        assert_eq!( fancy.get_text(0, "").unwrap().as_ref(), "go" );
+        assert_eq!( fancy.get(1, "kind").and_then( |v| v.as_str() ), Some("plain") );
+
+        //an explicit invocation value overrides the inherited default
+        let over = skui::Component {
+            name: "FancyButton",
+            params: Parameters::Map( [
+                ("label", Value::String("go")),
+                ("kind", Value::String("bold")),
+            ].into() ),
+            id: None, classes: Default::default(),
+            children: vec![], properties: Default::default(),
+        };
+        let fancy = stack.new_stack(&over);
+        assert_eq!( fancy.get(1, "kind").and_then( |v| v.as_str() ), Some("bold") );
+    }
+
+    #[test]
+    fn children_slots() {
+        let src = r#"
+            Card:
+            Flex() {
+                Children("header")
+                Label("divider")
+                Children()
+            }
+
+            Main:
+            Card() {
+                Label("title") { slot: "header" }
+                Label("body")
+                Label("more")
+            }
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+        let params = Parameters::empty();
+        let main_stack = ParamsStack::new_main(&params, &doc).unwrap();
+
+        //entering the Card invocation switches to the definition body and
+        //keeps the invocation's children as slot content
+        let card_stack = main_stack.new_stack(main_stack.component);
+        assert_eq!( card_stack.component.name, "Flex" );
+        let texts: Vec<_> = card_stack.children()
+            .map( |c| c.params.get(0, "").and_then( |v| v.as_str() ).unwrap() )
+            .collect();
+        assert_eq!( texts, ["title", "divider", "body", "more"] );
+
+        //without caller children the slots splice nothing
+        let empty = skui::Component { name:"Card", params:Parameters::empty(), id:None, classes:Default::default(), children:vec![], properties:Default::default() };
+        let bare = main_stack.new_stack(&empty);
+        let texts: Vec<_> = bare.children()
+            .map( |c| c.params.get(0, "").and_then( |v| v.as_str() ).unwrap() )
+            .collect();
+        assert_eq!( texts, ["divider"] );
+    }
+
+    #[test]
+    fn relative_fallbacks() {
+        let src = r#"
+            Main:
+            Flex() {
+                Label(${0.title:-"Untitled"})
+                Label("by ${0.author:-anonymous}")
+            }
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+
+        //resolvable paths ignore the fallback
+        let map = Value::Map( [
+            ("title", Value::String("Report")),
+            ("author", Value::String("Ann")),
+        ].into() );
+        let params = Parameters::Args( vec![map] );
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        let children: Vec<_> = stack.children().collect();
+        assert_eq!( stack.new_stack(children[0]).get_text(0, "text").unwrap().as_ref(), "Report" );
+        assert_eq!( stack.new_stack(children[1]).get_text(0, "text").unwrap().as_ref(), "by Ann" );
+
+        //missing paths resolve to the inline fallback instead of None
+        let params = Parameters::Args( vec![Value::Map( std::collections::HashMap::new() )] );
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        let children: Vec<_> = stack.children().collect();
+        assert_eq!( stack.new_stack(children[0]).get_text(0, "text").unwrap().as_ref(), "Untitled" );
+        assert_eq!( stack.new_stack(children[1]).get_text(0, "text").unwrap().as_ref(), "by anonymous" );
+    }
+
+    #[test]
+    fn color_parameter_conversion() {
+        type Color = AlphaColor<Srgb>;
+        assert_eq!( Color::from_value(&Value::Color(CssValue::HexColor("ff8800"))).unwrap().to_rgba8(), Color::from_rgb8(0xff, 0x88, 0x00).to_rgba8() );
+        assert_eq!( Color::from_value(&Value::Color(CssValue::Rgb((1,2,3)))).unwrap().to_rgba8(), Color::from_rgb8(1, 2, 3).to_rgba8() );
+        //named colors arrive as idents and go through the css color parser
+        assert_eq!( Color::from_value(&Value::Ident("red")).unwrap().to_rgba8(), Color::from_rgb8(255, 0, 0).to_rgba8() );
+        assert!( Color::from_value(&Value::Bool(true)).is_err() );
+    }
+
+    #[test]
+    fn loop_expansion() {
+        let src = r#"
+            Main:
+            Flex() {
+                for item in ${0.rows} {
+                    Label(${item.name})
+                }
+            }
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+
+        let rows = Value::Array(vec![
+            Value::Map( [("name", Value::String("a"))].into() ),
+            Value::Map( [("name", Value::String("b"))].into() ),
+        ]);
+        let params = Parameters::Args( vec![rows] );
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        let for_node = stack.children().next().unwrap();
+        assert_eq!( for_node.name, "for" );
+        let expanded = stack.expand_loop(for_node);
+        assert_eq!( expanded.len(), 2 );
+        assert_eq!( expanded[0].params.get(0, "text").and_then( |v| v.as_str() ), Some("a") );
+        assert_eq!( expanded[1].params.get(0, "text").and_then( |v| v.as_str() ), Some("b") );
+
+        //a non-array source expands to nothing
+        let params = Parameters::Args( vec![Value::Bool(true)] );
+        let stack = ParamsStack::new_main(&params, &doc).unwrap();
+        let for_node = stack.children().next().unwrap();
+        assert!( stack.expand_loop(for_node).is_empty() );
+    }
+
+    #[test]
+    fn conditional_children() {
+        let src = r#"
+            Main:
+            Flex() {
+                if ${show_advanced} {
+                    Label("advanced")
+                } else {
+                    Label("basic")
+                }
+                Label("tail")
+            }
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+
+        let texts_for = |params:&Parameters| -> Vec<String> {
+            let stack = ParamsStack::new_main(params, &doc).unwrap();
+            stack.children()
+                .filter_map( |c| c.params.get(0, "text").and_then( |v| v.as_str() ) )
+                .map( |s| s.to_string() )
+                .collect()
+        };
+
+        let mut args = std::collections::HashMap::new();
+        args.insert("show_advanced", Value::Bool(true));
+        assert_eq!( texts_for(&Parameters::Map(args)), vec!["advanced", "tail"] );
+
+        let mut args = std::collections::HashMap::new();
+        args.insert("show_advanced", Value::Bool(false));
+        assert_eq!( texts_for(&Parameters::Map(args)), vec!["basic", "tail"] );
+
+        //an unresolvable condition counts as false
+        assert_eq!( texts_for(&Parameters::Map(std::collections::HashMap::new())), vec!["basic", "tail"] );
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+// Builder pipeline instrumentation. Fed by `WidgetBuilder::build` /
+// `build_styles` and displayed by the `PerfHud()` component.
+
+pub static WIDGETS_BUILT: AtomicUsize = AtomicUsize::new(0);
+pub static STYLE_MATCHES: AtomicUsize = AtomicUsize::new(0);
+static LAST_BUILD_NANOS: AtomicU64 = AtomicU64::new(0);
+static LAST_RELOAD_NANOS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug,Clone,Copy,Default)]
+pub struct PerfSnapshot {
+    pub build_time: Duration,
+    pub widgets_built: usize,
+    pub style_matches: usize,
+    pub reload_latency: Duration,
+}
+
+pub fn reset_counters() {
+    WIDGETS_BUILT.store(0, Ordering::Relaxed);
+    STYLE_MATCHES.store(0, Ordering::Relaxed);
+}
+
+// Wrap a root build : `let _t = perf::time_build();`
+// The elapsed time is recorded when the guard drops.
+pub struct BuildTimer(Instant);
+
+pub fn time_build() -> BuildTimer {
+    reset_counters();
+    BuildTimer(Instant::now())
+}
+
+impl Drop for BuildTimer {
+    fn drop(&mut self) {
+        LAST_BUILD_NANOS.store(self.0.elapsed().as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+// Hot reload latency (file change noticed -> new tree attached), reported by the driver
+pub fn record_reload_latency(d:Duration) {
+    LAST_RELOAD_NANOS.store(d.as_nanos() as u64, Ordering::Relaxed);
+}
+
+pub fn snapshot() -> PerfSnapshot {
+    PerfSnapshot {
+        build_time: Duration::from_nanos( LAST_BUILD_NANOS.load(Ordering::Relaxed) ),
+        widgets_built: WIDGETS_BUILT.load(Ordering::Relaxed),
+        style_matches: STYLE_MATCHES.load(Ordering::Relaxed),
+        reload_latency: Duration::from_nanos( LAST_RELOAD_NANOS.load(Ordering::Relaxed) ),
+    }
+}
+
+pub fn hud_text() -> String {
+    let s = snapshot();
+    format!(
+        "build: {:.2}ms | widgets: {} | style matches: {} | reload: {:.2}ms",
+        s.build_time.as_secs_f64() * 1000.0,
+        s.widgets_built,
+        s.style_matches,
+        s.reload_latency.as_secs_f64() * 1000.0,
+    )
+}
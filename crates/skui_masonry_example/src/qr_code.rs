@@ -0,0 +1,46 @@
+use masonry::kurbo::{Point, Rect, Size};
+use masonry::peniko::color::AlphaColor;
+use masonry::peniko::Fill;
+use masonry::vello::Scene;
+use masonry::widgets::Canvas;
+use qrcode::{Color, QrCode as Qr};
+use crate::params::{ParamsStack, ValueConvError};
+use crate::{Error, RootWidgetBuilder, WidgetBuilder};
+
+// `QrCode("https://example.com", size=128)` : renders the code onto a Canvas,
+// centered in a `size` x `size` square. Pairing/sharing screens mostly.
+
+pub struct QrCode;
+
+impl WidgetBuilder for QrCode {
+    const WIDGET_NAME: &'static str = "QrCode";
+    type TargetWidget = Canvas;
+
+    fn build_target<'a, B: RootWidgetBuilder>(params_stack: &ParamsStack<'a>) -> Result<Self::TargetWidget, Error> {
+        let text = params_stack.get_text(0, "text")
+            .ok_or_else( || ValueConvError::MandatoryParamMissing.specific(params_stack.fn_name, params_stack.component.name, 0, "text") )?;
+        let target = params_stack.get(1, "size").and_then( |v| v.as_f64() ).unwrap_or(128.0);
+        let code = Qr::new( text.as_bytes() )
+            .map_err( |_| ValueConvError::InvalidValue.specific(params_stack.fn_name, params_stack.component.name, 0, "text") )?;
+        let modules = code.width();
+        let colors = code.to_colors();
+
+        let widget = Canvas::new( move |scene:&mut Scene, size:Size| {
+            let side = target.min(size.width).min(size.height);
+            let module = side / modules as f64;
+            let origin = Point::new( (size.width - side) / 2.0, (size.height - side) / 2.0 );
+            for (i,color) in colors.iter().enumerate() {
+                if *color != Color::Dark { continue }
+                let (x,y) = (i % modules, i / modules);
+                let rect = Rect::new(
+                    origin.x + x as f64 * module,
+                    origin.y + y as f64 * module,
+                    origin.x + (x + 1) as f64 * module,
+                    origin.y + (y + 1) as f64 * module,
+                );
+                scene.fill(Fill::NonZero, Default::default(), AlphaColor::from_rgb8(0, 0, 0), None, &rect);
+            }
+        });
+        Ok( widget )
+    }
+}
@@ -0,0 +1,810 @@
+// Runtime mutation journal. Apps that mutate a built tree (editors, form
+// builders) record each operation here; undo()/redo() hand back the inverse
+// operation to apply through the same mutation path. Targets are widget ids
+// (the same strings used with `get_widget_tag`).
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum Mutation {
+    AddClass { target: String, class: String },
+    RemoveClass { target: String, class: String },
+    // `source` carries the child's SKUI source so removal can be undone
+    InsertChild { parent: String, index: usize, source: String },
+    RemoveChild { parent: String, index: usize, source: String },
+    SetBinding { target: String, key: String, old: String, new: String },
+}
+
+impl Mutation {
+    pub fn inverted(&self) -> Mutation {
+        match self {
+            Mutation::AddClass { target, class } =>
+                Mutation::RemoveClass { target: target.clone(), class: class.clone() },
+            Mutation::RemoveClass { target, class } =>
+                Mutation::AddClass { target: target.clone(), class: class.clone() },
+            Mutation::InsertChild { parent, index, source } =>
+                Mutation::RemoveChild { parent: parent.clone(), index: *index, source: source.clone() },
+            Mutation::RemoveChild { parent, index, source } =>
+                Mutation::InsertChild { parent: parent.clone(), index: *index, source: source.clone() },
+            Mutation::SetBinding { target, key, old, new } =>
+                Mutation::SetBinding { target: target.clone(), key: key.clone(), old: new.clone(), new: old.clone() },
+        }
+    }
+}
+
+#[derive(Debug,Default)]
+pub struct MutationJournal {
+    undo_stack: Vec<Mutation>,
+    redo_stack: Vec<Mutation>,
+    // 0 = unlimited
+    limit: usize,
+}
+
+impl MutationJournal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limit(limit:usize) -> Self {
+        Self { limit, ..Self::default() }
+    }
+
+    // Record an applied operation. Any redo history becomes invalid.
+    pub fn record(&mut self, m:Mutation) {
+        self.undo_stack.push(m);
+        self.redo_stack.clear();
+        if self.limit > 0 && self.undo_stack.len() > self.limit {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    // Pop the newest operation; the caller applies the returned inverse.
+    pub fn undo(&mut self) -> Option<Mutation> {
+        let m = self.undo_stack.pop()?;
+        let inverse = m.inverted();
+        self.redo_stack.push(m);
+        Some( inverse )
+    }
+
+    // Re-apply the operation undone last; the caller applies the returned op.
+    pub fn redo(&mut self) -> Option<Mutation> {
+        let m = self.redo_stack.pop()?;
+        self.undo_stack.push( m.clone() );
+        Some( m )
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+// Fired by the timer scheduler for every `timers { .. }` entry; route it
+// through the driver like any other action.
+#[derive(Debug,Clone,PartialEq)]
+pub struct TimerFired(pub String);
+
+pub struct TimerHandle {
+    stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl TimerHandle {
+    pub fn stop(&self) {
+        self.stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+impl Drop for TimerHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// Spawn one thread per declared timer. `on_fire` runs on the timer thread;
+// typically it posts the action into the masonry event loop proxy.
+pub fn spawn_timers(timers:&[skui::TimerDef], on_fire:impl Fn(TimerFired) + Send + Sync + 'static) -> TimerHandle {
+    let stop = std::sync::Arc::new( std::sync::atomic::AtomicBool::new(false) );
+    let on_fire = std::sync::Arc::new(on_fire);
+    for timer in timers.iter() {
+        let name = timer.name.to_string();
+        let interval = timer.interval;
+        let stop = stop.clone();
+        let on_fire = on_fire.clone();
+        std::thread::spawn( move || {
+            loop {
+                std::thread::sleep(interval);
+                if stop.load(std::sync::atomic::Ordering::Relaxed) { break }
+                on_fire( TimerFired( name.clone() ) );
+            }
+        });
+    }
+    TimerHandle { stop }
+}
+
+// Streaming data bindings. Connect an mpsc receiver to a binding key and the
+// driver polls `poll()` once per frame, pushing every received value to the
+// widgets bound to that key (log viewers, live dashboards, ..).
+#[derive(Default)]
+pub struct StreamBindings {
+    streams: std::collections::HashMap<String, std::sync::mpsc::Receiver<String>>,
+    latest: std::collections::HashMap<String, String>,
+}
+
+impl StreamBindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Rebinding a key drops the previous receiver.
+    pub fn bind_stream(&mut self, key:&str, rx:std::sync::mpsc::Receiver<String>) {
+        self.streams.insert(key.to_string(), rx);
+    }
+
+    pub fn unbind(&mut self, key:&str) {
+        self.streams.remove(key);
+    }
+
+    // Drain every bound channel; returns (key, value) in arrival order per key.
+    // Disconnected senders unbind the key silently.
+    pub fn poll(&mut self) -> Vec<(String,String)> {
+        let mut updates = Vec::new();
+        let mut dead = Vec::new();
+        for (key,rx) in self.streams.iter() {
+            loop {
+                match rx.try_recv() {
+                    Ok(value) => {
+                        self.latest.insert(key.clone(), value.clone());
+                        updates.push( (key.clone(), value) );
+                    }
+                    Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                        dead.push(key.clone());
+                        break;
+                    }
+                }
+            }
+        }
+        for key in dead.into_iter() {
+            self.streams.remove(&key);
+        }
+        updates
+    }
+
+    pub fn latest(&self, key:&str) -> Option<&str> {
+        self.latest.get(key).map(String::as_str)
+    }
+}
+
+// `class-if` bindings recorded during the last build : which widgets toggle
+// which class on which binding path. The driver consults `class_bindings_for`
+// when a bound value changes and re-resolves styles for the returned targets.
+#[derive(Debug,Clone,PartialEq)]
+pub struct ClassBinding {
+    pub target: String,
+    pub class: String,
+    // dotted binding path, e.g. `item.selected`
+    pub binding: String,
+}
+
+static CLASS_BINDINGS: std::sync::Mutex<Vec<ClassBinding>> = std::sync::Mutex::new(Vec::new());
+
+pub(crate) fn record_class_bindings(target:Option<&str>, entries:&[crate::params::ClassIf]) {
+    let Some(target) = target else { return };
+    let mut bindings = CLASS_BINDINGS.lock().unwrap();
+    bindings.retain( |b| b.target != target );
+    for entry in entries.iter() {
+        let Some(path) = entry.binding else { continue };
+        let binding = path.iter()
+            .map( |k| match k {
+                skui::ValueKey::Index(i) => i.to_string(),
+                skui::ValueKey::Name(n) => n.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(".");
+        bindings.push( ClassBinding { target: target.to_string(), class: entry.class.to_string(), binding } );
+    }
+}
+
+// Call before a full rebuild so stale targets do not linger.
+pub fn clear_class_bindings() {
+    CLASS_BINDINGS.lock().unwrap().clear();
+}
+
+// Widgets whose classes depend on `changed_path`, for targeted restyling.
+pub fn class_bindings_for(changed_path:&str) -> Vec<ClassBinding> {
+    CLASS_BINDINGS.lock().unwrap().iter()
+        .filter( |b| b.binding == changed_path )
+        .cloned()
+        .collect()
+}
+
+// Event-driven style invalidation : combines live pseudo-state and the
+// conditional-class bindings above into one dirty set, so the driver restyles
+// only the widgets an event actually affects instead of the full tree. The
+// driver reports events (`set_hovered`, `binding_changed`, ..), drains
+// `take_dirty` and recomputes/patches properties for just those ids; it also
+// serves as the `PseudoStateProvider` for the re-resolution pass.
+#[derive(Debug, Default)]
+pub struct StyleInvalidator {
+    states: std::collections::HashMap<String, skui::selector::PseudoState>,
+    dirty: std::collections::BTreeSet<String>,
+}
+
+impl StyleInvalidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Replace a widget's pseudo-state; marks it dirty only when the state
+    // actually changed, so repeated hover reports stay cheap.
+    pub fn set_state(&mut self, id:&str, state:skui::selector::PseudoState) {
+        let prev = self.states.get(id);
+        let changed = prev.map_or(
+            state.hovered || state.active || state.focused || state.disabled,
+            |p| (p.hovered, p.active, p.focused, p.disabled) != (state.hovered, state.active, state.focused, state.disabled)
+        );
+        if changed {
+            self.states.insert(id.to_string(), state);
+            self.dirty.insert(id.to_string());
+        }
+    }
+
+    pub fn set_hovered(&mut self, id:&str, hovered:bool) {
+        let mut state = self.states.get(id).copied().unwrap_or_default();
+        state.hovered = hovered;
+        self.set_state(id, state);
+    }
+
+    pub fn set_focused(&mut self, id:&str, focused:bool) {
+        let mut state = self.states.get(id).copied().unwrap_or_default();
+        state.focused = focused;
+        self.set_state(id, state);
+    }
+
+    pub fn set_active(&mut self, id:&str, active:bool) {
+        let mut state = self.states.get(id).copied().unwrap_or_default();
+        state.active = active;
+        self.set_state(id, state);
+    }
+
+    pub fn set_disabled(&mut self, id:&str, disabled:bool) {
+        let mut state = self.states.get(id).copied().unwrap_or_default();
+        state.disabled = disabled;
+        self.set_state(id, state);
+    }
+
+    // A bound value changed : widgets whose `class-if` entries watch the path
+    // become dirty (see `record_class_bindings`).
+    pub fn binding_changed(&mut self, changed_path:&str) {
+        for b in class_bindings_for(changed_path) {
+            self.dirty.insert(b.target);
+        }
+    }
+
+    pub fn mark_dirty(&mut self, id:&str) {
+        self.dirty.insert(id.to_string());
+    }
+
+    // Inherited properties (color, font-size, ..) cascade : a restyle of `id`
+    // must also recompute its identified descendants.
+    pub fn mark_dirty_with_descendants(&mut self, skui:&skui::SKUI, id:&str) {
+        fn mark_ids(dirty:&mut std::collections::BTreeSet<String>, c:&skui::Component) {
+            if let Some(id) = c.id {
+                dirty.insert(id.to_string());
+            }
+            c.children.iter().for_each( |ch| mark_ids(dirty, ch) );
+        }
+        self.dirty.insert(id.to_string());
+        if let Some(c) = skui.find_by_id(id) {
+            c.children.iter().for_each( |ch| mark_ids(&mut self.dirty, ch) );
+        }
+    }
+
+    pub fn is_dirty(&self, id:&str) -> bool {
+        self.dirty.contains(id)
+    }
+
+    // Drain the accumulated dirty set for this patch cycle.
+    pub fn take_dirty(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.dirty).into_iter().collect()
+    }
+}
+
+impl skui::selector::PseudoStateProvider for StyleInvalidator {
+    fn pseudo_state(&self, id:&str) -> skui::selector::PseudoState {
+        self.states.get(id).copied().unwrap_or_default()
+    }
+}
+
+// Opt-in widget reuse across rebuilds. Hot reload and data-driven patching
+// tend to rebuild subtrees that are structurally identical to what they
+// replace; a driver that parks the replaced widgets here and checks the pool
+// before building fresh ones skips the allocation and initial layout for
+// every hit. Keyed by component name + structural id, so a recycled instance
+// is always the same widget type in the same tree position - the caller only
+// has to reset its parameters. The pool is plain storage : it works for any
+// retained representation (unmounted `NewWidget`s, virtual nodes, ..).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PoolKey {
+    pub name: String,
+    // tree path + declared id, see `structural_id`
+    pub structural_id: String,
+}
+
+impl PoolKey {
+    pub fn new(name:&str, structural_id:&str) -> Self {
+        Self { name: name.to_string(), structural_id: structural_id.to_string() }
+    }
+}
+
+// Stable identity for a tree position : the child-index path from the root
+// plus the declared `#id` when there is one, e.g. `0/2#save`. Widgets with an
+// id keep matching after siblings shift; anonymous ones match by position.
+pub fn structural_id(path:&[usize], c:&skui::Component) -> String {
+    let mut s = path.iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join("/");
+    if let Some(id) = c.id {
+        s.push('#');
+        s.push_str(id);
+    }
+    s
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PoolStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub parked: usize,
+    // parked entries dropped over capacity (oldest first)
+    pub evicted: usize,
+}
+
+pub struct WidgetPool<W> {
+    entries: std::collections::HashMap<PoolKey, W>,
+    // check-in order for capacity eviction
+    order: std::collections::VecDeque<PoolKey>,
+    // 0 = unlimited
+    capacity: usize,
+    stats: PoolStats,
+}
+
+impl <W> Default for WidgetPool<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl <W> WidgetPool<W> {
+    pub fn new() -> Self {
+        Self { entries: Default::default(), order: Default::default(), capacity: 0, stats: Default::default() }
+    }
+
+    pub fn with_capacity(capacity:usize) -> Self {
+        Self { capacity, ..Self::new() }
+    }
+
+    // Park a replaced widget for reuse. Re-parking a live key replaces the
+    // previous instance; over capacity the oldest entry is dropped.
+    pub fn park(&mut self, key:PoolKey, widget:W) {
+        if self.entries.insert(key.clone(), widget).is_none() {
+            self.order.push_back(key);
+        }
+        self.stats.parked += 1;
+        if self.capacity > 0 {
+            while self.entries.len() > self.capacity {
+                let Some(oldest) = self.order.pop_front() else { break };
+                self.entries.remove(&oldest);
+                self.stats.evicted += 1;
+            }
+        }
+    }
+
+    // Take a compatible instance out of the pool; the caller resets its
+    // parameters and mounts it instead of building fresh.
+    pub fn recycle(&mut self, key:&PoolKey) -> Option<W> {
+        match self.entries.remove(key) {
+            Some(widget) => {
+                self.order.retain( |k| k != key );
+                self.stats.hits += 1;
+                Some(widget)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+
+    // Drop everything parked (e.g. after a document swap where nothing is
+    // structurally compatible anymore); stats survive for reporting.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+// Snapshot of user-visible UI state, keyed by widget id. The driver fills it
+// from the widgets it knows about before shutdown and applies it back after
+// the next build, so state survives sessions without bespoke code per widget.
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct UiState {
+    pub bound_values: std::collections::BTreeMap<String,String>,
+    pub active_pages: std::collections::BTreeMap<String,usize>,
+    pub expanded: std::collections::BTreeMap<String,bool>,
+    pub scroll_offsets: std::collections::BTreeMap<String,(f64,f64)>,
+}
+
+impl UiState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Line-based blob : `kind<TAB>id<TAB>value`, ids percent-free since they are
+    // widget tags. Stable across releases; unknown kinds are ignored on restore.
+    pub fn save_state(&self) -> String {
+        let mut out = String::new();
+        for (id,v) in self.bound_values.iter() {
+            out.push_str( &format!("value\t{id}\t{}\n", v.replace('\n', "\\n")) );
+        }
+        for (id,v) in self.active_pages.iter() {
+            out.push_str( &format!("page\t{id}\t{v}\n") );
+        }
+        for (id,v) in self.expanded.iter() {
+            out.push_str( &format!("expanded\t{id}\t{v}\n") );
+        }
+        for (id,(x,y)) in self.scroll_offsets.iter() {
+            out.push_str( &format!("scroll\t{id}\t{x} {y}\n") );
+        }
+        out
+    }
+
+    pub fn restore_state(blob:&str) -> Self {
+        let mut state = Self::default();
+        for line in blob.lines() {
+            let mut parts = line.splitn(3, '\t');
+            let (Some(kind), Some(id), Some(value)) = (parts.next(), parts.next(), parts.next())
+            else { continue };
+            match kind {
+                "value" => { state.bound_values.insert(id.to_string(), value.replace("\\n", "\n")); }
+                "page" => if let Ok(v) = value.parse() {
+                    state.active_pages.insert(id.to_string(), v);
+                }
+                "expanded" => if let Ok(v) = value.parse() {
+                    state.expanded.insert(id.to_string(), v);
+                }
+                "scroll" => {
+                    let mut xy = value.split(' ');
+                    if let (Some(Ok(x)), Some(Ok(y))) = (xy.next().map(str::parse), xy.next().map(str::parse)) {
+                        state.scroll_offsets.insert(id.to_string(), (x,y));
+                    }
+                }
+                _ => {} //forward compatible
+            }
+        }
+        state
+    }
+}
+
+// One owner for the resources a multi-document app shares across windows :
+// the widget tag registry, stream bindings, the style invalidator, the
+// mutation journal, UI state, theme tokens and a document-source cache.
+// Supersedes reaching for the process-wide globals (`WID_TABLE`, installed
+// `BuildOptions`) from application code; the static builder entry points keep
+// working, but apps hold one of these and thread it to their windows.
+pub struct SkuiRuntime {
+    pub options: crate::options::BuildOptions,
+    pub build_config: crate::params::BuildConfig,
+    pub bindings: StreamBindings,
+    pub invalidator: StyleInvalidator,
+    pub journal: MutationJournal,
+    pub ui_state: UiState,
+    tags: std::collections::HashMap<String, &'static str>,
+    theme: std::collections::BTreeMap<String, String>,
+    documents: std::collections::HashMap<String, String>,
+}
+
+impl Default for SkuiRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkuiRuntime {
+    pub fn new() -> Self {
+        Self {
+            options: crate::options::BuildOptions::new(),
+            build_config: crate::params::BuildConfig::new(),
+            bindings: StreamBindings::new(),
+            invalidator: StyleInvalidator::new(),
+            journal: MutationJournal::new(),
+            ui_state: UiState::new(),
+            tags: Default::default(),
+            theme: Default::default(),
+            documents: Default::default(),
+        }
+    }
+
+    // Instance-scoped widget id registry (same leak-once semantics as the
+    // `WID_TABLE` global : `WidgetTag` wants `'static` names).
+    pub fn widget_id(&mut self, map_id:&str) -> &'static str {
+        if let Some(&id) = self.tags.get(map_id) {
+            return id;
+        }
+        let leaked: &'static str = Box::leak(map_id.to_string().into_boxed_str());
+        self.tags.insert(map_id.to_string(), leaked);
+        leaked
+    }
+
+    pub fn set_theme_value(&mut self, name:&str, value:&str) {
+        self.theme.insert(name.to_string(), value.to_string());
+    }
+
+    // Theme tokens as an `Env` : pass to `new_main_with_env` and documents
+    // read them as `${env.theme.name}`.
+    pub fn theme_env(&self) -> crate::params::Env<'_> {
+        let theme = self.theme.iter()
+            .map( |(k,v)| (k.as_str(), skui::Value::String(v.as_str())) )
+            .collect();
+        skui::Parameters::Map( [("theme", skui::Value::Map(theme))].into() )
+    }
+
+    // Document-source cache : windows re-parse from here instead of each
+    // keeping its own copy of shared fragments.
+    pub fn insert_document(&mut self, name:&str, source:&str) {
+        self.documents.insert(name.to_string(), source.to_string());
+    }
+
+    pub fn document_source(&self, name:&str) -> Option<&str> {
+        self.documents.get(name).map(String::as_str)
+    }
+
+    pub fn remove_document(&mut self, name:&str) -> bool {
+        self.documents.remove(name).is_some()
+    }
+
+    // Make this runtime's build options current for the following builds on
+    // this thread (the builder entry points are static trait fns; see
+    // `BuildOptions::install`).
+    pub fn install_options(&mut self) {
+        std::mem::take(&mut self.options).install();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_binding() {
+        let mut bindings = StreamBindings::new();
+        let (tx,rx) = std::sync::mpsc::channel();
+        bindings.bind_stream("log_lines", rx);
+
+        tx.send("line 1".to_string()).unwrap();
+        tx.send("line 2".to_string()).unwrap();
+        let updates = bindings.poll();
+        assert_eq!( updates.len(), 2 );
+        assert_eq!( bindings.latest("log_lines"), Some("line 2") );
+
+        //disconnected sender unbinds the key
+        drop(tx);
+        assert!( bindings.poll().is_empty() );
+        assert!( bindings.streams.is_empty() );
+        //last value survives the unbind
+        assert_eq!( bindings.latest("log_lines"), Some("line 2") );
+    }
+
+    #[test]
+    fn class_if() {
+        let src = r#"
+            Main:
+            Flex() {
+                Label("row") #row {
+                    class-if: { selected: ${item.selected}, overdue: ${item.overdue}, pinned: true }
+                }
+            }
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+
+        let mut item = std::collections::HashMap::new();
+        item.insert("selected", skui::Value::Bool(true));
+        item.insert("overdue", skui::Value::Bool(false));
+        let mut args = std::collections::HashMap::new();
+        args.insert("item", skui::Value::Map(item));
+        let params = skui::Parameters::Map(args);
+
+        let stack = crate::params::ParamsStack::new_main(&params, &doc).unwrap();
+        let label = stack.new_stack( &stack.component.children[0] );
+        let entries = label.class_if();
+        assert_eq!( entries.len(), 3 );
+        let active: Vec<&str> = entries.iter().filter( |e| e.active ).map( |e| e.class ).collect();
+        assert!( active.contains(&"selected") );
+        assert!( active.contains(&"pinned") );
+        assert!( !active.contains(&"overdue") );
+
+        //bound entries land in the registry under their dotted path
+        record_class_bindings( label.get_id(), &entries );
+        let hits = class_bindings_for("item.selected");
+        assert_eq!( hits.len(), 1 );
+        assert_eq!( hits[0].target, "row" );
+        assert_eq!( hits[0].class, "selected" );
+        //constant `pinned: true` has nothing to watch
+        assert!( class_bindings_for("pinned").is_empty() );
+        clear_class_bindings();
+    }
+
+    #[test]
+    fn style_invalidation() {
+        use skui::selector::{PseudoState, PseudoStateProvider};
+        let src = r#"
+            Main:
+            Flex() #panel {
+                Button("ok") #save
+                Label("hint") #hint
+            }
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+
+        let mut inv = StyleInvalidator::new();
+
+        //a state change dirties exactly that widget; repeating it is a no-op
+        inv.set_hovered("save", true);
+        assert!( inv.is_dirty("save") );
+        assert!( !inv.is_dirty("hint") );
+        assert!( inv.pseudo_state("save").hovered );
+        assert_eq!( inv.take_dirty(), ["save"] );
+        inv.set_hovered("save", true);
+        assert!( inv.take_dirty().is_empty() );
+        inv.set_hovered("save", false);
+        assert_eq!( inv.take_dirty(), ["save"] );
+
+        //the provider feeds live re-resolution
+        assert!( !inv.pseudo_state("save").hovered );
+        assert_eq!( inv.pseudo_state("unknown").focused, PseudoState::default().focused );
+
+        //binding changes route through the class-binding registry
+        record_class_bindings( Some("hint"), &[crate::params::ClassIf {
+            class:"visible", active:true, binding:Some(&[skui::ValueKey::Name("form"), skui::ValueKey::Name("touched")]),
+        }] );
+        inv.binding_changed("form.touched");
+        assert_eq!( inv.take_dirty(), ["hint"] );
+        //drop only this test's registry entries (the registry is global)
+        record_class_bindings( Some("hint"), &[] );
+
+        //inherited properties dirty the identified descendants too
+        inv.mark_dirty_with_descendants(&doc, "panel");
+        assert_eq!( inv.take_dirty(), ["hint", "panel", "save"] );
+    }
+
+    #[test]
+    fn runtime_shared_resources() {
+        let mut rt = SkuiRuntime::new();
+
+        //the tag registry hands back the same leaked id per name
+        let a = rt.widget_id("save_button");
+        let b = rt.widget_id("save_button");
+        assert!( std::ptr::eq(a, b) );
+        assert_ne!( rt.widget_id("other"), a );
+
+        //theme tokens reach documents as `${env.theme.*}`
+        rt.set_theme_value("accent", "#ff8800");
+        let src = "Main:\nLabel(${env.theme.accent})";
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+        let params = skui::Parameters::empty();
+        let env = rt.theme_env();
+        let stack = crate::params::ParamsStack::new_main_with_env(&params, Some(&env), &doc).unwrap();
+        assert_eq!( stack.get_text(0, "").unwrap().as_ref(), "#ff8800" );
+
+        //the document cache serves shared fragments to every window
+        rt.insert_document("sidebar", "Main:\nLabel(\"side\")");
+        assert!( rt.document_source("sidebar").unwrap().contains("side") );
+        assert!( rt.remove_document("sidebar") );
+        assert!( !rt.remove_document("sidebar") );
+    }
+
+    #[test]
+    fn state_roundtrip() {
+        let mut state = UiState::new();
+        state.bound_values.insert("name_input".into(), "multi\nline".into());
+        state.active_pages.insert("tabs".into(), 2);
+        state.expanded.insert("details".into(), true);
+        state.scroll_offsets.insert("list".into(), (0.0, 142.5));
+
+        let blob = state.save_state();
+        println!("{blob}");
+        assert_eq!( UiState::restore_state(&blob), state );
+
+        //unknown kinds are skipped, not errors
+        let restored = UiState::restore_state("future\tid\twhatever\npage\ttabs\t1\n");
+        assert_eq!( restored.active_pages.get("tabs"), Some(&1) );
+    }
+
+    #[test]
+    fn widget_pool() {
+        let src = r#"
+            Main:
+            Flex() {
+                Button("ok") #save
+                Label("hint")
+            }
+        "#;
+        let tks = skui::TokenAndSpan::new(src);
+        let doc = skui::SKUI::parse(&tks).unwrap();
+        let root = &doc.components[0].component;
+
+        //identified widgets key by path+id, anonymous ones by path alone
+        assert_eq!( structural_id(&[0,0], &root.children[0]), "0/0#save" );
+        assert_eq!( structural_id(&[0,1], &root.children[1]), "0/1" );
+
+        //the pool here holds strings standing in for retained widgets
+        let mut pool: WidgetPool<String> = WidgetPool::new();
+        let key = PoolKey::new("Button", "0/0#save");
+        pool.park( key.clone(), "old button".to_string() );
+        assert_eq!( pool.recycle(&key), Some("old button".to_string()) );
+        //a hit consumes the entry
+        assert_eq!( pool.recycle(&key), None );
+        assert_eq!( pool.stats(), PoolStats { hits: 1, misses: 1, parked: 1, evicted: 0 } );
+
+        //capacity evicts oldest first
+        let mut pool: WidgetPool<u32> = WidgetPool::with_capacity(2);
+        pool.park( PoolKey::new("Label", "0"), 0 );
+        pool.park( PoolKey::new("Label", "1"), 1 );
+        pool.park( PoolKey::new("Label", "2"), 2 );
+        assert_eq!( pool.len(), 2 );
+        assert_eq!( pool.recycle(&PoolKey::new("Label", "0")), None );
+        assert_eq!( pool.recycle(&PoolKey::new("Label", "2")), Some(2) );
+        assert_eq!( pool.stats().evicted, 1 );
+    }
+
+    #[test]
+    fn journal() {
+        let mut journal = MutationJournal::new();
+        journal.record( Mutation::AddClass { target: "title".into(), class: "bold".into() } );
+        journal.record( Mutation::SetBinding { target: "count".into(), key: "text".into(), old: "0".into(), new: "1".into() } );
+
+        //undo returns inverses, newest first
+        assert_eq!(
+            journal.undo(),
+            Some( Mutation::SetBinding { target: "count".into(), key: "text".into(), old: "1".into(), new: "0".into() } )
+        );
+        assert_eq!(
+            journal.undo(),
+            Some( Mutation::RemoveClass { target: "title".into(), class: "bold".into() } )
+        );
+        assert!( journal.undo().is_none() );
+
+        //redo replays the original operations in order
+        assert_eq!(
+            journal.redo(),
+            Some( Mutation::AddClass { target: "title".into(), class: "bold".into() } )
+        );
+        assert!( journal.can_redo() );
+
+        //new record invalidates the remaining redo history
+        journal.record( Mutation::RemoveClass { target: "title".into(), class: "bold".into() } );
+        assert!( !journal.can_redo() );
+    }
+}
@@ -0,0 +1,174 @@
+use masonry::core::DefaultProperties;
+use masonry::kurbo::Size;
+use masonry_testing::TestHarness;
+use skui::{Parameters, TokenAndSpan, SKUI};
+use crate::params::ParamsStack;
+use crate::{BasicWidgetBuilder, Error, RootWidgetBuilder};
+
+// Offscreen rendering through the masonry test harness. Lets documentation,
+// snapshot tests and the HTML exporter embed actual screenshots of a document
+// without opening a window.
+
+pub fn render_to_png(src:&str, size:(u32,u32)) -> Result<Vec<u8>, Error> {
+    let tks = TokenAndSpan::new(src);
+    let skui = SKUI::parse(&tks)?;
+    let parameters = Parameters::empty();
+    let params_stack = ParamsStack::new_main(&parameters, &skui).ok_or(Error::RootComponentNotFound)?;
+    let root = BasicWidgetBuilder::build_widget(&params_stack)?.erased();
+
+    let mut harness = TestHarness::create_with_size(
+        DefaultProperties::new(),
+        root,
+        Size::new(size.0 as f64, size.1 as f64),
+    );
+    let image = harness.render();
+    Ok( encode_png(image.width(), image.height(), image.as_raw()) )
+}
+
+// Result of a headless run : the widget tree dump plus the last rendered frame.
+pub struct HeadlessReport {
+    pub frames: usize,
+    pub tree: String,
+    pub png: Vec<u8>,
+}
+
+// True when the process should run headless (`SKUI_HEADLESS=1`), e.g. in CI
+// without a display server.
+pub fn headless_requested() -> bool {
+    std::env::var("SKUI_HEADLESS").is_ok_and( |v| v != "0" )
+}
+
+// Exercise the full build/render path off screen : render `frames` frames
+// through the test harness and report the tree plus the final frame as PNG.
+pub fn run_headless(root:masonry::core::NewWidget<dyn masonry::core::Widget>, size:(u32,u32), frames:usize) -> HeadlessReport {
+    let mut harness = TestHarness::create_with_size(
+        DefaultProperties::new(),
+        root,
+        Size::new(size.0 as f64, size.1 as f64),
+    );
+    let mut image = harness.render();
+    for _ in 1..frames {
+        image = harness.render();
+    }
+    HeadlessReport {
+        frames: frames.max(1),
+        tree: format!("{:#?}", harness.root_widget()),
+        png: encode_png(image.width(), image.height(), image.as_raw()),
+    }
+}
+
+// Headless entry for example `main`s; returns true when the run was handled.
+// `SKUI_HEADLESS_FRAMES` / `SKUI_HEADLESS_SIZE` (`400x300`) tune the run and
+// `SKUI_HEADLESS_PNG` writes the final frame to the given path.
+pub fn run_headless_from_env(make_root:impl FnOnce() -> masonry::core::NewWidget<dyn masonry::core::Widget>) -> bool {
+    if !headless_requested() {
+        return false;
+    }
+    let frames = std::env::var("SKUI_HEADLESS_FRAMES").ok()
+        .and_then( |v| v.parse().ok() )
+        .unwrap_or(1);
+    let size = std::env::var("SKUI_HEADLESS_SIZE").ok()
+        .and_then( |v| {
+            let (w, h) = v.split_once('x')?;
+            Some( (w.parse().ok()?, h.parse().ok()?) )
+        } )
+        .unwrap_or( (400, 400) );
+
+    let report = run_headless(make_root(), size, frames);
+    println!("{}", report.tree);
+    if let Ok(path) = std::env::var("SKUI_HEADLESS_PNG") {
+        if let Err(e) = std::fs::write(&path, &report.png) {
+            eprintln!("failed to write {path}: {e}");
+        }
+    }
+    true
+}
+
+// Minimal PNG encoder (RGBA8, stored deflate blocks). Avoids pulling an image
+// crate in just for the snapshot path; output is valid if unoptimized PNG.
+fn encode_png(width:u32, height:u32, rgba:&[u8]) -> Vec<u8> {
+    //filter byte 0 in front of every scanline
+    let stride = width as usize * 4;
+    let mut raw = Vec::with_capacity( (stride + 1) * height as usize );
+    for row in rgba.chunks(stride) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+
+    let mut ihdr = Vec::new();
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]); //8bit, RGBA
+    write_chunk(&mut out, b"IHDR", &ihdr);
+
+    write_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn write_chunk(out:&mut Vec<u8>, tag:&[u8;4], data:&[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(tag);
+    out.extend_from_slice(data);
+    let mut crc_input = Vec::with_capacity(4 + data.len());
+    crc_input.extend_from_slice(tag);
+    crc_input.extend_from_slice(data);
+    out.extend_from_slice(&crc32(&crc_input).to_be_bytes());
+}
+
+// zlib stream with uncompressed (stored) deflate blocks
+fn zlib_stored(data:&[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut chunks = data.chunks(0xffff).peekable();
+    if data.is_empty() {
+        out.extend_from_slice(&[0x01, 0x00, 0x00, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        out.push( if chunks.peek().is_none() { 1 } else { 0 } );
+        let len = chunk.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data:&[u8]) -> u32 {
+    let mut a = 1u32;
+    let mut b = 0u32;
+    for &byte in data.iter() {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn crc32(data:&[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data.iter() {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_container() {
+        //2x2 red square through the raw encoder
+        let rgba = [255,0,0,255].repeat(4);
+        let png = encode_png(2, 2, &rgba);
+        assert_eq!( &png[..8], &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'] );
+        assert_eq!( &png[12..16], b"IHDR" );
+        assert!( png.windows(4).any( |w| w == b"IEND" ) );
+    }
+}
@@ -7,13 +7,87 @@ use masonry::core::StyleProperty as MasonryStyleProperty;
 use masonry::parley::LineHeight;
 use skui::selector::PseudoClass;
 
+// Base values `em`/`pt` lengths resolve against. The driver sets this once
+// (or per monitor on DPI change) before building.
+#[derive(Debug, Clone, Copy)]
+pub struct LengthContext {
+    pub base_font_size: f64,
+    pub dpi: f64,
+}
+
+impl Default for LengthContext {
+    fn default() -> Self {
+        Self { base_font_size: 16.0, dpi: 96.0 }
+    }
+}
+
+thread_local! {
+    static LENGTH_CONTEXT: std::cell::Cell<LengthContext> = std::cell::Cell::new( LengthContext::default() );
+}
+
+pub fn set_length_context(ctx:LengthContext) {
+    LENGTH_CONTEXT.with( |c| c.set(ctx) );
+}
+
+pub fn length_context() -> LengthContext {
+    LENGTH_CONTEXT.with( |c| c.get() )
+}
+
+// Absolute pixels for any length-ish value : `1.2em` scales the base font
+// size, `12pt` converts through DPI (72pt per inch), bare numbers pass as px.
+pub fn resolve_px(value:&CssValue) -> Option<f64> {
+    let ctx = length_context();
+    match value {
+        CssValue::Px(v) | CssValue::Number(v) => Some(*v),
+        CssValue::Em(v) => Some( v * ctx.base_font_size ),
+        CssValue::Pt(v) => Some( v * ctx.dpi / 72.0 ),
+        _ => None,
+    }
+}
+
+// Color-string parsing (`#rrggbb`, named colors) goes through a full CSS
+// color parser; a large document with shared classes re-converts the same
+// handful of strings once per widget per rule. Memoized per thread (style
+// conversion runs on the UI thread) - failed parses are cached too so a typo
+// doesn't re-parse on every rebuild. Length math stays uncached : it is plain
+// arithmetic. Rgb/Rgba literals skip the cache for the same reason.
+thread_local! {
+    static COLOR_CACHE: std::cell::RefCell<std::collections::HashMap<String, Option<AlphaColor<Srgb>>>> = Default::default();
+    static COLOR_CACHE_STATS: std::cell::Cell<(usize,usize)> = const { std::cell::Cell::new((0,0)) };
+}
+
+fn parse_color_cached(key:&str) -> Option<AlphaColor<Srgb>> {
+    COLOR_CACHE.with( |cache| {
+        let mut cache = cache.borrow_mut();
+        let (hits, misses) = COLOR_CACHE_STATS.get();
+        if let Some(&cached) = cache.get(key) {
+            COLOR_CACHE_STATS.set( (hits + 1, misses) );
+            return cached;
+        }
+        COLOR_CACHE_STATS.set( (hits, misses + 1) );
+        let parsed = AlphaColor::from_str(key).ok();
+        cache.insert(key.to_string(), parsed);
+        parsed
+    })
+}
+
+// (hits, misses) of the color memo on this thread, for perf reporting.
+pub fn color_cache_stats() -> (usize, usize) {
+    COLOR_CACHE_STATS.get()
+}
+
+pub fn clear_color_cache() {
+    COLOR_CACHE.with( |cache| cache.borrow_mut().clear() );
+    COLOR_CACHE_STATS.set( (0,0) );
+}
+
 pub fn to_color_from_value(value:CssValue) -> Option<AlphaColor<Srgb>> {
     let v = match value {
-        CssValue::HexColor(col) => AlphaColor::from_str( &format!("#{col}") ).ok()?,
+        CssValue::HexColor(col) => parse_color_cached( &format!("#{col}") )?,
         CssValue::Rgb( (r,g,b) )  => AlphaColor::from_rgb8( r, g, b ),
         CssValue::Rgba( (r,g,b,a) ) => AlphaColor::from_rgba8( r, g, b, a ),
         CssValue::Ident( str ) => {
-            AlphaColor::from_str(str).ok()?
+            parse_color_cached(str)?
         },
         _ => return None
     };
@@ -35,10 +109,10 @@ pub fn to_content_color(prop:&StyleProperty) -> Option<ContentColor> {
 pub fn to_border(prop:&StyleProperty) -> (Option<BorderWidth>, Option<BorderColor>) {
     let (width, color) = match &prop.values.as_slice() {
         &[width, CssValue::Ident(_brush), color] => {
-            (width.as_f64(), to_color_from_value(*color))
+            (resolve_px(&width), to_color_from_value(*color))
         }
         &[width, color] => {
-            (width.as_f64(), to_color_from_value(*color))
+            (resolve_px(&width), to_color_from_value(*color))
         }
         _ => (None, None)
     };
@@ -47,7 +121,7 @@ pub fn to_border(prop:&StyleProperty) -> (Option<BorderWidth>, Option<BorderColo
 
 pub fn to_font_size(prop:&StyleProperty) -> Option<MasonryStyleProperty> {
     Some(
-        MasonryStyleProperty::FontSize( prop.values.get(0)?.as_f64()? as _ )
+        MasonryStyleProperty::FontSize( resolve_px( prop.values.get(0)? )? as _ )
     )
 }
 
@@ -55,6 +129,7 @@ pub fn to_lineheight(prop:&StyleProperty) -> Option<MasonryStyleProperty> {
     let v = match prop.values.get(0)? {
         CssValue::Number(v) => LineHeight::FontSizeRelative( *v as _ ),
         CssValue::Px(v) => LineHeight::Absolute( *v as _ ),
+        v @ (CssValue::Em(_) | CssValue::Pt(_)) => LineHeight::Absolute( resolve_px(v)? as _ ),
         CssValue::Percent(v) => LineHeight::MetricsRelative( *v as _ ),
         _ => return None
     };
@@ -73,7 +148,7 @@ pub fn style_parse(build_prop:bool, build_styles:bool, style:&Style, props:&mut
                     if let Some(w) = w { props.insert(w); }
                     if let Some(c) = c { props.insert(c); }
                 }
-                "border-width" => if let Some(v) = property.as_f64() {
+                "border-width" => if let Some(v) = property.values.get(0).and_then(resolve_px) {
                     props.insert(BorderWidth::all(v));
                 }
                 "border-color" => if let Some(v) = to_color(property) {
@@ -84,10 +159,10 @@ pub fn style_parse(build_prop:bool, build_styles:bool, style:&Style, props:&mut
                         v @ _ => { eprintln!("Unknown border-color pseudo state : {v:?}"); }
                     };
                 }
-                "padding" => if let Some(v) = property.as_f64() {
+                "padding" => if let Some(v) = property.values.get(0).and_then(resolve_px) {
                     props.insert(Padding::all(v));
                 }
-                "gap" => if let Some(v) = property.as_f64() {
+                "gap" => if let Some(v) = property.values.get(0).and_then(resolve_px) {
                     props.insert(Gap::from(Length::px(v as _)));
                 },
                 "background-color" => {
@@ -130,4 +205,49 @@ pub fn style_parse(build_prop:bool, build_styles:bool, style:&Style, props:&mut
         }
 
     });
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_units() {
+        set_length_context( LengthContext::default() );
+        assert_eq!( resolve_px(&CssValue::Px(12.0)), Some(12.0) );
+        assert_eq!( resolve_px(&CssValue::Number(12.0)), Some(12.0) );
+        //1.5em * 16px base
+        assert_eq!( resolve_px(&CssValue::Em(1.5)), Some(24.0) );
+        //72pt = 1in = 96px at default DPI
+        assert_eq!( resolve_px(&CssValue::Pt(72.0)), Some(96.0) );
+        assert_eq!( resolve_px(&CssValue::Percent(50.0)), None );
+
+        //a larger base font scales em lengths
+        set_length_context( LengthContext { base_font_size: 20.0, ..LengthContext::default() } );
+        assert_eq!( resolve_px(&CssValue::Em(2.0)), Some(40.0) );
+        set_length_context( LengthContext::default() );
+    }
+
+    #[test]
+    fn color_memo() {
+        clear_color_cache();
+
+        //first conversion parses, repeats hit the memo
+        let first = to_color_from_value( CssValue::HexColor("ff8800") );
+        assert!( first.is_some() );
+        assert_eq!( color_cache_stats(), (0,1) );
+        assert_eq!( to_color_from_value( CssValue::HexColor("ff8800") ), first );
+        assert_eq!( to_color_from_value( CssValue::HexColor("ff8800") ), first );
+        assert_eq!( color_cache_stats(), (2,1) );
+
+        //named colors share the cache; failed parses are cached too
+        assert!( to_color_from_value( CssValue::Ident("rebeccapurple") ).is_some() );
+        assert!( to_color_from_value( CssValue::Ident("not-a-color") ).is_none() );
+        assert!( to_color_from_value( CssValue::Ident("not-a-color") ).is_none() );
+        assert_eq!( color_cache_stats(), (3,3) );
+
+        //rgb literals bypass it entirely
+        assert!( to_color_from_value( CssValue::Rgb((1,2,3)) ).is_some() );
+        assert_eq!( color_cache_stats(), (3,3) );
+        clear_color_cache();
+    }
+}
@@ -0,0 +1,123 @@
+use skui::{Parameters, Style, SKUI};
+use crate::backend::{self, Backend, ContainerAxis};
+use crate::Error;
+
+// Text-mode preview backend : approximates Flex/Grid/Label/Button layout with
+// ASCII boxes. Good enough to check document structure on headless machines
+// and in CI logs; no styling beyond the box borders.
+
+#[derive(Debug,Clone,Default)]
+pub struct TermNode {
+    lines: Vec<String>,
+}
+
+impl TermNode {
+    fn width(&self) -> usize {
+        self.lines.iter().map( |l| l.chars().count() ).max().unwrap_or(0)
+    }
+
+    fn pad_to(&mut self, width:usize) {
+        for line in self.lines.iter_mut() {
+            let missing = width.saturating_sub( line.chars().count() );
+            line.push_str( &" ".repeat(missing) );
+        }
+    }
+}
+
+impl std::fmt::Display for TermNode {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for line in self.lines.iter() {
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug,Default)]
+pub struct TermBackend;
+
+impl Backend for TermBackend {
+    type Node = TermNode;
+
+    fn create_label(&mut self, text:&str) -> Self::Node {
+        TermNode { lines: text.lines().map( |l| l.to_string() ).collect() }
+    }
+
+    fn create_button(&mut self, label:&str) -> Self::Node {
+        TermNode { lines: vec![ format!("[ {label} ]") ] }
+    }
+
+    fn create_container(&mut self, axis:ContainerAxis, children:Vec<Self::Node>) -> Self::Node {
+        let mut inner = TermNode::default();
+        match axis {
+            ContainerAxis::Vertical => {
+                for c in children.into_iter() {
+                    inner.lines.extend( c.lines );
+                }
+            }
+            ContainerAxis::Horizontal => {
+                let mut children = children;
+                let height = children.iter().map( |c| c.lines.len() ).max().unwrap_or(0);
+                for c in children.iter_mut() {
+                    let w = c.width();
+                    c.lines.resize( height, String::new() );
+                    c.pad_to(w);
+                }
+                for row in 0..height {
+                    let line = children.iter()
+                        .map( |c| c.lines[row].as_str() )
+                        .collect::<Vec<_>>()
+                        .join("  ");
+                    inner.lines.push(line);
+                }
+            }
+        }
+        //frame the container so nesting stays visible
+        let w = inner.width();
+        let mut framed = TermNode { lines: vec![ format!("+{}+", "-".repeat(w + 2)) ] };
+        inner.pad_to(w);
+        for line in inner.lines.into_iter() {
+            framed.lines.push( format!("| {line} |") );
+        }
+        framed.lines.push( format!("+{}+", "-".repeat(w + 2)) );
+        framed
+    }
+
+    fn apply_style(&mut self, _node:&mut Self::Node, _style:&Style) {
+        //box preview ignores styling
+    }
+
+    fn create_placeholder(&mut self, name:&str) -> Self::Node {
+        TermNode { lines: vec![ format!("<{name}>") ] }
+    }
+}
+
+pub fn preview<'a>(params:&'a Parameters<'a>, skui:&'a SKUI<'a>) -> Result<String, Error> {
+    let mut term = TermBackend::default();
+    let node = backend::build_root(&mut term, params, skui)?;
+    Ok( node.to_string() )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use skui::TokenAndSpan;
+
+    #[test]
+    fn box_preview() {
+        let src = r#"
+            Main:
+            Flex() {
+                Label("hello")
+                Button("ok")
+            }
+        "#;
+        let tks = TokenAndSpan::new(src);
+        let skui = SKUI::parse(&tks).unwrap();
+        let params = Parameters::empty();
+        let out = preview(&params, &skui).unwrap();
+        println!("{out}");
+        assert!( out.contains("hello") );
+        assert!( out.contains("[ ok ]") );
+    }
+}
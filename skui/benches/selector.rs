@@ -0,0 +1,65 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use skui::selector::{AncestorFilter, PseudoState};
+use skui::{TokenAndSpan, SKUI};
+
+// Deep synthetic tree : `depth` nested Flexes, each with a class, a Label at
+// the bottom. Rules mostly name classes that exist nowhere on the chain, the
+// worst case for exact descendant matching.
+fn deep_source(depth: usize, rules: usize) -> String {
+    let mut src = String::new();
+    for i in 0..rules {
+        src.push_str(&format!(".missing{i} Label {{ padding: 1px }}\n"));
+    }
+    src.push_str(".level1 Label { padding: 2px }\n");
+    src.push_str("Main:\n");
+    for i in 0..depth {
+        src.push_str(&format!("Flex() .level{i} {{\n"));
+    }
+    src.push_str("Label(\"leaf\") #leaf\n");
+    for _ in 0..depth {
+        src.push('}');
+    }
+    src
+}
+
+fn bench_descendant_matching(c: &mut Criterion) {
+    let src = deep_source(64, 256);
+    let tks = TokenAndSpan::new(&src);
+    let skui = SKUI::parse(&tks).unwrap();
+
+    let leaf = skui.find_by_id("leaf").unwrap();
+    let main = skui.get_main_component().unwrap();
+    let mut parents = vec![];
+    main.component.find(&mut parents, leaf);
+
+    //bloom pre-filter in front of the exact walk (what get_styles does)
+    c.bench_function("descendant_bloom", |b| {
+        b.iter(|| {
+            let n = skui
+                .get_styles(std::hint::black_box(parents.as_slice()), leaf)
+                .count();
+            assert_eq!(n, 1);
+        })
+    });
+
+    //exact walk for every rule, for comparison
+    c.bench_function("descendant_exact", |b| {
+        b.iter(|| {
+            let parents = std::hint::black_box(parents.as_slice());
+            let n = skui
+                .styles
+                .iter()
+                .filter(|e| e.selector.is_matches(parents, leaf, PseudoState::default()))
+                .count();
+            assert_eq!(n, 1);
+        })
+    });
+
+    //the hash itself, built once per element
+    c.bench_function("ancestor_filter_build", |b| {
+        b.iter(|| AncestorFilter::from_parents(std::hint::black_box(parents.as_slice())))
+    });
+}
+
+criterion_group!(benches, bench_descendant_matching);
+criterion_main!(benches);
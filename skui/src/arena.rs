@@ -0,0 +1,215 @@
+use std::cell::RefCell;
+use thiserror::Error;
+use crate::{ParseOptions, SKUIParseError, TokenAndSpan, SKUI};
+
+// Supplies source text for `@import` paths : a filesystem loader in the app,
+// a fixture map in tests.
+pub trait SourceResolver {
+    fn resolve(&self, path:&str) -> Option<String>;
+}
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("no source for import {0:?}")]
+    Unresolved(String),
+
+    #[error("import cycle through {0:?}")]
+    Cycle(String),
+
+    #[error("parse error in {path:?}: {error:?}")]
+    Parse { path: String, error: SKUIParseError },
+}
+
+// Arena-backed parsing for the hot-reload path. The AST borrows both the
+// source text and the token stream, which normally chains three lifetimes the
+// caller has to keep alive by hand. An `Arena` owns both : sources and token
+// streams are appended and never dropped until the arena itself is, so
+// `parse_in` hands back a `SKUI` tied only to the arena.
+//
+// This is an append-only store, not a bump allocator — node containers
+// (children `Vec`s, property maps) still use the global allocator. What it
+// buys is one owner for everything a document borrows, cheap generation
+// turnover via `reset`, and no per-document lifetime bookkeeping.
+#[derive(Default)]
+pub struct Arena {
+    sources: RefCell<Vec<Box<str>>>,
+    //lifetime erased internally; entries borrow `sources` of the same arena
+    tokens: RefCell<Vec<Box<TokenAndSpan<'static>>>>,
+}
+
+impl Arena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Copy `s` into the arena. The contents of a `Box<str>` never move when
+    // the outer `Vec` grows, and nothing is dropped before the arena, so the
+    // returned reference is valid for the arena's whole life.
+    pub fn alloc_str<'arena>(&'arena self, s:&str) -> &'arena str {
+        let boxed: Box<str> = s.into();
+        let ptr: *const str = &*boxed;
+        self.sources.borrow_mut().push(boxed);
+        unsafe { &*ptr }
+    }
+
+    fn alloc_tokens<'arena>(&'arena self, src:&'arena str) -> &'arena TokenAndSpan<'arena> {
+        let tks = Box::new( TokenAndSpan::new(src) );
+        let ptr: *const TokenAndSpan = &*tks;
+        //erase the borrow of `src` : it lives exactly as long as this entry
+        self.tokens.borrow_mut().push( unsafe { std::mem::transmute::<Box<TokenAndSpan<'arena>>, Box<TokenAndSpan<'static>>>(tks) } );
+        unsafe { &*ptr }
+    }
+
+    pub fn source_count(&self) -> usize {
+        self.sources.borrow().len()
+    }
+
+    pub fn allocated_bytes(&self) -> usize {
+        self.sources.borrow().iter().map( |s| s.len() ).sum()
+    }
+
+    // Drop every generation at once. `&mut self` proves no parsed document
+    // still borrows the arena.
+    pub fn reset(&mut self) {
+        self.tokens.get_mut().clear();
+        self.sources.get_mut().clear();
+    }
+}
+
+impl <'a> SKUI<'a> {
+    // Parse a document whose whole backing storage lives in `arena`. `src`
+    // can be a temporary — it is copied in — and the result stays valid until
+    // the arena is reset or dropped.
+    pub fn parse_in(arena:&'a Arena, src:&str) -> Result<SKUI<'a>, SKUIParseError> {
+        Self::parse_in_with_options(arena, src, &ParseOptions::default())
+    }
+
+    pub fn parse_in_with_options(arena:&'a Arena, src:&str, opts:&ParseOptions) -> Result<SKUI<'a>, SKUIParseError> {
+        let src = arena.alloc_str(src);
+        let tks = arena.alloc_tokens(src);
+        SKUI::parse_with_options(tks, opts)
+    }
+
+    // Parse `src` and recursively resolve its `@import` directives. Imported
+    // documents merge first (in directive order) and the importing document
+    // last, so local definitions override imported ones - the same semantics
+    // as `SKUI::merge`. The entry document has no path of its own, so only
+    // cycles between imported files are detected.
+    pub fn parse_with_resolver(arena:&'a Arena, src:&str, resolver:&dyn SourceResolver) -> Result<SKUI<'a>, ImportError> {
+        let mut active = Vec::new();
+        parse_resolving(arena, "", src, resolver, &mut active)
+    }
+}
+
+fn parse_resolving<'a>(arena:&'a Arena, path:&str, src:&str, resolver:&dyn SourceResolver, active:&mut Vec<String>) -> Result<SKUI<'a>, ImportError> {
+    let doc = SKUI::parse_in(arena, src)
+        .map_err( |error| ImportError::Parse { path: path.to_string(), error } )?;
+    let mut merged: Option<SKUI> = None;
+    for import in doc.imports.iter() {
+        if active.iter().any( |p| p == import ) {
+            return Err(ImportError::Cycle(import.to_string()));
+        }
+        let text = resolver.resolve(import)
+            .ok_or_else( || ImportError::Unresolved(import.to_string()) )?;
+        active.push(import.to_string());
+        let imported = parse_resolving(arena, import, &text, resolver, active)?;
+        active.pop();
+        merged = Some(match merged {
+            Some(base) => SKUI::merge(&base, &imported),
+            None => imported,
+        });
+    }
+    Ok(match merged {
+        Some(base) => SKUI::merge(&base, &doc),
+        None => doc,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_in_arena() {
+        let arena = Arena::new();
+
+        //source is a temporary; the document outlives it
+        let doc = {
+            let src = String::from(r#"
+                Main:
+                Flex() { Label("one") }
+            "#);
+            SKUI::parse_in(&arena, &src).unwrap()
+        };
+        assert_eq!( doc.find_all_by_name("Label").len(), 1 );
+
+        //several generations share the arena (hot reload)
+        let doc2 = SKUI::parse_in(&arena, r#"
+            Main:
+            Flex() { Label("one") Label("two") }
+        "#).unwrap();
+        assert_eq!( doc2.find_all_by_name("Label").len(), 2 );
+        assert_eq!( arena.source_count(), 2 );
+        assert!( arena.allocated_bytes() > 0 );
+
+        //still readable after later allocations grew the internal vecs
+        assert_eq!( doc.components[0].component.children[0].name, "Label" );
+    }
+
+    #[test]
+    fn imports_resolved() {
+        struct MapResolver(std::collections::HashMap<&'static str, &'static str>);
+        impl SourceResolver for MapResolver {
+            fn resolve(&self, path:&str) -> Option<String> {
+                self.0.get(path).map( |s| s.to_string() )
+            }
+        }
+
+        let mut files = std::collections::HashMap::new();
+        files.insert("theme.skui", ".brand { color: #112233 }");
+        files.insert("widgets.skui", "@import \"theme.skui\"\nHeader:\nLabel(\"imported\") #title");
+        let resolver = MapResolver(files);
+
+        let arena = Arena::new();
+        let doc = SKUI::parse_with_resolver(&arena, r#"
+            @import "widgets.skui"
+            .brand { color: #445566 }
+            Main:
+            Flex() { Label("local") }
+        "#, &resolver).unwrap();
+
+        //styles from every document; the importing one comes last (cascade order)
+        assert_eq!( doc.styles.len(), 2 );
+        assert!( doc.get_root_component("Header").is_some() );
+        assert!( doc.get_root_component("Main").is_some() );
+        assert!( doc.find_by_id("title").is_some() );
+
+        //unknown paths and cycles surface as errors
+        assert!( matches!(
+            SKUI::parse_with_resolver(&arena, "@import \"missing.skui\"", &resolver),
+            Err(ImportError::Unresolved(_))
+        ));
+        let mut files = std::collections::HashMap::new();
+        files.insert("a.skui", "@import \"b.skui\"");
+        files.insert("b.skui", "@import \"a.skui\"");
+        let resolver = MapResolver(files);
+        assert!( matches!(
+            SKUI::parse_with_resolver(&arena, "@import \"a.skui\"", &resolver),
+            Err(ImportError::Cycle(_))
+        ));
+    }
+
+    #[test]
+    fn reset_frees_generations() {
+        let mut arena = Arena::new();
+        for _ in 0..8 {
+            SKUI::parse_in(&arena, "Main:\nLabel(\"x\")").unwrap();
+        }
+        assert_eq!( arena.source_count(), 8 );
+        arena.reset();
+        assert_eq!( arena.source_count(), 0 );
+        assert_eq!( arena.allocated_bytes(), 0 );
+        //usable again after reset
+        assert!( SKUI::parse_in(&arena, "Main:\nLabel(\"x\")").is_ok() );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{parse_style_item, resolve_css_vars, Cursor, ParseError, ParseOptions, Result, Style, TokenAndSpan, SKUI};
+use crate::cursor::SplitCursor;
+use crate::selector::SelectorParser;
+use crate::token::Token;
+
+// Interop with conventional `.css` files : designers keep styles in standard
+// CSS edited with existing tooling while layouts stay in `.skui`. The loader
+// reads the shared subset of the grammar - the selector forms this crate
+// matches plus the property syntax `parse_style_item` already accepts.
+// At-rules (`@media`, `@font-face`, ..) are skipped whole; selectors outside
+// the subset (`:root`, ..) are skipped with a diagnostic under
+// `ParseOptions::lenient` and fatal otherwise.
+
+pub fn parse_css<'a>(tks:&'a TokenAndSpan<'a>, opts:&ParseOptions) -> Result<Vec<Style<'a>>> {
+    let mut styles = vec![];
+    let mut cursor = tks.start_cursor();
+    while !cursor.is_eof() {
+        //at-rules are outside the loader's shared subset (`.skui` documents
+        //carry their own `@media` groups) : drop the prelude and, when one
+        //follows, the whole block (`@import "x.css";`, `@media .. { .. }`)
+        if let (next, [Token::At, Token::Ident(_)]) = cursor.fork().consume() {
+            cursor = skip_rule(next);
+            continue;
+        }
+
+        //trimmed to raw for selector, same as the main parse loop
+        cursor = tks.raw_cursor(cursor);
+        let span = cursor.span();
+        let selector = match SelectorParser::parse(cursor.fork()) {
+            Ok( (next, selector) ) => {
+                cursor = next;
+                selector
+            }
+            Err(e) => {
+                let e = ParseError::not_selector(span, e);
+                if opts.lenient {
+                    opts.push_diagnostic(e);
+                    cursor = skip_rule( tks.trimmed_cursor(cursor) );
+                    continue;
+                }
+                return Err(e);
+            }
+        };
+
+        cursor = tks.trimmed_cursor(cursor);
+        match parse_style_item(selector, cursor.fork()) {
+            Ok( (next, mut parsed) ) => {
+                styles.append(&mut parsed);
+                cursor = tks.trimmed_cursor(next);
+            }
+            Err(e) if opts.lenient => {
+                opts.push_diagnostic(e);
+                cursor = skip_rule(cursor);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok( styles )
+}
+
+// Recovery : drop tokens up to the end of the offending rule - its brace
+// block when one opens, otherwise the next `;`. Unlike `skip_statement`
+// (which resynchronizes on component-body statement heads) this never eats
+// into a following rule that happens to start with a class or id selector.
+fn skip_rule(mut cursor:Cursor<'_>) -> Cursor<'_> {
+    while !cursor.is_eof() {
+        let (n, t) = cursor.fork().consume_one();
+        if t == Token::LBrace {
+            if let Some( SplitCursor{next, ..} ) = cursor.consume_delimited_inner( Token::block_brace() ) {
+                return next;
+            }
+            return n;
+        }
+        if t == Token::Semicolon {
+            return n;
+        }
+        cursor = n;
+    }
+    cursor
+}
+
+impl <'a> SKUI<'a> {
+    // Parse a `.css` stylesheet and append its rules after this document's
+    // own, so the sheet wins ties in the cascade. `--name` declarations merge
+    // into `vars` (last one wins) and `var(..)` references within the sheet
+    // are substituted the same way `parse` does; references to variables the
+    // sheet does not declare itself are not resolved.
+    pub fn load_css(&mut self, tks:&'a TokenAndSpan<'a>, opts:&ParseOptions) -> Result<()> {
+        let mut styles = parse_css(tks, opts)?;
+        let vars = resolve_css_vars(&mut styles);
+        self.vars.extend(vars);
+        self.styles.append(&mut styles);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selector::{Selector, SelectorKind, SimpleSelector};
+    use crate::TokenAndSpan;
+
+    #[test]
+    fn conventional_sheet() {
+        let css = r#"
+            /* palette shared with the web build */
+            @import url(base.css);
+            .title { color: #ff0000; padding: 4px; }
+            #root > Button:hover { width: 50%; }
+            @media (max-width: 600px) {
+                .title { padding: 2px; }
+            }
+            Label, .hint { color: #888888; }
+        "#;
+        let tks = TokenAndSpan::new(css);
+        let styles = parse_css(&tks, &ParseOptions::default()).unwrap();
+
+        //at-rules are skipped whole, including the rules nested in @media
+        assert_eq!( styles.len(), 3 );
+        assert_eq!( styles[0].selector.to_string(), ".title" );
+        assert_eq!( styles[0].properties.len(), 2 );
+        assert_eq!( styles[1].selector.to_string(), "#root > Button:hover" );
+        assert!( matches!( &styles[2].selector, Selector::Group(_) ) );
+    }
+
+    #[test]
+    fn lenient_skips_unsupported() {
+        let css = r#"
+            :root { --accent: #ff8800; }
+            .ok { color: #00ff00; }
+        "#;
+        let tks = TokenAndSpan::new(css);
+        let opts = ParseOptions { lenient: true, ..Default::default() };
+        let styles = parse_css(&tks, &opts).unwrap();
+        assert_eq!( styles.len(), 1 );
+        assert_eq!( opts.take_diagnostics().len(), 1 );
+
+        //the same sheet is fatal without lenient mode
+        assert!( parse_css(&tks, &ParseOptions::default()).is_err() );
+    }
+
+    #[test]
+    fn load_into_document() {
+        let input = r#"
+            .title { color: #0000ff }
+            Main:
+            Label("x") .title
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let mut doc = SKUI::parse(&tks).unwrap();
+
+        let css = r#"
+            .sheet { --pad: 4px; }
+            .title { padding: var(--pad); }
+        "#;
+        let css_tks = TokenAndSpan::new(css);
+        doc.load_css(&css_tks, &ParseOptions::default()).unwrap();
+
+        //sheet rules land after the document's own, vars are substituted
+        assert_eq!( doc.styles.len(), 3 );
+        assert_eq!( doc.styles[2].to_string(), ".title { padding: 4px }" );
+        assert!( doc.vars.contains_key("--pad") );
+
+        //sheet selectors parse to the ordinary selector shapes
+        assert_eq!( doc.styles[1].selector,
+            Selector::Simple( SimpleSelector { kinds: vec![SelectorKind::Class("sheet")], pseudo_class: None } ) );
+    }
+}
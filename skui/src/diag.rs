@@ -0,0 +1,135 @@
+use logos::Span;
+use crate::{ParseErrorKind, SKUIParseError};
+
+// Structured diagnostics. `SKUIParseError` stays the low-level result type;
+// `Diagnostic` is the reporting shape on top of it - severity, primary span,
+// secondary labels and a help suggestion - ready to feed into miette/ariadne
+// style reporters or an in-app error overlay. Builder-side errors carry no
+// source position, so the primary span is optional.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+// A secondary source position with its own message ("first defined here", ..).
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    // byte range into the source text; `None` for errors raised after parsing
+    // (builder errors, missing components, ..)
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn note(message: impl Into<String>) -> Self {
+        Self::new(Severity::Note, message)
+    }
+
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self { severity, message: message.into(), span: None, labels: vec![], help: None }
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push( Label { span, message: message.into() } );
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+}
+
+impl From<&SKUIParseError> for Diagnostic {
+    fn from(e: &SKUIParseError) -> Self {
+        let mut d = Diagnostic::error( e.kind.to_string() ).with_span( e.span.clone() );
+        if let Some(help) = kind_help( e.kind.kind() ) {
+            d = d.with_help(help);
+        }
+        d
+    }
+}
+
+impl From<SKUIParseError> for Diagnostic {
+    fn from(e: SKUIParseError) -> Self {
+        Diagnostic::from(&e)
+    }
+}
+
+// Fix-it style suggestion per error kind, where one sentence of guidance
+// exists beyond the error message itself.
+fn kind_help(kind: &ParseErrorKind) -> Option<&'static str> {
+    Some( match kind {
+        ParseErrorKind::ExpectValue =>
+            "string values need quotes : `text: \"hello\"`, and bindings are written `${path}`",
+        ParseErrorKind::InvalidCssSelector(_) =>
+            "style rules start with a selector : `.class`, `#id`, `Tag` or a combination",
+        ParseErrorKind::ExpectKeyValue =>
+            "named parameters use `=` (`Flex(axis=Vertical)`), body properties use `:` (`gap: 4`)",
+        ParseErrorKind::IdAlreadyDefined =>
+            "`#id` must be unique per document; use a class for shared styling",
+        ParseErrorKind::InvalidRelativeValue =>
+            "a binding is `${path.to.value}` with an optional `:-fallback` or `|filter` suffix",
+        ParseErrorKind::InvalidTimerInterval =>
+            "timer intervals are a number plus a unit : `tick: 250ms`, `poll: 2s`, `sync: 1m`",
+        ParseErrorKind::UnsupportedVersion { .. } =>
+            "the grammar version is declared at the top of the document : `#skui 0.4`",
+        ParseErrorKind::DepthLimitExceeded(_) | ParseErrorKind::ChildrenLimitExceeded(_) | ParseErrorKind::SourceTooLarge(_) =>
+            "raise the limit via ParseOptions if this document is trusted",
+        _ => return None,
+    } )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TokenAndSpan, SKUI};
+
+    #[test]
+    fn from_parse_error() {
+        let input = "Main:\nFlex() {\n    gap: =\n}\n";
+        let tks = TokenAndSpan::new(input);
+        let err = SKUI::parse(&tks).unwrap_err();
+
+        let diag = Diagnostic::from(&err);
+        assert_eq!( diag.severity, Severity::Error );
+        assert_eq!( diag.span, Some(err.span.clone()) );
+        //ExpectValue carries a fix-it suggestion
+        assert!( diag.help.as_deref().unwrap().contains("${path}") );
+    }
+
+    #[test]
+    fn builder_style() {
+        //post-parse errors have no span but can still carry labels and help
+        let diag = Diagnostic::error("unknown component `Buton`")
+            .with_label(10..15, "referenced here")
+            .with_help("did you mean `Button`?");
+        assert_eq!( diag.span, None );
+        assert_eq!( diag.labels.len(), 1 );
+    }
+}
@@ -0,0 +1,596 @@
+use crate::{Component, CssKeyword, CssValue, Number, Parameters, Style, StyleProperty, Value, SKUI};
+use crate::selector::{AttrValue, PseudoClass, Selector, SelectorKind};
+
+// Source emission : serializes a (possibly mutated) AST back to parseable SKUI
+// text. Output is normalized (one child per line, indentation from
+// `FmtOptions`), not a round-trip of the original formatting — comments and
+// whitespace are gone by the time we have an AST. Refactoring tools mutate
+// the tree then call this.
+
+// Emitter configuration. The default matches the style used throughout this
+// repository : four spaces per level.
+#[derive(Debug, Clone)]
+pub struct FmtOptions {
+    pub indent_width: usize,
+    pub hard_tabs: bool,
+}
+
+impl Default for FmtOptions {
+    fn default() -> Self {
+        Self { indent_width: 4, hard_tabs: false }
+    }
+}
+
+impl FmtOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_indent_width(mut self, width:usize) -> Self {
+        self.indent_width = width;
+        self
+    }
+
+    pub fn with_hard_tabs(mut self, hard_tabs:bool) -> Self {
+        self.hard_tabs = hard_tabs;
+        self
+    }
+
+    fn indent(&self, depth:usize) -> String {
+        if self.hard_tabs {
+            "\t".repeat(depth)
+        } else {
+            " ".repeat(self.indent_width * depth)
+        }
+    }
+}
+
+pub fn to_source(skui:&SKUI) -> String {
+    to_source_with_options(skui, &FmtOptions::default())
+}
+
+pub fn to_source_with_options(skui:&SKUI, opts:&FmtOptions) -> String {
+    let mut out = String::new();
+    if let Some((major, minor)) = skui.version {
+        out.push_str( &format!("#skui {major}.{minor}\n") );
+    }
+    for path in skui.imports.iter() {
+        out.push_str( &format!("@import \"{path}\"\n") );
+    }
+    for style in skui.styles.iter() {
+        write_style(&mut out, style);
+    }
+    for theme in skui.themes.iter() {
+        out.push_str( &format!("theme {} {{\n", theme.name) );
+        for style in theme.styles.iter() {
+            out.push_str( &opts.indent(1) );
+            out.push_str( &style.to_string() );
+            out.push('\n');
+        }
+        out.push_str("}\n");
+    }
+    for media in skui.media_styles.iter() {
+        out.push_str( &format!("@media{} {{\n", media_query_source(&media.query)) );
+        for style in media.styles.iter() {
+            out.push_str( &opts.indent(1) );
+            out.push_str( &style.to_string() );
+            out.push('\n');
+        }
+        out.push_str("}\n");
+    }
+    if (!skui.styles.is_empty() || !skui.themes.is_empty() || !skui.media_styles.is_empty()) && !skui.components.is_empty() {
+        out.push('\n');
+    }
+    for (i,rc) in skui.components.iter().enumerate() {
+        if i > 0 { out.push('\n'); }
+        out.push_str(rc.name);
+        if let Some(parent) = rc.extends {
+            out.push_str(" extends ");
+            out.push_str(parent);
+        }
+        out.push_str(":\n");
+        write_component(&mut out, &rc.component, 0, opts);
+    }
+    out
+}
+
+fn write_style(out:&mut String, style:&Style) {
+    out.push_str( &style.to_string() );
+    out.push('\n');
+}
+
+// The stored bounds are plain numbers : px is the unit they were declared in,
+// so the emitted text parses back to the same query.
+fn media_query_source(q:&crate::MediaQuery) -> String {
+    let mut out = String::new();
+    let features = [
+        ("min-width", q.min_width), ("max-width", q.max_width),
+        ("min-height", q.min_height), ("max-height", q.max_height),
+    ];
+    for (key, v) in features {
+        if let Some(v) = v {
+            if !out.is_empty() { out.push_str(" and"); }
+            out.push_str( &format!(" ({key}: {v}px)") );
+        }
+    }
+    out
+}
+
+// `Display` mirrors the emitter so selectors and style rules can be printed
+// piecemeal (inspector output, golden tests) without serializing a whole
+// document. The text is always parseable back.
+impl std::fmt::Display for Selector<'_> {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str( &selector_source(self) )
+    }
+}
+
+impl std::fmt::Display for StyleProperty<'_> {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.key)?;
+        for v in self.values.iter() {
+            write!(f, " {}", css_value_source(v))?;
+        }
+        if self.important {
+            f.write_str(" !important")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Style<'_> {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {{", self.selector)?;
+        for (i,prop) in self.properties.iter().enumerate() {
+            if i > 0 { f.write_str(";")?; }
+            write!(f, " {prop}")?;
+        }
+        f.write_str(" }")
+    }
+}
+
+// Selector text is the same grammar the stylesheet parser accepts.
+pub(crate) fn selector_source(selector:&Selector) -> String {
+    match selector {
+        Selector::Simple(simple) => {
+            let mut s = String::new();
+            for kind in simple.kinds.iter() {
+                match kind {
+                    SelectorKind::Tag(tag) => s.push_str(tag),
+                    SelectorKind::Id(id) => { s.push('#'); s.push_str(id); }
+                    SelectorKind::Class(cls) => { s.push('.'); s.push_str(cls); }
+                    SelectorKind::Attr(key, expected) => {
+                        s.push('[');
+                        s.push_str(key);
+                        match expected {
+                            Some(AttrValue::Str(v)) => { s.push_str("=\""); s.push_str(v); s.push('"'); }
+                            Some(AttrValue::Int(v)) => s.push_str( &format!("={v}") ),
+                            Some(AttrValue::Float(v)) => s.push_str( &format!("={v}") ),
+                            Some(AttrValue::Bool(v)) => s.push_str( &format!("={v}") ),
+                            None => {}
+                        }
+                        s.push(']');
+                    }
+                    SelectorKind::Not(inner) => {
+                        s.push_str(":not(");
+                        s.push_str( &selector_source(&Selector::Simple(inner.clone())) );
+                        s.push(')');
+                    }
+                    SelectorKind::Any => s.push('*'),
+                }
+            }
+            if let Some(pseudo) = simple.pseudo_class.as_ref() {
+                s.push(':');
+                s.push_str( match pseudo {
+                    PseudoClass::Hover => "hover",
+                    PseudoClass::Active => "active",
+                    PseudoClass::Focus => "focus",
+                    PseudoClass::Disabled => "disabled",
+                });
+            }
+            s
+        }
+        Selector::Group(selectors) => {
+            selectors.iter().map(selector_source).collect::<Vec<_>>().join(", ")
+        }
+        Selector::Descendant(left, right) => {
+            format!("{} {}", selector_source(left), selector_source(right))
+        }
+        Selector::Child(left, right) => {
+            format!("{} > {}", selector_source(left), selector_source(right))
+        }
+        Selector::Adjacent(left, right) => {
+            format!("{} + {}", selector_source(left), selector_source(right))
+        }
+        Selector::Sibling(left, right) => {
+            format!("{} ~ {}", selector_source(left), selector_source(right))
+        }
+    }
+}
+
+pub(crate) fn css_value_source(v:&CssValue) -> String {
+    match v {
+        CssValue::Keyword(CssKeyword::Auto) => "auto".to_string(),
+        CssValue::Keyword(CssKeyword::None) => "none".to_string(),
+        CssValue::Keyword(CssKeyword::Inherit) => "inherit".to_string(),
+        CssValue::Px(v) => format!("{v}px"),
+        CssValue::Em(v) => format!("{v}em"),
+        CssValue::Pt(v) => format!("{v}pt"),
+        CssValue::Number(v) => format!("{v}"),
+        CssValue::Percent(v) => format!("{v}%"),
+        CssValue::Ident(s) => s.to_string(),
+        CssValue::Str(s) => format!("\"{s}\""),
+        CssValue::HexColor(s) => format!("#{}", s.trim_start_matches('#')),
+        CssValue::Rgba((r,g,b,a)) => format!("rgba({r},{g},{b},{a})"),
+        CssValue::Rgb((r,g,b)) => format!("rgb({r},{g},{b})"),
+        CssValue::Var(name) => format!("var({name})"),
+        CssValue::Calc(expr) => format!("calc({expr})"),
+    }
+}
+
+fn write_component(out:&mut String, c:&Component, depth:usize, opts:&FmtOptions) {
+    let indent = opts.indent(depth);
+    //synthetic conditional node (see parse_if) : emit the `if`/`else` surface
+    //form, not a component call
+    if c.name == "if" {
+        let (body, else_node) = match c.children.split_last() {
+            Some((last, rest)) if last.name == "else" => (rest, Some(last)),
+            _ => (c.children.as_slice(), None),
+        };
+        out.push_str(&indent);
+        out.push_str("if ");
+        if let Some(cond) = c.params.get(0, "") {
+            out.push_str( &value_source(cond, depth, opts) );
+        }
+        out.push_str(" {\n");
+        for child in body {
+            write_component(out, child, depth + 1, opts);
+        }
+        out.push_str(&indent);
+        out.push('}');
+        if let Some(else_node) = else_node {
+            out.push_str(" else {\n");
+            for child in else_node.children.iter() {
+                write_component(out, child, depth + 1, opts);
+            }
+            out.push_str(&indent);
+            out.push('}');
+        }
+        out.push('\n');
+        return;
+    }
+    //synthetic loop node (see parse_for) : same treatment
+    if c.name == "for" {
+        out.push_str(&indent);
+        out.push_str("for ");
+        if let Some(var) = c.params.get(0, "").and_then( |v| v.as_str() ) {
+            out.push_str(var);
+        }
+        out.push_str(" in ");
+        if let Some(source) = c.params.get(1, "") {
+            out.push_str( &value_source(source, depth, opts) );
+        }
+        out.push_str(" {\n");
+        for child in c.children.iter() {
+            write_component(out, child, depth + 1, opts);
+        }
+        out.push_str(&indent);
+        out.push_str("}\n");
+        return;
+    }
+    out.push_str(&indent);
+    out.push_str(c.name);
+    out.push_str( &params_source(&c.params, opts) );
+    if let Some(id) = c.id {
+        out.push_str( &format!(" #{id}") );
+    }
+    for cls in c.classes.iter() {
+        out.push_str( &format!(" .{cls}") );
+    }
+
+    if c.children.is_empty() && c.properties.is_empty() {
+        out.push('\n');
+        return;
+    }
+    out.push_str(" {\n");
+    // properties sorted so emission is deterministic (HashMap order is not)
+    let mut props: Vec<_> = c.properties.iter().collect();
+    props.sort_by_key( |(k,_)| **k );
+    let inner = opts.indent(depth + 1);
+    for (k,v) in props {
+        out.push_str( &format!("{inner}{k}: {}\n", value_source(v, depth + 1, opts)) );
+    }
+    for child in c.children.iter() {
+        write_component(out, child, depth + 1, opts);
+    }
+    out.push_str(&indent);
+    out.push_str("}\n");
+}
+
+fn params_source(params:&Parameters, opts:&FmtOptions) -> String {
+    match params {
+        Parameters::Args(args) => {
+            let list = args.iter()
+                .map( |v| value_source(v, 0, opts) )
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({list})")
+        }
+        Parameters::Map(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key( |(k,_)| **k );
+            //mixed form : numeric keys are positional entries, emitted bare
+            //and first so the output parses back to the same map
+            let (pos, named): (Vec<_>, Vec<_>) = entries.into_iter()
+                .partition( |(k,_)| k.as_bytes()[0].is_ascii_digit() );
+            let list = pos.iter()
+                .map( |(_,v)| value_source(v, 0, opts) )
+                .chain( named.iter().map( |(k,v)| format!("{k}={}", value_source(v, 0, opts)) ) )
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({list})")
+        }
+    }
+}
+
+pub(crate) fn value_source(v:&Value, depth:usize, opts:&FmtOptions) -> String {
+    match v {
+        Value::Ident(s) => s.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(Number::I64(n)) => n.to_string(),
+        Value::Number(Number::F64(n)) => {
+            // keep a dot so it re-lexes as a Float
+            if n.fract() == 0.0 { format!("{n:.1}") } else { n.to_string() }
+        }
+        Value::String(s) => {
+            //backtick strings may hold raw quotes/newlines the quoted form can't
+            if s.contains('"') || s.contains('\n') {
+                format!("`{s}`")
+            } else {
+                format!("\"{s}\"")
+            }
+        }
+        Value::Array(items) => {
+            let list = items.iter()
+                .map( |item| value_source(item, depth, opts) )
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("[{list}]")
+        }
+        Value::Map(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key( |(k,_)| **k );
+            let list = entries.iter()
+                .map( |(k,item)| format!("{k}: {}", value_source(item, depth, opts)) )
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{{{list}}}")
+        }
+        Value::Closure(s) => s.to_string(),
+        Value::Unit(unit) => css_value_source(unit),
+        Value::Color(color) => css_value_source(color),
+        Value::Component(c) => {
+            let mut s = String::new();
+            write_component(&mut s, c, depth, opts);
+            s.trim_start().trim_end_matches('\n').to_string()
+        }
+        Value::Interpolated(segments) => {
+            //reassemble the original quoted form : literal segments verbatim,
+            //binding segments through their `${..}` rendering
+            let mut s = String::from("\"");
+            for seg in segments.iter() {
+                match seg {
+                    Value::String(lit) => s.push_str(lit),
+                    seg => s.push_str( &value_source(seg, depth, opts) ),
+                }
+            }
+            s.push('"');
+            s
+        }
+        Value::Relative(keys) => {
+            let path = keys.iter()
+                .map( |k| match k {
+                    crate::ValueKey::Index(i) => i.to_string(),
+                    crate::ValueKey::Name(n) => n.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+            format!("${{{path}}}")
+        }
+        Value::RelativeOr(keys, fallback) => {
+            let path = keys.iter()
+                .map( |k| match k {
+                    crate::ValueKey::Index(i) => i.to_string(),
+                    crate::ValueKey::Name(n) => n.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+            format!("${{{path}:-{}}}", value_source(fallback, depth, opts))
+        }
+        Value::Filtered(keys, filters) => {
+            let path = keys.iter()
+                .map( |k| match k {
+                    crate::ValueKey::Index(i) => i.to_string(),
+                    crate::ValueKey::Name(n) => n.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+            let chain = filters.iter()
+                .map( |f| if f.args.is_empty() {
+                    f.name.to_string()
+                } else {
+                    format!("{}({})", f.name, f.args.iter().map(|a| format!("\"{a}\"")).collect::<Vec<_>>().join(", "))
+                })
+                .collect::<Vec<_>>()
+                .join(" | ");
+            format!("${{{path} | {chain}}}")
+        }
+        Value::Tr(tr) => {
+            if tr.args.is_empty() {
+                format!("tr(\"{}\")", tr.key)
+            } else {
+                let mut entries: Vec<_> = tr.args.iter().collect();
+                entries.sort_by_key( |(k,_)| **k );
+                let list = entries.iter()
+                    .map( |(k,item)| format!("{k}={}", value_source(item, depth, opts)) )
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("tr(\"{}\", {list})", tr.key)
+            }
+        }
+    }
+}
+
+impl <'a> SKUI<'a> {
+    // Serialize back to SKUI source. `parse(to_source(doc))` always succeeds
+    // and yields an equivalent document.
+    pub fn to_source(&self) -> String {
+        to_source(self)
+    }
+
+    pub fn to_source_with_options(&self, opts:&FmtOptions) -> String {
+        to_source_with_options(self, opts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenAndSpan;
+
+    #[test]
+    fn roundtrip() {
+        let input = r#"
+            .title { color: #ff0000; padding: 4px }
+            #root > Button:hover { background-color: rgb(1,2,3) }
+            Main:
+            Flex(axis=horizontal) #root {
+                Label("hello").title
+                Button(text="ok", size=1.5)
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let skui = SKUI::parse(&tks).unwrap();
+        let src = skui.to_source();
+        println!("{src}");
+
+        //emitted source parses back to the same shape
+        let tks2 = TokenAndSpan::new(&src);
+        let reparsed = SKUI::parse(&tks2).unwrap();
+        assert_eq!( reparsed.styles.len(), skui.styles.len() );
+        assert_eq!( reparsed.stats().component_count, skui.stats().component_count );
+        assert_eq!( reparsed.find_by_id("root").map(|c| c.name), Some("Flex") );
+        assert_eq!( reparsed.find_all_by_class("title").len(), 1 );
+    }
+
+    #[test]
+    fn selector_and_style_display() {
+        let input = r#"
+            #root > Flex .item:hover, Button.primary { padding: 4px 1.5em; width: 50%; color: #ff8800 }
+            Main:
+            Label("x")
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let skui = SKUI::parse(&tks).unwrap();
+        let style = &skui.styles[0];
+
+        //selector text round-trips through the stylesheet grammar
+        let css = style.selector.to_css_string();
+        assert_eq!( css, "#root > Flex .item:hover, Button.primary" );
+        assert_eq!( style.selector.to_string(), css );
+
+        //rule Display keeps the original units
+        let rule = style.to_string();
+        assert_eq!( rule, "#root > Flex .item:hover, Button.primary { padding: 4px 1.5em; width: 50%; color: #ff8800 }" );
+        let doc = format!("{rule}\nMain:\nLabel(\"x\")");
+        let tks2 = TokenAndSpan::new(&doc);
+        let reparsed = SKUI::parse(&tks2).unwrap();
+        assert_eq!( reparsed.styles[0].selector, style.selector );
+
+        //properties print standalone too
+        assert_eq!( style.properties[1].to_string(), "width: 50%" );
+    }
+
+    #[test]
+    fn mutate_then_emit() {
+        let input = r#"
+            Main:
+            Flex() {
+                Label("a") #keep
+                Label("b")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let mut skui = SKUI::parse(&tks).unwrap();
+
+        let root = &mut skui.components[0].component;
+        root.add_class("wide");
+        root.set_param("axis", Value::Ident("horizontal"));
+        let removed = root.remove_child(1).unwrap();
+        assert_eq!( removed.name, "Label" );
+        root.insert_child(0, Component {
+            name: "Button", params: Parameters::empty(),
+            id: None, classes: Default::default(),
+            children: vec![], properties: Default::default(),
+        });
+
+        let src = skui.to_source();
+        println!("{src}");
+        let tks2 = TokenAndSpan::new(&src);
+        let reparsed = SKUI::parse(&tks2).unwrap();
+        let flex = &reparsed.components[0].component;
+        assert!( flex.classes.contains(&"wide") );
+        assert_eq!( flex.params.get(0, "axis").and_then(|v| v.as_str()), Some("horizontal") );
+        assert_eq!( flex.children.len(), 2 );
+        assert_eq!( flex.children[0].name, "Button" );
+        assert_eq!( flex.children[1].id, Some("keep") );
+    }
+
+    #[test]
+    fn configurable_indent() {
+        let input = r#"
+            Main:
+            Flex() {
+                Label("a")
+                Flex() { Label("b") }
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let skui = SKUI::parse(&tks).unwrap();
+
+        let two = skui.to_source_with_options( &FmtOptions::new().with_indent_width(2) );
+        assert!( two.contains("\n  Label(\"a\")\n") );
+        assert!( two.contains("\n    Label(\"b\")\n") );
+
+        let tabs = skui.to_source_with_options( &FmtOptions::new().with_hard_tabs(true) );
+        assert!( tabs.contains("\n\tLabel(\"a\")\n") );
+        assert!( tabs.contains("\n\t\tLabel(\"b\")\n") );
+
+        //every width still emits parseable source
+        for src in [two, tabs] {
+            let tks2 = TokenAndSpan::new(&src);
+            let reparsed = SKUI::parse(&tks2).unwrap();
+            assert_eq!( reparsed.stats().component_count, skui.stats().component_count );
+        }
+    }
+
+    #[test]
+    fn rename_class_everywhere() {
+        let input = r#"
+            .old { padding: 1px }
+            Flex .old { color: #fff }
+            Main:
+            Flex() {
+                Label("x").old
+                Label("y").other
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let mut skui = SKUI::parse(&tks).unwrap();
+        let n = skui.rename_class("old", "new");
+        assert_eq!( n, 3 ); //two selector kinds + one component
+        let src = skui.to_source();
+        assert!( !src.contains(".old") );
+        assert_eq!( src.matches(".new").count(), 3 );
+    }
+}
@@ -0,0 +1,69 @@
+use logos::{Logos, Span};
+use crate::token::Token;
+
+// Token classifier for editor highlighting. Runs the same lexer the parser
+// uses so the highlighting can never disagree with what actually parses.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenClass {
+    Keyword,    //true/false
+    Number,     //integers, floats and unit values (px/em/pt/%)
+    String,
+    Color,      //#hex, rgb(), rgba()
+    Ident,
+    Selector,   //#id / .class
+    Binding,    //${..}
+    Comment,
+    Punct,
+}
+
+impl Token<'_> {
+    pub fn class(&self) -> Option<TokenClass> {
+        let class = match self {
+            Token::True | Token::False => TokenClass::Keyword,
+            Token::Integer(_) | Token::Float(_)
+            | Token::Px(_) | Token::Em(_) | Token::Pt(_) | Token::Percent(_)
+            | Token::Calc(_) => TokenClass::Number,
+            Token::Str(_) => TokenClass::String,
+            Token::Rgb(_) | Token::Rgba(_) => TokenClass::Color,
+            Token::Ident(_) | Token::VarName(_) => TokenClass::Ident,
+            Token::Id(_) | Token::Class(_) => TokenClass::Selector,
+            Token::Relative(_) => TokenClass::Binding,
+            Token::Comment => TokenClass::Comment,
+            Token::Whitespace | Token::None => return None,
+            _ => TokenClass::Punct,
+        };
+        Some(class)
+    }
+}
+
+// Classified spans in source order. Unlexable stretches are skipped, so the
+// output stays usable while the user is mid-edit.
+pub fn classify(src:&str) -> Vec<(Span, TokenClass)> {
+    Token::lexer(src)
+        .spanned()
+        .filter_map( |(t,span)| t.ok().and_then( |t| t.class() ).map( |c| (span,c) ) )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_spans() {
+        let src = r#"Label("hi", size=12px, on=true) #main ${0.count}"#;
+        let classes: Vec<TokenClass> = classify(src).into_iter().map( |(_,c)| c ).collect();
+        assert!( classes.contains(&TokenClass::Ident) );
+        assert!( classes.contains(&TokenClass::String) );
+        assert!( classes.contains(&TokenClass::Number) );
+        assert!( classes.contains(&TokenClass::Keyword) );
+        assert!( classes.contains(&TokenClass::Selector) );
+        assert!( classes.contains(&TokenClass::Binding) );
+
+        //spans point back into the source
+        let (span,class) = classify(src).into_iter().find( |(_,c)| *c == TokenClass::String ).unwrap();
+        assert_eq!( &src[span], "\"hi\"" );
+        assert_eq!( class, TokenClass::String );
+    }
+}
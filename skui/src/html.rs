@@ -0,0 +1,204 @@
+use crate::{Component, CssKeyword, CssValue, Style, Value, SKUI};
+use crate::selector::{AttrValue, PseudoClass, Selector, SelectorKind};
+
+// Static HTML+CSS export : renders a document as a self-contained preview file
+// so layouts can be reviewed in a browser without running the app. Components
+// map to plain elements (Flex -> flexbox div, Label -> span, ..); parameters
+// the web has no equivalent for are kept as data attributes.
+
+pub fn export_html(skui:&SKUI) -> String {
+    let mut out = String::new();
+    out.push_str("<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n");
+    out.push_str(".skui-flex { display: flex; flex-direction: column; }\n");
+    out.push_str(".skui-flex[data-axis=\"horizontal\"] { flex-direction: row; }\n");
+    for style in skui.styles.iter() {
+        write_style(&mut out, style);
+    }
+    out.push_str("</style>\n</head>\n<body>\n");
+    for rc in skui.components.iter() {
+        out.push_str( &format!("<section data-root=\"{}\">\n", escape_attr(rc.name)) );
+        write_component(&mut out, &rc.component, 1);
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn write_style(out:&mut String, style:&Style) {
+    out.push_str( &selector_css(&style.selector) );
+    out.push_str(" {");
+    for prop in style.properties.iter() {
+        out.push_str( &format!(" {}:", prop.key) );
+        for v in prop.values.iter() {
+            out.push(' ');
+            out.push_str( &css_value(v) );
+        }
+        out.push(';');
+    }
+    out.push_str(" }\n");
+}
+
+fn selector_css(selector:&Selector) -> String {
+    match selector {
+        Selector::Simple(simple) => {
+            let mut s = String::new();
+            for kind in simple.kinds.iter() {
+                match kind {
+                    SelectorKind::Tag(tag) => s.push_str(tag),
+                    SelectorKind::Id(id) => { s.push('#'); s.push_str(id); }
+                    SelectorKind::Class(cls) => { s.push('.'); s.push_str(cls); }
+                    // CSS attribute selectors always compare as strings
+                    SelectorKind::Attr(key, expected) => {
+                        s.push('[');
+                        s.push_str(key);
+                        if let Some(v) = expected {
+                            let text = match v {
+                                AttrValue::Str(v) => v.to_string(),
+                                AttrValue::Int(v) => v.to_string(),
+                                AttrValue::Float(v) => v.to_string(),
+                                AttrValue::Bool(v) => v.to_string(),
+                            };
+                            s.push_str( &format!("=\"{text}\"") );
+                        }
+                        s.push(']');
+                    }
+                    SelectorKind::Not(inner) => {
+                        s.push_str(":not(");
+                        s.push_str( &selector_css(&Selector::Simple(inner.clone())) );
+                        s.push(')');
+                    }
+                    SelectorKind::Any => s.push('*'),
+                }
+            }
+            if let Some(pseudo) = simple.pseudo_class.as_ref() {
+                s.push(':');
+                s.push_str( match pseudo {
+                    PseudoClass::Hover => "hover",
+                    PseudoClass::Active => "active",
+                    PseudoClass::Focus => "focus",
+                    PseudoClass::Disabled => "disabled",
+                });
+            }
+            s
+        }
+        Selector::Group(selectors) => {
+            selectors.iter().map(selector_css).collect::<Vec<_>>().join(", ")
+        }
+        Selector::Descendant(left, right) => {
+            format!("{} {}", selector_css(left), selector_css(right))
+        }
+        Selector::Child(left, right) => {
+            format!("{} > {}", selector_css(left), selector_css(right))
+        }
+        Selector::Adjacent(left, right) => {
+            format!("{} + {}", selector_css(left), selector_css(right))
+        }
+        Selector::Sibling(left, right) => {
+            format!("{} ~ {}", selector_css(left), selector_css(right))
+        }
+    }
+}
+
+fn css_value(v:&CssValue) -> String {
+    match v {
+        CssValue::Keyword(CssKeyword::Auto) => "auto".to_string(),
+        CssValue::Keyword(CssKeyword::None) => "none".to_string(),
+        CssValue::Keyword(CssKeyword::Inherit) => "inherit".to_string(),
+        CssValue::Px(v) => format!("{v}px"),
+        CssValue::Em(v) => format!("{v}em"),
+        CssValue::Pt(v) => format!("{v}pt"),
+        CssValue::Number(v) => format!("{v}"),
+        CssValue::Percent(v) => format!("{v}%"),
+        CssValue::Ident(s) => s.to_string(),
+        CssValue::Str(s) => format!("\"{s}\""),
+        CssValue::HexColor(s) => format!("#{}", s.trim_start_matches('#')),
+        CssValue::Rgba((r,g,b,a)) => format!("rgba({r},{g},{b},{a})"),
+        CssValue::Rgb((r,g,b)) => format!("rgb({r},{g},{b})"),
+        CssValue::Var(name) => format!("var({name})"),
+        CssValue::Calc(expr) => format!("calc({expr})"),
+    }
+}
+
+fn write_component(out:&mut String, c:&Component, depth:usize) {
+    let indent = "  ".repeat(depth);
+    let (elem, class) = match c.name {
+        "Label" => ("span", "skui-label"),
+        "Prose" => ("p", "skui-prose"),
+        "Button" => ("button", "skui-button"),
+        "Checkbox" => ("label", "skui-checkbox"),
+        "Flex" => ("div", "skui-flex"),
+        "Grid" => ("div", "skui-grid"),
+        _ => ("div", ""),
+    };
+
+    out.push_str( &format!("{indent}<{elem}") );
+    if let Some(id) = c.id {
+        out.push_str( &format!(" id=\"{}\"", escape_attr(id)) );
+    }
+    let mut classes = Vec::new();
+    if !class.is_empty() { classes.push(class.to_string()); }
+    classes.extend( c.classes.iter().map(|s| s.to_string()) );
+    if !classes.is_empty() {
+        out.push_str( &format!(" class=\"{}\"", escape_attr(&classes.join(" "))) );
+    }
+    out.push_str( &format!(" data-skui=\"{}\"", escape_attr(c.name)) );
+    //only the containers have an axis; on leaf components `get` would fall
+    //back to positional slot 0 and read the text as an axis
+    if matches!( c.name, "Flex" | "Grid" ) {
+        if let Some(axis) = c.params.get(0, "axis").and_then( |v| v.as_str() ) {
+            out.push_str( &format!(" data-axis=\"{}\"", escape_attr(axis)) );
+        }
+    }
+    out.push('>');
+
+    if c.name == "Checkbox" {
+        out.push_str("<input type=\"checkbox\">");
+    }
+    if let Some(Value::String(text)) = c.params.get(0, "text") {
+        out.push_str( &escape_text(text) );
+    }
+
+    if c.children.is_empty() {
+        out.push_str( &format!("</{elem}>\n") );
+    } else {
+        out.push('\n');
+        for child in c.children.iter() {
+            write_component(out, child, depth + 1);
+        }
+        out.push_str( &format!("{indent}</{elem}>\n") );
+    }
+}
+
+fn escape_text(s:&str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn escape_attr(s:&str) -> String {
+    escape_text(s).replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenAndSpan;
+
+    #[test]
+    fn export() {
+        let input = r#"
+            .title { color: #ff0000; padding: 4px }
+            Main:
+            Flex(axis=horizontal) {
+                Label("hello").title
+                Button("ok")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let skui = SKUI::parse(&tks).unwrap();
+        let html = export_html(&skui);
+        println!("{html}");
+        assert!( html.contains(".title { color: #ff0000; padding: 4px; }") );
+        assert!( html.contains("data-axis=\"horizontal\"") );
+        assert!( html.contains("<span class=\"skui-label title\" data-skui=\"Label\">hello</span>") );
+        assert!( html.contains("<button") );
+    }
+}
@@ -2,7 +2,18 @@ mod token;
 mod value;
 mod params;
 mod cursor;
+pub mod arena;
+pub mod css;
+pub mod diag;
+pub mod fmt;
+pub mod highlight;
+pub mod html;
+pub mod refactor;
 pub mod selector;
+pub mod tokens;
+#[cfg(feature = "wasm")]
+pub mod web;
+pub mod worker;
 
 use token::Token;
 use cursor::TokenCursor;
@@ -15,7 +26,7 @@ use crate::cursor::{CursorSpan, SplitCursor};
 
 pub use value::*;
 pub use params::*;
-use crate::selector::{PseudoState, Selector, SelectorParseError, SelectorParser};
+use crate::selector::{PseudoState, Selector, SelectorParseError, SelectorParser, Specificity};
 // pub use selector::*;
 
 pub type Cursor<'a> = TokenCursor<'a,Token<'a>>;
@@ -30,6 +41,12 @@ pub struct ParseError {
     kind: ParseErrorKind,
 }
 
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
 impl ParseError {
 
     pub fn expect_ident(span: CursorSpan) -> Self {
@@ -72,9 +89,58 @@ impl ParseError {
         Self { span, kind:ParseErrorKind::IdAlreadyDefined }
     }
 
+    pub fn depth_limit_exceeded(span: CursorSpan, max:usize) -> Self {
+        Self { span, kind:ParseErrorKind::DepthLimitExceeded(max) }
+    }
+
+    pub fn children_limit_exceeded(span: CursorSpan, max:usize) -> Self {
+        Self { span, kind:ParseErrorKind::ChildrenLimitExceeded(max) }
+    }
+
+    pub fn source_too_large(span: CursorSpan, max:usize) -> Self {
+        Self { span, kind:ParseErrorKind::SourceTooLarge(max) }
+    }
+
+    pub fn invalid_timer_interval(span: CursorSpan) -> Self {
+        Self { span, kind:ParseErrorKind::InvalidTimerInterval }
+    }
+
+    pub fn invalid_media_query(span: CursorSpan) -> Self {
+        Self { span, kind:ParseErrorKind::InvalidMediaQuery }
+    }
+
     pub fn invalid_relative_value(span: CursorSpan) -> Self {
         Self { span, kind:ParseErrorKind::InvalidRelativeValue }
     }
+
+    pub fn ambiguous_component_value(span: CursorSpan) -> Self {
+        Self { span, kind:ParseErrorKind::AmbiguousComponentValue }
+    }
+
+    pub fn duplicate_flag(span: CursorSpan) -> Self {
+        Self { span, kind:ParseErrorKind::DuplicateFlag }
+    }
+
+    pub fn unsupported_version(span: CursorSpan, required:(u32,u32), available:(u32,u32)) -> Self {
+        Self { span, kind:ParseErrorKind::UnsupportedVersion { required, available } }
+    }
+
+    // Token position of the error (resolve to a source span via `TokenAndSpan::span`).
+    pub fn span(&self) -> CursorSpan {
+        self.span.clone()
+    }
+
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    // Resource-limit errors stay fatal even under `ParseOptions::lenient`.
+    fn is_limit(&self) -> bool {
+        matches!(self.kind,
+            ParseErrorKind::DepthLimitExceeded(_)
+            | ParseErrorKind::ChildrenLimitExceeded(_)
+            | ParseErrorKind::SourceTooLarge(_))
+    }
 }
 
 
@@ -112,9 +178,34 @@ pub enum ParseErrorKind {
 
     #[error("invalid relative value expression")]
     InvalidRelativeValue,
+
+    #[error("component nesting deeper than the configured limit ({0})")]
+    DepthLimitExceeded(usize),
+
+    #[error("more children than the configured limit ({0})")]
+    ChildrenLimitExceeded(usize),
+
+    #[error("document larger than the configured limit ({0} bytes)")]
+    SourceTooLarge(usize),
+
+    #[error("invalid timer interval. e.g. 500ms, 1s, 2m")]
+    InvalidTimerInterval,
+
+    #[error("invalid media query. e.g. @media (min-width: 600px) and (max-height: 400px)")]
+    InvalidMediaQuery,
+
+    #[error("component-valued property without a terminating ';'. the component is the property value, not a child - end the statement with ';' to make that explicit")]
+    AmbiguousComponentValue,
+
+    #[error("duplicate boolean flag in a parameter list")]
+    DuplicateFlag,
+
+    #[error("requires skui >= {}.{} but the available grammar is {}.{}", required.0, required.1, available.0, available.1)]
+    UnsupportedVersion { required:(u32,u32), available:(u32,u32) },
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CssKeyword {
     #[default]
     Auto,
@@ -123,9 +214,14 @@ pub enum CssKeyword {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CssValue<'a> {
     Keyword(CssKeyword),
     Px(f64),
+    // font-relative / point units; resolved against the integration's base
+    // font size and DPI at style-apply time
+    Em(f64),
+    Pt(f64),
     Number(f64),
     Percent(f64),
     Ident(&'a str),
@@ -133,6 +229,13 @@ pub enum CssValue<'a> {
     HexColor(&'a str),
     Rgba( (u8,u8,u8,u8) ),
     Rgb( (u8,u8,u8) ),
+    // `var(--name)` reference. Only exists while parsing : every reference is
+    // substituted from `SKUI::vars` before the document is handed out, so
+    // `get_styles` consumers never see one.
+    Var(&'a str),
+    // inner expression of `calc(..)`, evaluated by `resolve` at layout time
+    // when the parent dimension is known
+    Calc(&'a str),
 }
 
 impl <'a> CssValue<'a> {
@@ -143,6 +246,86 @@ impl <'a> CssValue<'a> {
             _ => None,
         }
     }
+
+    // Resolve to absolute pixels against the parent dimension : `%` scales
+    // `parent`, `calc(..)` is evaluated, px/unit-less numbers pass through.
+    // em/pt need a font context the core crate does not have - the
+    // integration layer resolves those (see the masonry `LengthContext`).
+    pub fn resolve(&self, parent:f64) -> Option<f64> {
+        match self {
+            Self::Px(v) | Self::Number(v) => Some(*v),
+            Self::Percent(v) => Some(parent * v / 100.0),
+            Self::Calc(expr) => eval_calc(expr, parent),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CalcTok {
+    Num(f64),
+    Op(char),
+}
+
+fn lex_calc(expr:&str, parent:f64) -> Option<Vec<CalcTok>> {
+    let mut out = Vec::new();
+    let mut rest = expr.trim_start();
+    while !rest.is_empty() {
+        let c = rest.chars().next()?;
+        //`-` right after a number is an operator, otherwise a sign
+        if "+*/".contains(c) || (c == '-' && matches!(out.last(), Some(CalcTok::Num(_)))) {
+            out.push(CalcTok::Op(c));
+            rest = rest[1..].trim_start();
+            continue;
+        }
+        let end = rest[1..].find( |ch:char| !(ch.is_ascii_digit() || ch == '.') ).map( |i| i + 1 ).unwrap_or(rest.len());
+        let v: f64 = rest[..end].parse().ok()?;
+        rest = &rest[end..];
+        let v = if let Some(r) = rest.strip_prefix("px") { rest = r; v }
+            else if let Some(r) = rest.strip_prefix('%') { rest = r; parent * v / 100.0 }
+            else { v };
+        out.push(CalcTok::Num(v));
+        rest = rest.trim_start();
+    }
+    Some(out)
+}
+
+// `calc(..)` evaluator. Terms are px / unit-less numbers (absolute) and `%`
+// of the parent dimension, combined with + - * / at the usual precedence.
+// Malformed expressions, unknown units and division by zero yield None.
+fn eval_calc(expr:&str, parent:f64) -> Option<f64> {
+    let mut sums: Vec<f64> = Vec::new();
+    let mut sum_ops: Vec<char> = Vec::new();
+    let mut cur: Option<f64> = None;
+    let mut muldiv: Option<char> = None;
+    for t in lex_calc(expr, parent)? {
+        match t {
+            CalcTok::Num(v) => {
+                cur = match (cur, muldiv.take()) {
+                    (None, None) => Some(v),
+                    (Some(c), Some('*')) => Some(c * v),
+                    (Some(c), Some('/')) if v != 0.0 => Some(c / v),
+                    _ => return None,
+                };
+            }
+            CalcTok::Op(op @ ('*' | '/')) => {
+                if cur.is_none() || muldiv.is_some() { return None }
+                muldiv = Some(op);
+            }
+            CalcTok::Op(op) => {
+                if muldiv.is_some() { return None }
+                sums.push(cur.take()?);
+                sum_ops.push(op);
+            }
+        }
+    }
+    if muldiv.is_some() { return None }
+    sums.push(cur?);
+    let mut acc = sums[0];
+    for (op, v) in sum_ops.iter().zip(sums[1..].iter()) {
+        acc = if *op == '+' { acc + v } else { acc - v };
+    }
+    Some(acc)
 }
 
 impl <'a> Default for CssValue<'a> {
@@ -159,6 +342,8 @@ impl <'a> TryFrom< (CursorSpan, Token<'a>) > for CssValue<'a> {
             Token::Ident("none") => Ok(CssValue::Keyword(CssKeyword::None)),
             Token::Ident("inherit") => Ok(CssValue::Keyword(CssKeyword::Inherit)),
             Token::Px(v) => Ok(CssValue::Px(v)),
+            Token::Em(v) => Ok(CssValue::Em(v)),
+            Token::Pt(v) => Ok(CssValue::Pt(v)),
             Token::Percent(v) => Ok(CssValue::Percent(v)),
             Token::Float(v) => Ok(CssValue::Number(v)),
             Token::Integer(v) => Ok(CssValue::Number(v as f64)),
@@ -167,12 +352,14 @@ impl <'a> TryFrom< (CursorSpan, Token<'a>) > for CssValue<'a> {
             Token::Id(s) => Ok(CssValue::HexColor(s)),
             Token::Str(s) => Ok(CssValue::Str(s)),
             Token::Ident(s) => Ok(CssValue::Ident(s)),
+            Token::Calc(s) => Ok(CssValue::Calc(s)),
             _ => Err( ParseError::invalid_css_value(span) ),
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ComponentIdent<'a> {
     Id(&'a str),
     Class(&'a str),
@@ -184,10 +371,14 @@ impl Default for ComponentIdent<'_> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StyleProperty<'a> {
     pub key: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub values: ArrayVec<[CssValue<'a>;5]>,
+    // `color: red !important` : wins the cascade regardless of specificity
+    pub important: bool,
 }
 
 impl StyleProperty<'_> {
@@ -200,29 +391,122 @@ impl StyleProperty<'_> {
 }
 
 impl <'a> Default for StyleProperty<'a> {
-    fn default() -> Self { StyleProperty { key: "", values: ArrayVec::default() } }
+    fn default() -> Self { StyleProperty { key: "", values: ArrayVec::default(), important: false } }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Style<'a> {
     //pub selector: ArrayVec<[Selector<'a>;5]>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub selector: Selector<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub properties: ArrayVec<[StyleProperty<'a>;10]>,
 }
 
+impl Style<'_> {
+    // Whether any declaration carries `!important`. Importance itself cascades
+    // per declaration (see `get_styles`); this is the syntax-level check the
+    // 0.4 version gate uses.
+    pub fn has_important(&self) -> bool {
+        self.properties.iter().any( |p| p.important )
+    }
+}
 
+// One entry of `SKUI::styles_for` : the value a key resolved to after the
+// cascade, together with where it came from - the selector of the winning
+// rule and that rule's position in `styles` (document order, imports and
+// loaded sheets included).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedDeclaration<'a> {
+    pub key: &'a str,
+    pub values: ArrayVec<[CssValue<'a>;5]>,
+    pub important: bool,
+    // the winning rule's selector, rendered as source
+    pub selector: String,
+    // active theme rules count on past `styles.len()`
+    pub rule_index: usize,
+}
 
-#[derive(Debug, Clone)]
+
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Component<'a> {
     pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub params: Parameters<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub id: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub classes: ArrayVec<[&'a str; 5]>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub children: Vec<Component<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub properties: HashMap<&'a str, Value<'a>>,
 }
 
 impl <'a> Component<'a> {
+    // === mutation helpers : keep the invariants (unique id slot, either/or
+    // parameter kind) that hand-editing the pub fields would let you break ===
+
+    // Set/replace a named parameter. Positional parameter lists stay
+    // positional : naming one entry of `("a", "b")` has no source form.
+    pub fn set_param(&mut self, key:&'a str, value:Value<'a>) -> Option<Value<'a>> {
+        match &mut self.params {
+            Parameters::Map(map) => map.insert(key, value),
+            Parameters::Args(args) if args.is_empty() => {
+                let mut map = HashMap::new();
+                map.insert(key, value);
+                self.params = Parameters::Map(map);
+                None
+            }
+            Parameters::Args(_) => {
+                eprintln!("set_param({key:?}) ignored : {} has positional parameters", self.name);
+                None
+            }
+        }
+    }
+
+    pub fn add_class(&mut self, class:&'a str) {
+        if !self.classes.contains(&class) {
+            self.classes.push(class);
+        }
+    }
+
+    pub fn remove_class(&mut self, class:&str) -> bool {
+        match self.classes.iter().position( |c| *c == class ) {
+            Some(idx) => { self.classes.remove(idx); true }
+            None => false,
+        }
+    }
+
+    pub fn insert_child(&mut self, idx:usize, child:Component<'a>) {
+        self.children.insert(idx.min(self.children.len()), child);
+    }
+
+    pub fn remove_child(&mut self, idx:usize) -> Option<Component<'a>> {
+        if idx < self.children.len() {
+            Some( self.children.remove(idx) )
+        } else { None }
+    }
+
+    // Owned construction for trees assembled in Rust (`Component::builder`
+    // chains read better than struct literals full of `Default::default()`).
+    // The result feeds the same building path as parsed components.
+    pub fn builder(name:&'a str) -> ComponentBuilder<'a> {
+        ComponentBuilder {
+            component: Component {
+                name,
+                params: Parameters::empty(),
+                id: None,
+                classes: Default::default(),
+                children: vec![],
+                properties: Default::default(),
+            }
+        }
+    }
+
     pub fn find<'b>(&'a self, parents:&'b mut Vec<&'a Component<'a>>, target:&'a Component<'a>) -> bool {
         if std::ptr::eq(self, target) {
             true
@@ -239,615 +523,3410 @@ impl <'a> Component<'a> {
     }
 }
 
-#[derive(Debug, Clone)]
+// Chainable `Component` construction; see `Component::builder`. Each method
+// goes through the corresponding mutation helper, so the builder cannot
+// produce states the parser never would (duplicate classes, mixed parameter
+// kinds, ..).
+pub struct ComponentBuilder<'a> {
+    component: Component<'a>,
+}
+
+impl <'a> ComponentBuilder<'a> {
+    // Positional parameter. Mixing with `named_param` follows the same rule
+    // as source text : positional entries must all come first.
+    pub fn param(mut self, value:Value<'a>) -> Self {
+        match &mut self.component.params {
+            Parameters::Args(args) => args.push(value),
+            Parameters::Map(_) => {
+                eprintln!("param() ignored : {} already has named parameters", self.component.name);
+            }
+        }
+        self
+    }
+
+    pub fn named_param(mut self, key:&'a str, value:Value<'a>) -> Self {
+        self.component.set_param(key, value);
+        self
+    }
+
+    pub fn id(mut self, id:&'a str) -> Self {
+        self.component.id = Some(id);
+        self
+    }
+
+    pub fn class(mut self, class:&'a str) -> Self {
+        self.component.add_class(class);
+        self
+    }
+
+    pub fn property(mut self, key:&'a str, value:Value<'a>) -> Self {
+        self.component.properties.insert(key, value);
+        self
+    }
+
+    pub fn child(mut self, child:impl Into<Component<'a>>) -> Self {
+        let idx = self.component.children.len();
+        self.component.insert_child(idx, child.into());
+        self
+    }
+
+    pub fn build(self) -> Component<'a> {
+        self.component
+    }
+}
+
+// Nested builders pass straight to `child()` without a `.build()` call.
+impl <'a> From<ComponentBuilder<'a>> for Component<'a> {
+    fn from(builder:ComponentBuilder<'a>) -> Self {
+        builder.build()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RootComponent<'a> {
     pub name: &'a str,
+    // `FancyButton extends MyButton:` : the parent definition this one reuses.
+    // Resolution order comes from `SKUI::extends_chain`.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub extends: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub component: Component<'a>,
 }
 
-#[derive(Debug, Clone)]
-pub struct SKUI<'a> {
-    pub styles: Vec<Style<'a>>,
-    pub components: Vec<RootComponent<'a>>,
+// Per-parse configuration. Conditional sections (`@if-feature("..")`) are
+// evaluated against this at parse time.
+#[derive(Default, Debug, Clone)]
+pub struct ParseOptions {
+    pub features: std::collections::HashSet<String>,
+    // include `@debug { .. }` sections (layout outlines, FPS labels, ..)
+    pub debug_ui: bool,
+    // Guards against pathological (untrusted/generated) inputs. `None` = unlimited.
+    pub max_depth: Option<usize>,
+    pub max_children: Option<usize>,
+    pub max_source_len: Option<usize>,
+    // Error recovery : a bad statement (top-level or inside a component body)
+    // no longer aborts the parse. It is skipped up to the next statement
+    // boundary, the error is recorded (drain with `take_diagnostics`) and the
+    // rest of the document stays usable for preview rendering.
+    pub lenient: bool,
+    // component nesting depth of the current parse (interior mutability :
+    // options are passed as `&ParseOptions` through the whole parser)
+    depth: std::cell::Cell<usize>,
+    diagnostics: std::cell::RefCell<Vec<ParseError>>,
 }
 
-impl <'a> SKUI <'a> {
-    pub fn get_main_component(&self) -> Option<&RootComponent<'a>> {
-        self.get_root_component("Main")
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn get_root_component(&self, name:&str) -> Option<&RootComponent<'a>> {
-        self.components.iter().find(|rc| rc.name == name)
+    pub fn with_feature(mut self, name:&str) -> Self {
+        self.features.insert(name.to_string());
+        self
     }
-    pub fn parse(tks: &'a TokenAndSpan) -> Result<Self, SKUIParseError> {
-        parse(tks).map_err(|e| SKUIParseError { span: e.span, kind: e.kind })
+
+    pub fn with_debug_ui(mut self, debug_ui:bool) -> Self {
+        self.debug_ui = debug_ui;
+        self
     }
 
-    // pub fn styles(&self, comp:&Component) -> impl Iterator<Item=&Style> {
-    //     self.styles.iter().filter(|style| {
-    //         style.selector.
-    //     })
-    // }
+    pub fn with_max_depth(mut self, max:usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
 
-    pub fn get_lookup_scoped_component(&self, c:&'a Component, targets:&[&str]) -> &Component<'a> {
-        let item_wrap = self.components.iter()
-            .find(|rc|
-                rc.name == c.name
-                    && targets.iter().find( |&&s| s == rc.component.name ).is_some()
-                    ).map( |rc| &rc.component);
-        item_wrap.unwrap_or( c )
+    pub fn with_max_children(mut self, max:usize) -> Self {
+        self.max_children = Some(max);
+        self
     }
 
-    // pub fn find_component<'b>(parents:&'b mut Vec<&'a Component<'a>>, comp:&'a Component<'a>, target:&'a Component<'a>) -> bool {
-    //     if std::ptr::eq(comp, target) {
-    //         true
-    //     } else {
-    //         parents.push(comp);
-    //         for child in comp.children.iter() {
-    //             if Self::find_component(parents, child, target) {
-    //                 return true;
-    //             }
-    //         }
-    //         parents.pop();
-    //         false
-    //     }
-    // }
+    pub fn with_max_source_len(mut self, max:usize) -> Self {
+        self.max_source_len = Some(max);
+        self
+    }
 
-    pub fn get_styles<'b>(&self, parents:&'b [&'a Component<'a>], c:&'a Component<'a>) -> impl Iterator<Item=&Style<'a>> {
+    pub fn with_lenient(mut self, lenient:bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
 
-        // let root = self.get_main_component().unwrap();
-        // let mut curr = &root.component;
-        // let mut parents = vec![];
-        // Self::find_comp(&mut parents, &root.component, c);
+    // Errors recovered from during a lenient parse plus ambiguity warnings
+    // (collected in strict mode too), in source order.
+    pub fn take_diagnostics(&self) -> Vec<ParseError> {
+        self.diagnostics.take()
+    }
 
-        self.styles.iter()
-            .filter( move |e| e.selector.is_matches(parents, c, PseudoState::default()) )
+    pub(crate) fn push_diagnostic(&self, e:ParseError) {
+        self.diagnostics.borrow_mut().push(e);
     }
 }
 
+// Grammar version this parser implements, checked against the `#skui <ver>`
+// pragma. Bumped when the surface syntax grows (attribute selectors and
+// `!important` arrived in 0.4). Documents declaring a newer version fail with
+// a "requires skui >= X" error instead of a cryptic parse error; documents
+// pinning an older version refuse the newer syntax the same way.
+pub const GRAMMAR_VERSION: (u32, u32) = (0, 4);
 
-fn parse_style_inner_properties(cursor: Cursor) -> Result< ArrayVec<[StyleProperty;10]> > {
-    let (_cursor, styles) = cursor.consume_collect_until_arrayvec::<10,_,_>( |c| {
-        let c = c.ignore_until( |t| t != Token::Semicolon );
-        let span = c.span();
-        if c.is_eof() {
-            Ok( (c,None) )
-        } else if let (mut new_cursor,[Token::Ident(key), Token::Colon]) = c.fork().consume() {
-            let css_val;
-            (new_cursor,css_val) = new_cursor.consume_collect_until_arrayvec::<5,_,_>( |c| {
-                let span = c.span();
-                let (n,t) = c.consume_one();
-                Ok( (n,CssValue::try_from( (span,t) ).ok()) )
-            } )?;
-            let style_property = StyleProperty { key: key, values: css_val };
-            Ok( (new_cursor,Some(style_property)) )
-        } else {
-            Err(ParseError::expect_ident(span))
-        }
-    } )?;
-    Ok( styles )
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SKUI<'a> {
+    // `#skui 0.4` pragma, when the document declares one
+    pub version: Option<(u32, u32)>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub styles: Vec<Style<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub components: Vec<RootComponent<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub timers: Vec<TimerDef<'a>>,
+    // `theme dark { .. }` groups in document order, layered over `styles`
+    // while their name is active - see `set_active_theme`
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub themes: Vec<ThemeDef<'a>>,
+    // the theme the style matchers currently resolve against
+    pub active_theme: Option<String>,
+    // `@media (..) { .. }` groups in document order, layered over `styles`
+    // while the viewport satisfies their query - see `set_viewport`
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub media_styles: Vec<MediaStyle<'a>>,
+    // the window size the style matchers currently resolve against
+    pub viewport: Option<(f64, f64)>,
+    // `--name` custom property declarations, document scoped (last one wins).
+    // Their `var(--name)` references are already substituted into `styles`.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub vars: HashMap<&'a str, ArrayVec<[CssValue<'a>;5]>>,
+    // `@import "path"` paths in document order. Plain `parse` only records
+    // them; `parse_with_resolver` resolves and merges the referenced documents.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub imports: Vec<&'a str>,
+    // `let name = value` document-scope constants. References in parameters
+    // and style values are already substituted; kept for introspection.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub consts: HashMap<&'a str, Value<'a>>,
 }
 
-// fn parse_def_selectors(cursor:Cursor) -> CursorResult<ArrayVec<[Selector;5]>> {
-//     let Some(SplitCursor{next, result}) = cursor.fork().split_until( |t| t == Token::LBrace )
-//     else { return Err(ParseError::expect_brace_block(cursor.span())) };
-//     let (_,selectors) = result.consume_collect_until_arrayvec::<5,_,_>( |mut c| {
-//         let v = take_match!(c,
-//             [Token::Id(s)] => Selector::Id(s),
-//             [Token::Class(s)] => Selector::Class(s),
-//             [Token::Ident(s)] => Selector::Tag(s),
-//             _ => return Ok( (c,None) )
-//         );
-//         Ok( (c,Some(v)) )
-//     })?;
-//     Ok( (next,selectors) )
-// }
+// `timers { tick: 1s  poll: 500ms }` entry. The driver schedules these and
+// routes a `TimerFired(name)` action each time one elapses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimerDef<'a> {
+    pub name: &'a str,
+    pub interval: std::time::Duration,
+}
 
-fn parse_style_item<'a>(selector: Selector<'a>, cursor:Cursor<'a>) -> CursorResult<'a, Style<'a>> {
-    //let (cursor,selector) = parse_def_selectors(cursor)?;
-    let span = cursor.span();
-    let SplitCursor{next:cursor, result:block} = cursor.consume_delimited_inner( Token::block_brace() ).ok_or_else(|| ParseError::expect_brace_block(span))?;
-    let properties = parse_style_inner_properties( block )?;
-    cursor.ok_with( Style { selector, properties })
+// `theme dark { Label { color: #eee } }` : a named group of style rules that
+// only participates in the cascade while the theme is active. Repeated blocks
+// of the same name append to one group.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThemeDef<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub styles: Vec<Style<'a>>,
 }
 
-fn parse_inner_map(mut cursor:Cursor) -> Result<HashMap<&str, Value>> {
-    let mut map = HashMap::new();
-    while !cursor.is_eof() {
-        let span = cursor.span();
-        if let (next_cursor, [Token::Ident(key), Token::Equal]) = cursor.consume() {
-            cursor = next_cursor;
-            let value;
-            (cursor,value) = parse_value(cursor.fork())?;
-            map.insert(key, value);
-            //TODO : check flag?
-            (cursor,_) = cursor.ignore( [Token::Comma] );
-        } else {
-            return Err(ParseError::expect_kv(span));
-        }
-    }
-    Ok(map)
+// `@media (min-width: 600px) and (max-height: 400px)` : the window-dimension
+// conditions a conditional style group is keyed on. Only the four dimensional
+// features exist - there is no media type to speak of. Absent bounds hold for
+// every size.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaQuery {
+    pub min_width: Option<f64>,
+    pub max_width: Option<f64>,
+    pub min_height: Option<f64>,
+    pub max_height: Option<f64>,
 }
 
-fn parse_inner_array(mut cursor:Cursor) -> Result<Vec<Value>> {
-    let mut values = vec![];
-    while !cursor.is_eof() {
-        let (next_cursor, value) = parse_value(cursor)?;
-        cursor = next_cursor;
-        values.push( value );
-        (cursor,_) = cursor.ignore( [Token::Comma] );
+impl MediaQuery {
+    pub fn matches(&self, width:f64, height:f64) -> bool {
+        self.min_width.is_none_or( |v| width >= v )
+            && self.max_width.is_none_or( |v| width <= v )
+            && self.min_height.is_none_or( |v| height >= v )
+            && self.max_height.is_none_or( |v| height <= v )
     }
-    Ok(values)
 }
 
+// `@media (..) { .. }` group : rules that participate in the cascade only
+// while the viewport satisfies the query - see `set_viewport`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaStyle<'a> {
+    pub query: MediaQuery,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub styles: Vec<Style<'a>>,
+}
 
-fn parse_value(cursor:Cursor) -> CursorResult<Value> {
-    let (cursor,value) = if let Ok( (cursor, comp) ) = parse_component(cursor.fork()) {
-        (cursor, Value::Component(comp))
-    } else if let Some( SplitCursor{next:cursor,result:block} ) = cursor.fork().consume_delimited_inner(Token::block_brace()) {
-        let map = parse_inner_map(block)?;
-        (cursor, Value::Map( map ))
-    } else if let Some( SplitCursor{next:cursor,result:block} ) = cursor.fork().consume_delimited_inner( Token::block_bracket() ) {
-        let arr = parse_inner_array(block)?;
-        (cursor, Value::Array( arr ))
-    }
-    else {
-        let span = cursor.span();
-        let (cursor,value) = cursor.consume_one();
-        let v = match value {
-            Token::Str(s) => Value::String(s),
-            Token::Ident(s) => Value::Ident(s),
-            Token::Integer(v) => Value::Number(Number::I64(v)),
-            Token::Float(v) => Value::Number(Number::F64(v)),
-            Token::True => Value::Bool(true),
-            Token::False => Value::Bool(false),
-            Token::Relative(s) => {
-                let vkeys = ValueKey::vec_from_str(s).map_err(|_| ParseError::invalid_relative_value(span))?;
-                Value::Relative( vkeys )
-            },
-            _ => return Err(ParseError::expect_value(span))
-        };
-        (cursor, v)
-    };
-    cursor.ok_with(value)
+// Memory/size report from `SKUI::stats()`.
+// `string_bytes` sums the borrowed source slices the AST points at (repeats counted),
+// `estimated_heap_bytes` only the owned containers (Vec/HashMap payloads).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DocumentStats {
+    pub component_count: usize,
+    pub style_rule_count: usize,
+    pub style_declaration_count: usize,
+    pub value_count: usize,
+    pub string_bytes: usize,
+    pub estimated_heap_bytes: usize,
 }
 
+impl DocumentStats {
+    fn add_str(&mut self, s:&str) {
+        self.string_bytes += s.len();
+    }
+
+    fn add_component(&mut self, c:&Component) {
+        self.component_count += 1;
+        self.add_str(c.name);
+        if let Some(id) = c.id { self.add_str(id); }
+        c.classes.iter().for_each(|cls| self.add_str(cls));
+        self.add_params(&c.params);
+        self.estimated_heap_bytes += c.properties.len() * (size_of::<&str>() + size_of::<Value>());
+        for (k,v) in c.properties.iter() {
+            self.add_str(k);
+            self.add_value(v);
+        }
+        self.estimated_heap_bytes += c.children.len() * size_of::<Component>();
+        c.children.iter().for_each(|child| self.add_component(child));
+    }
 
-fn parse_inner_parameters(cursor:Cursor) -> Result<Parameters> {
-    if cursor.is_eof() {
-        Ok(Parameters::Args(Vec::new()))
-    } else if let Ok( map ) = parse_inner_map(cursor.fork()) {
-        Ok( Parameters::Map(map) )
-    } else if let Ok( arr ) = parse_inner_array(cursor.fork()) {
-        Ok( Parameters::Args( arr ) )
-    } else {
-        Err( ParseError::not_parameter( cursor.span() ) )
+    fn add_params(&mut self, p:&Parameters) {
+        match p {
+            Parameters::Map(map) => {
+                self.estimated_heap_bytes += map.len() * (size_of::<&str>() + size_of::<Value>());
+                for (k,v) in map.iter() {
+                    self.add_str(k);
+                    self.add_value(v);
+                }
+            }
+            Parameters::Args(args) => {
+                self.estimated_heap_bytes += args.len() * size_of::<Value>();
+                args.iter().for_each(|v| self.add_value(v));
+            }
+        }
+    }
+
+    fn add_value(&mut self, v:&Value) {
+        self.value_count += 1;
+        match v {
+            Value::Ident(s) | Value::String(s) | Value::Closure(s) => self.add_str(s),
+            Value::Array(items) => {
+                self.estimated_heap_bytes += items.len() * size_of::<Value>();
+                items.iter().for_each(|item| self.add_value(item));
+            }
+            Value::Map(map) => {
+                self.estimated_heap_bytes += map.len() * (size_of::<&str>() + size_of::<Value>());
+                for (k,item) in map.iter() {
+                    self.add_str(k);
+                    self.add_value(item);
+                }
+            }
+            Value::Component(c) => self.add_component(c),
+            Value::Relative(keys) => {
+                self.estimated_heap_bytes += keys.len() * size_of::<ValueKey>();
+            }
+            Value::Filtered(keys, filters) => {
+                self.estimated_heap_bytes += keys.len() * size_of::<ValueKey>();
+                self.estimated_heap_bytes += filters.len() * size_of::<FilterCall>();
+                filters.iter().for_each(|f| self.add_str(f.name));
+            }
+            Value::Tr(tr) => {
+                self.add_str(tr.key);
+                self.estimated_heap_bytes += tr.args.len() * (size_of::<&str>() + size_of::<Value>());
+                for (k,item) in tr.args.iter() {
+                    self.add_str(k);
+                    self.add_value(item);
+                }
+            }
+            _ => {}
+        }
     }
 }
 
-fn parse_component<'a>(cursor:Cursor<'a>) -> CursorResult<Component> {
-    let span = cursor.span();
-    let (cursor, Token::Ident(name)) = cursor.consume_one()
-    else { return Err(ParseError::expect_ident(span)) };
+fn walk_components<'a,'b>(c:&'b Component<'a>, visit:&mut impl FnMut(&'b Component<'a>)) {
+    visit(c);
+    c.children.iter().for_each( |child| walk_components(child, visit) );
+}
 
-    let Some( SplitCursor{next:cursor,result:param_block} ) = cursor.fork().consume_delimited_inner( Token::block_paren() )
-    else { return Err(ParseError::expect_parent_block(cursor.span())) };
-    let params = parse_inner_parameters(param_block)?;
+// `reparse` reporting : collect the `#id`s under a changed definition, the
+// definition name itself when it carries none.
+fn mark_changed(rc:&RootComponent, changed:&mut Vec<String>) {
+    let before = changed.len();
+    walk_components(&rc.component, &mut |c| {
+        if let Some(id) = c.id {
+            changed.push(id.to_string());
+        }
+    });
+    if changed.len() == before {
+        changed.push(rc.name.to_string());
+    }
+}
 
-    let span = cursor.span();
-    let (mut cursor,selectors) = cursor.consume_collect_until( |cursor| {
-        let (c, token) = cursor.fork().consume_one();
-        match token {
-            Token::Id(id) => Ok( (c,Some( ComponentIdent::Id(id) ) ) ),
-            Token::Class(cls) => Ok( (c,Some( ComponentIdent::Class(cls) ) ) ),
-            _ => Ok( (cursor,None) )
+pub(crate) fn walk_components_mut<'a>(c:&mut Component<'a>, visit:&mut impl FnMut(&mut Component<'a>)) {
+    visit(c);
+    c.children.iter_mut().for_each( |child| walk_components_mut(child, visit) );
+}
+
+// Read-only AST traversal for linters, analyzers and code generators.
+// Implement only the hooks you care about; `SKUI::walk` drives the recursion
+// - every style rule first, then components depth-first in tree order,
+// descending into parameter/property values and the `Component`s nested
+// inside them (`Item( Label(..) )` values), which hand-rolled loops over
+// `Component::children` miss.
+pub trait Visit<'a,'b> {
+    fn visit_component(&mut self, _c:&'b Component<'a>) {}
+    fn visit_style(&mut self, _style:&'b Style<'a>) {}
+    fn visit_value(&mut self, _value:&'b Value<'a>) {}
+}
+
+fn visit_walk<'a,'b>(c:&'b Component<'a>, visitor:&mut impl Visit<'a,'b>) {
+    visitor.visit_component(c);
+    match &c.params {
+        Parameters::Args(args) => args.iter().for_each( |v| visit_walk_value(v, visitor) ),
+        Parameters::Map(map) => map.values().for_each( |v| visit_walk_value(v, visitor) ),
+    }
+    c.properties.values().for_each( |v| visit_walk_value(v, visitor) );
+    c.children.iter().for_each( |child| visit_walk(child, visitor) );
+}
+
+fn visit_walk_value<'a,'b>(value:&'b Value<'a>, visitor:&mut impl Visit<'a,'b>) {
+    visitor.visit_value(value);
+    match value {
+        Value::Array(items) | Value::Interpolated(items) =>
+            items.iter().for_each( |v| visit_walk_value(v, visitor) ),
+        Value::Map(map) => map.values().for_each( |v| visit_walk_value(v, visitor) ),
+        Value::RelativeOr(_, fallback) => visit_walk_value(fallback, visitor),
+        Value::Component(c) => visit_walk(c, visitor),
+        Value::Tr(tr) => tr.args.values().for_each( |v| visit_walk_value(v, visitor) ),
+        _ => {}
+    }
+}
+
+fn query_walk<'a:'s,'b,'s>(parents:&mut Vec<&'b Component<'a>>, c:&'b Component<'a>, sel:&Selector<'s>, out:&mut Vec<&'b Component<'a>>) {
+    if sel.is_matches(parents, c, PseudoState::ANY) {
+        out.push(c);
+    }
+    parents.push(c);
+    c.children.iter().for_each( |child| query_walk(parents, child, sel, out) );
+    parents.pop();
+}
+
+impl <'a> SKUI <'a> {
+    pub fn get_main_component(&self) -> Option<&RootComponent<'a>> {
+        self.get_root_component("Main")
+    }
+
+    pub fn get_root_component(&self, name:&str) -> Option<&RootComponent<'a>> {
+        self.components.iter().find(|rc| rc.name == name)
+    }
+
+    // `extends` resolution order : the definition itself, then its parent,
+    // grandparent, .. A cycle or an unknown parent ends the chain.
+    pub fn extends_chain(&self, name:&str) -> Vec<&RootComponent<'a>> {
+        let mut chain: Vec<&RootComponent<'a>> = Vec::new();
+        let mut next = self.get_root_component(name);
+        while let Some(rc) = next {
+            if chain.iter().any( |c| std::ptr::eq(*c, rc) ) { break }
+            chain.push(rc);
+            next = rc.extends.and_then( |p| self.get_root_component(p) );
         }
-    })?;
+        chain
+    }
 
-    let mut id = None;
-    let mut classes = ArrayVec::<[&str;5]>::new();
-    for s in selectors.into_iter() {
-        match s {
-            ComponentIdent::Id(identify) => {
-                if id.is_some() {
-                    return Err(ParseError::id_already_defined(span))
+    // === find/query : AST lookups for tests, tooling and programmatic edits ===
+
+    pub fn find_by_id(&self, id:&str) -> Option<&Component<'a>> {
+        let mut found = None;
+        for rc in self.components.iter() {
+            walk_components(&rc.component, &mut |c| {
+                if found.is_none() && c.id == Some(id) {
+                    found = Some(c);
                 }
-                id = Some(identify)
-            },
-            ComponentIdent::Class(cls) => {
-                classes.push(cls)
-            },
-            _ => unreachable!()
+            });
+            if found.is_some() { break }
         }
+        found
     }
-    //classes.dedup();
 
-    let mut properties = HashMap::new();
-    let mut children = Vec::new();
-    if let Some( SplitCursor{next,result:mut comp_block} ) = cursor.fork().consume_delimited_inner(Token::block_brace()) {
-        cursor = next;
-        while !comp_block.is_eof() {
-            let span = comp_block.span();
-            //Try child component block
-            if let (_,[Token::Ident(key), Token::LParen]) = comp_block.fork().consume() {
-                let child;
-                (comp_block, child) = parse_component(comp_block)?;
-                children.push( child );
+    pub fn find_all_by_class(&self, class:&str) -> Vec<&Component<'a>> {
+        let mut out = vec![];
+        for rc in self.components.iter() {
+            walk_components(&rc.component, &mut |c| {
+                if c.classes.contains(&class) {
+                    out.push(c);
+                }
+            });
+        }
+        out
+    }
+
+    pub fn find_all_by_name(&self, name:&str) -> Vec<&Component<'a>> {
+        let mut out = vec![];
+        for rc in self.components.iter() {
+            walk_components(&rc.component, &mut |c| {
+                if c.name == name {
+                    out.push(c);
+                }
+            });
+        }
+        out
+    }
+
+    // CSS-like query against the AST, e.g. `query("Flex > Button.primary")`.
+    // Reuses the selector engine, so combinators behave exactly like styles do.
+    pub fn query(&self, selector:&str) -> std::result::Result<Vec<&Component<'a>>, SelectorParseError> {
+        //the selector grammar is brace terminated
+        let src = format!("{selector} {{");
+        let tks = TokenAndSpan::new(&src);
+        let sel = Selector::parse_from_token(&tks)?;
+        let mut out = vec![];
+        for rc in self.components.iter() {
+            let mut parents = vec![];
+            query_walk(&mut parents, &rc.component, &sel, &mut out);
+        }
+        Ok( out )
+    }
+
+    // Layer an override document on a base one (white-labeling, per-customer
+    // skins). Override roots replace the base subtree carrying the same id,
+    // or the base root of the same name; everything else is appended. Override
+    // styles land after the base styles so they win the cascade.
+    pub fn merge(base:&SKUI<'a>, overrides:&SKUI<'a>) -> SKUI<'a> {
+        let mut merged = base.clone();
+        merged.styles.extend( overrides.styles.iter().cloned() );
+        merged.vars.extend( overrides.vars.iter().map( |(k,v)| (*k, v.clone()) ) );
+        merged.imports.extend( overrides.imports.iter().cloned() );
+        merged.consts.extend( overrides.consts.iter().map( |(k,v)| (*k, v.clone()) ) );
+        for timer in overrides.timers.iter() {
+            match merged.timers.iter_mut().find( |t| t.name == timer.name ) {
+                Some(t) => *t = *timer,
+                None => merged.timers.push(*timer),
             }
-            //Try property
-            else if let (next,[Token::Ident(key), Token::Colon]) = comp_block.fork().consume() {
-                comp_block = next;
-                let value;
-                (comp_block, value) = parse_value(comp_block)?;
-                properties.insert( key, value );
-            } else {
-                return Err(ParseError::expect_brace_block(span));
+        }
+        //override theme rules append after the base theme's, like styles
+        for theme in overrides.themes.iter() {
+            match merged.themes.iter_mut().find( |t| t.name == theme.name ) {
+                Some(t) => t.styles.extend( theme.styles.iter().cloned() ),
+                None => merged.themes.push( theme.clone() ),
+            }
+        }
+        merged.media_styles.extend( overrides.media_styles.iter().cloned() );
+        for rc in overrides.components.iter() {
+            //replace the base subtree with the matching id first
+            if let Some(id) = rc.component.id {
+                let mut replaced = false;
+                for base_rc in merged.components.iter_mut() {
+                    walk_components_mut(&mut base_rc.component, &mut |c| {
+                        if !replaced && c.id == Some(id) {
+                            *c = rc.component.clone();
+                            replaced = true;
+                        }
+                    });
+                    if replaced { break }
+                }
+                if replaced { continue }
+            }
+            //then fall back to replace-by-root-name, else append as a new root
+            match merged.components.iter_mut().find( |b| b.name == rc.name ) {
+                Some(base_rc) => base_rc.component = rc.component.clone(),
+                None => merged.components.push( rc.clone() ),
             }
         }
+        merged
     }
 
-    cursor.ok_with(Component {
-        name,
-        params,
-        id,
-        classes,
-        children,
-        properties,
-    })
-}
-
-// pub fn parse_tokens<'a>( tokens: &'a [Token<'a>] ) -> Result<(Vec<Style<'a>>,Vec<RootComponent<'a>>)> {
-//     let mut cursor = Cursor::new( tokens );
-//     let mut styles = vec![];
-//     let mut root_components = vec![];
-//
-//     while !cursor.is_eof() {
-//         if let (_, [Token::Ident(name), Token::Colon, Token::Ident(_), Token::LParen], ) = cursor.fork().consume() {
-//             let component;
-//             (cursor, component) = parse_component( cursor.fork().skip(2) )?;
-//             root_components.push(RootComponent{name, component});
-//             continue;
-//         }
-//
-//         let mut check_fork = cursor.fork();
-//         let is_style_item = take_match!(check_fork,
-//             [Token::Id(_)] => true,
-//             [Token::Class(_)] => true,
-//             [Token::Ident(_), Token::LParen] => false,
-//             [Token::Ident(_)] => true,
-//             _ => false
-//         );
-//         if is_style_item {
-//             let style;
-//             (cursor, style) = parse_style_item(cursor)?;
-//             styles.push(style);
-//             continue;
-//         }
-//
-//         //Error
-//         return Err(ParseError::unknown_start(cursor.span()));
-//     }
-//     Ok( (styles, root_components) )
-// }
+    // Rename a class across the whole document : component class lists and
+    // every stylesheet selector that mentions it. Returns the number of sites.
+    pub fn rename_class(&mut self, old:&str, new:&'a str) -> usize {
+        let mut count = 0;
+        for style in self.styles.iter_mut() {
+            count += style.selector.rename_class(old, new);
+        }
+        for rc in self.components.iter_mut() {
+            walk_components_mut(&mut rc.component, &mut |c| {
+                if c.remove_class(old) {
+                    c.add_class(new);
+                    count += 1;
+                }
+            });
+        }
+        count
+    }
 
-pub fn parse_tokens<'a>( tks:&'a TokenAndSpan<'a> ) -> Result<(Vec<Style<'a>>,Vec<RootComponent<'a>>)> {
-    let cut_off = tks.tokens.len();
-    let mut cursor = tks.start_cursor();
-    let mut styles = vec![];
-    let mut root_components = vec![];
+    // Drive a `Visit` implementation over the whole document : styles in
+    // declaration order, then every root's component tree depth-first,
+    // including `Component`s nested inside parameter/property values.
+    pub fn walk<'b>(&'b self, visitor:&mut impl Visit<'a,'b>) {
+        for style in self.styles.iter() {
+            visitor.visit_style(style);
+        }
+        for rc in self.components.iter() {
+            visit_walk(&rc.component, visitor);
+        }
+    }
 
-    while !cursor.is_eof() {
-        //raw to trimmed for Component
-        if let (_, [Token::Ident(name), Token::Colon, Token::Ident(_), Token::LParen], ) = cursor.fork().consume() {
-            let component;
-            (cursor, component) = parse_component( cursor.fork().skip(2) )?;
-            root_components.push(RootComponent{name, component});
-            continue;
+    // Rough cost report for very large (generated) documents
+    pub fn stats(&self) -> DocumentStats {
+        let mut stats = DocumentStats::default();
+        for style in self.styles.iter() {
+            stats.style_rule_count += 1;
+            stats.style_declaration_count += style.properties.len();
+            for prop in style.properties.iter() {
+                stats.add_str(prop.key);
+                for v in prop.values.iter() {
+                    if let CssValue::Ident(s) | CssValue::Str(s) | CssValue::HexColor(s) = v {
+                        stats.add_str(s);
+                    }
+                }
+            }
         }
+        stats.estimated_heap_bytes += self.styles.len() * size_of::<Style>();
+        for rc in self.components.iter() {
+            stats.add_str(rc.name);
+            stats.add_component(&rc.component);
+        }
+        stats.estimated_heap_bytes += self.components.len() * size_of::<RootComponent>();
+        stats
+    }
 
-        //trimmed to raw for selector
-        cursor = tks.raw_cursor(cursor);
-        let span = cursor.span();
-        let selector;
-        (cursor, selector) = SelectorParser::parse(cursor).map_err(|e| ParseError::not_selector(span,e) )?;
+    pub fn parse(tks: &'a TokenAndSpan) -> Result<Self, SKUIParseError> {
+        Self::parse_with_options(tks, &ParseOptions::default())
+    }
 
-        //trimmed to raw for style item
-        cursor = tks.trimmed_cursor(cursor);
-        let span = cursor.span();
-        let style;
-        (cursor,style) = parse_style_item(selector, cursor)?;
-        styles.push(style);
-        cursor = tks.trimmed_cursor(cursor);
-        continue;
+    pub fn parse_with_options(tks: &'a TokenAndSpan, opts:&ParseOptions) -> Result<Self, SKUIParseError> {
+        parse(tks, opts)
+    }
 
-        // let mut check_fork = cursor.fork();
-        // let is_style_item = take_match!(check_fork,
-        //     [Token::Id(_)] => true,
-        //     [Token::Class(_)] => true,
-        //     [Token::Ident(_), Token::LParen] => false,
-        //     [Token::Ident(_)] => true,
-        //     _ => false
-        // );
-        // if is_style_item {
-        //     let style;
-        //     (cursor, style) = parse_style_item(cursor)?;
-        //     styles.push(style);
-        //     continue;
-        // }
+    // Error-collecting parse for live editors : instead of stopping at the
+    // first error, bad statements are skipped to the next statement boundary
+    // and every recovered error is returned alongside whatever parsed cleanly.
+    // Limit errors (`max_depth`, ..) still abort; the partial document built
+    // up to that point is returned with the limit error appended.
+    pub fn parse_recovering(tks: &'a TokenAndSpan) -> (Self, Vec<SKUIParseError>) {
+        Self::parse_recovering_with_options(tks, &ParseOptions::default().with_lenient(true))
+    }
 
-        //Error
-        //return Err(ParseError::unknown_start(span));
+    pub fn parse_recovering_with_options(tks: &'a TokenAndSpan, opts:&ParseOptions) -> (Self, Vec<SKUIParseError>) {
+        let parsed = parse(tks, opts);
+        let mut errors: Vec<SKUIParseError> = opts.take_diagnostics().into_iter()
+            .map( |e| SKUIParseError::new(e, tks) )
+            .collect();
+        match parsed {
+            Ok(doc) => (doc, errors),
+            Err(e) => {
+                errors.push(e);
+                let empty = SKUI {
+                    version: None, styles: vec![], components: vec![], timers: vec![],
+                    themes: vec![], active_theme: None, media_styles: vec![], viewport: None,
+                    vars: Default::default(), imports: vec![], consts: Default::default(),
+                };
+                (empty, errors)
+            }
+        }
     }
-    Ok( (styles, root_components) )
-}
 
-#[derive(Debug,Clone)]
-pub struct SKUIParseError {
-    pub kind: ParseError,
-    pub span: Span,
-}
+    // Hot-reload aid for editors : parse the edited source, swap the document
+    // in place and report which component ids are affected, so the caller
+    // rebuilds only those subtrees instead of the whole window. Tokenizing is
+    // cheap enough to redo whole - the expensive part, widget rebuilding, is
+    // what the returned set narrows. Roots are compared structurally, so a
+    // whitespace-only edit reports nothing. A changed root without any `#id`
+    // inside is reported under its definition name; a style/var/const change
+    // conservatively marks every definition dirty. `edit_range` is the edited
+    // byte range in the new source (empty = nothing to do).
+    pub fn reparse(&mut self, new_tks:&'a TokenAndSpan, edit_range:std::ops::Range<usize>) -> Result<Vec<String>, SKUIParseError> {
+        if edit_range.is_empty() {
+            return Ok( vec![] );
+        }
+        let mut new = Self::parse(new_tks)?;
+        //the active theme and viewport are runtime state, not source : they
+        //survive the swap
+        new.active_theme = self.active_theme.take();
+        new.viewport = self.viewport;
+        let mut changed: Vec<String> = vec![];
+        let styles_changed = self.styles != new.styles
+            || self.themes != new.themes
+            || self.media_styles != new.media_styles
+            || self.vars != new.vars
+            || self.consts != new.consts;
+
+        for rc in new.components.iter() {
+            let dirty = styles_changed || match self.get_root_component(rc.name) {
+                Some(old) => old.extends != rc.extends || old.component != rc.component,
+                None => true,
+            };
+            if dirty {
+                mark_changed(rc, &mut changed);
+            }
+        }
+        //removed definitions : their former ids need a rebuild too
+        for rc in self.components.iter() {
+            if new.get_root_component(rc.name).is_none() {
+                mark_changed(rc, &mut changed);
+            }
+        }
+        changed.sort();
+        changed.dedup();
+        *self = new;
+        Ok( changed )
+    }
 
-pub struct TokenAndSpan<'a> {
-    cut_off: usize,
+    // pub fn styles(&self, comp:&Component) -> impl Iterator<Item=&Style> {
+    //     self.styles.iter().filter(|style| {
+    //         style.selector.
+    //     })
+    // }
 
-    tokens: Vec<Token<'a>>,
-    idxs: Vec<usize>,
+    pub fn get_lookup_scoped_component(&self, c:&'a Component, targets:&[&str]) -> &Component<'a> {
+        let item_wrap = self.components.iter()
+            .find(|rc|
+                rc.name == c.name
+                    && targets.iter().find( |&&s| s == rc.component.name ).is_some()
+                    ).map( |rc| &rc.component);
+        item_wrap.unwrap_or( c )
+    }
 
-    trimmed_tokens: Vec<Token<'a>>,
-    trimmed_idxs: Vec<usize>,
+    // pub fn find_component<'b>(parents:&'b mut Vec<&'a Component<'a>>, comp:&'a Component<'a>, target:&'a Component<'a>) -> bool {
+    //     if std::ptr::eq(comp, target) {
+    //         true
+    //     } else {
+    //         parents.push(comp);
+    //         for child in comp.children.iter() {
+    //             if Self::find_component(parents, child, target) {
+    //                 return true;
+    //             }
+    //         }
+    //         parents.pop();
+    //         false
+    //     }
+    // }
 
-    spans: Vec<Span>
-}
+    pub fn get_styles<'b>(&'b self, parents:&'b [&'a Component<'a>], c:&'a Component<'a>) -> impl Iterator<Item=Style<'a>> {
 
-impl <'a> TokenAndSpan<'a> {
-    pub fn new(src:&'a str) -> Self {
-        let mut trimmed_tokens = Vec::new();
-        let mut trimmed_idxs = Vec::new();
-        let mut idxs = Vec::new();
-        let mut tokens = Vec::new();
-        let mut spans = Vec::new();
+        // let root = self.get_main_component().unwrap();
+        // let mut curr = &root.component;
+        // let mut parents = vec![];
+        // Self::find_comp(&mut parents, &root.component, c);
 
-        let mut tidx = 0;
-        for (idx,(token,span)) in Token::lexer(src)
-            .spanned()
-            .filter_map(| (t,s) | t.map( |v| (v,s) ).ok() )
-            .enumerate() {
-            idxs.push( tidx );
-            tokens.push(token);
-            spans.push(span);
-            if token != Token::Whitespace {
-                trimmed_tokens.push(token);
-                trimmed_idxs.push(idx);
-                tidx += 1;
+        //`ANY` : pseudo-class rules all match here so the integration can
+        //compile them into its own state-aware properties
+        Self::split_important( self.matching_styles(parents, c, PseudoState::ANY).map( |(_,s)| s ) ).into_iter()
+    }
+
+    // `get_styles` with live pseudo-class state : the provider reports the
+    // component's current hover/active/focus/disabled by id, so `:hover` rules
+    // match only while the state actually holds. Components without an id
+    // match with the default state, like plain `get_styles`.
+    pub fn get_styles_with_state<'b>(&'b self, parents:&'b [&'a Component<'a>], c:&'a Component<'a>, provider:&'b dyn selector::PseudoStateProvider) -> impl Iterator<Item=Style<'a>> {
+        let state = c.id.map( |id| provider.pseudo_state(id) ).unwrap_or_default();
+        Self::split_important( self.matching_styles(parents, c, state).map( |(_,s)| s ) ).into_iter()
+    }
+
+    // `!important` cascades per declaration, the way CSS does : the matched
+    // rules replay as two passes, normal declarations first and important
+    // ones after, each pass in cascade order. A rule mixing the two is
+    // emitted once per pass with only that pass's declarations, so a single
+    // important declaration no longer drags its rule's normal ones over
+    // higher-specificity rules.
+    fn split_important<'b>(matched: impl Iterator<Item=&'b Style<'a>>) -> Vec<Style<'a>> where 'a: 'b {
+        let matched: Vec<&Style<'a>> = matched.collect();
+        let mut passes = Vec::with_capacity( matched.len() );
+        for important in [false, true] {
+            for s in matched.iter() {
+                let properties: ArrayVec<[StyleProperty<'a>;10]> =
+                    s.properties.iter().filter( |p| p.important == important ).cloned().collect();
+                if !properties.is_empty() {
+                    passes.push( Style { selector: s.selector.clone(), properties } );
+                }
             }
         }
-        //cut_off + 1로 하여 두 커서가 공존할 수 없게 함
-        Self {
-            cut_off:tokens.len() + 1, tokens, idxs, trimmed_tokens, trimmed_idxs, spans
-        }
+        passes
     }
 
-    pub fn start_cursor(&self) -> Cursor {
-        Cursor::new_offset( &self.trimmed_tokens[..], self.cut_off )
+    // Matching rules in cascade order : weakest first, so a consumer applying
+    // them in sequence ends up with the strongest declaration winning. Sorted
+    // by (specificity, source order) - a later bare tag rule can no longer
+    // override an earlier `.class` rule. `!important` does not participate
+    // here : `split_important` and `styles_for` handle it per declaration.
+    fn matching_styles<'b,'c>(&'c self, parents:&'b [&'c Component<'a>], c:&'c Component<'a>, state:PseudoState) -> impl Iterator<Item=(usize, &'c Style<'a>)> {
+        //hash the ancestor chain once; rules whose ancestor part cannot match
+        //are rejected in O(1) instead of re-walking the chain per rule
+        let filter = selector::AncestorFilter::from_parents(parents);
+        //media then theme rules chain after the base ones, so their indices
+        //continue past `styles.len()` and they win cascade ties - the active
+        //theme last, so a skin beats a responsive override
+        let viewport = self.viewport;
+        let media = self.media_styles.iter()
+            .filter( move |m| viewport.is_some_and( |(w,h)| m.query.matches(w, h) ) )
+            .flat_map( |m| m.styles.iter() );
+        let active = self.active_theme.as_deref();
+        let themed = self.themes.iter()
+            .filter( move |t| Some(t.name) == active )
+            .flat_map( |t| t.styles.iter() );
+        let mut matched: Vec<(Specificity, usize, &Style<'a>)> = self.styles.iter()
+            .chain(media)
+            .chain(themed)
+            .enumerate()
+            .filter( |(_,e)| !e.selector.fast_reject(&filter) )
+            .filter_map( |(i,e)| {
+                e.selector.match_score(parents, c, state)
+                    .map( |spec| (spec, i, e) )
+            })
+            .collect();
+        matched.sort_by_key( |&(spec, i, _)| (spec, i) );
+        matched.into_iter().map( |(_,i,e)| (i, e) )
     }
 
-    pub fn trimmed_cursor<'b>(&'b self, cursor:Cursor<'b>) -> Cursor<'b> {
-        if cursor.idx() < self.cut_off && !cursor.is_eof() { //this cursor raw cursor
-            let mirror_trimmed_idx = self.idxs[cursor.idx()];
-            Cursor::new_offset( &self.trimmed_tokens[ mirror_trimmed_idx .. ], mirror_trimmed_idx + self.cut_off )
-        } else {
-            cursor
+    // Post-cascade styling of the component carrying id `query` (with or
+    // without the leading `#`), one entry per declared key : what the key
+    // resolved to and which rule it came from. This is the programmatic
+    // counterpart to the visual inspector - tests assert styling outcomes
+    // without building widgets. Matches with the default pseudo state, so
+    // `:hover` and friends do not contribute. Empty when no component in the
+    // document carries the id.
+    pub fn styles_for(&self, query:&str) -> Vec<ResolvedDeclaration<'a>> {
+        let id = query.trim_start_matches('#');
+        for rc in self.components.iter() {
+            let mut parents = vec![];
+            if let Some(c) = find_with_parents(&mut parents, &rc.component, id) {
+                let mut resolved: Vec<ResolvedDeclaration<'a>> = vec![];
+                //cascade order is weakest first, so overwriting an earlier
+                //entry for the same key is exactly the consumer contract
+                //`matching_styles` documents. Normal declarations resolve
+                //first and the important pass overwrites them after, so
+                //`!important` wins per declaration, not per rule.
+                for important in [false, true] {
+                    for (i, style) in self.matching_styles(&parents, c, PseudoState::default()) {
+                        for prop in style.properties.iter().filter( |p| p.important == important ) {
+                            let decl = ResolvedDeclaration {
+                                key: prop.key,
+                                values: prop.values.clone(),
+                                important: prop.important,
+                                selector: style.selector.to_string(),
+                                rule_index: i,
+                            };
+                            match resolved.iter_mut().find( |d| d.key == prop.key ) {
+                                Some(slot) => *slot = decl,
+                                None => resolved.push(decl),
+                            }
+                        }
+                    }
+                }
+                return resolved;
+            }
         }
+        vec![]
     }
 
-    pub fn raw_cursor<'b>(&'b self, cursor:Cursor<'b>) -> Cursor<'b> {
-        if cursor.idx() >= self.cut_off && !cursor.is_eof() { //this cursor is trimmed cursor
-            let slice_idx = cursor.idx() - self.cut_off;
-            let mirror_raw_idx = self.trimmed_idxs[slice_idx];
-            Cursor::new_offset( &self.tokens[ mirror_raw_idx .. ], mirror_raw_idx )
-        } else {
-            cursor
+    // The cascade with the named theme layered in : the theme's rules follow
+    // the document's own, so they win ties the way a loaded sheet does. An
+    // unknown name yields the base rules unchanged.
+    pub fn styles_for_theme(&self, name:&str) -> Vec<Style<'a>> {
+        let mut styles = self.styles.clone();
+        for t in self.themes.iter().filter( |t| t.name == name ) {
+            styles.extend( t.styles.iter().cloned() );
         }
+        styles
     }
 
-    pub fn span(&self, idx:usize) -> Span {
-        let idx = idx % self.cut_off;
-        self.spans[idx].clone()
+    // Runtime theme switch, no reparse : from here on `get_styles` and
+    // `styles_for` resolve against the base rules plus the named theme's.
+    // The integration rebuilds its widgets and every component picks the
+    // themed rules up. `None` (and unknown names) fall back to the base
+    // rules alone.
+    pub fn set_active_theme(&mut self, name:Option<&str>) {
+        self.active_theme = name.map(String::from);
     }
 
-    pub fn render_error(&self, input:&str, idx:usize, context_lines:usize) -> String {
-        self.render_error_from_span(input, self.span(idx), context_lines)
+    // The cascade for a window of this size : rules from every `@media` group
+    // the size satisfies follow the document's own, so they win ties.
+    pub fn get_styles_for_viewport(&self, width:f64, height:f64) -> Vec<Style<'a>> {
+        let mut styles = self.styles.clone();
+        for m in self.media_styles.iter().filter( |m| m.query.matches(width, height) ) {
+            styles.extend( m.styles.iter().cloned() );
+        }
+        styles
     }
 
-    pub fn render_error_from_span( &self, input: &str, span: Span, context_lines: usize) -> String {
-        #[derive(Debug)]
-        struct LineInfo {
-            line_no: usize,      // 1-based
+    // Viewport tracking for responsive documents : wire this to a resize
+    // notification (the integration's `ResizeObserver`), rebuild, and the
+    // rules under every satisfied `@media` participate in style lookups.
+    // `None` keeps media groups out of the cascade entirely.
+    pub fn set_viewport(&mut self, size:Option<(f64,f64)>) {
+        self.viewport = size;
+    }
+}
+
+// Depth-first search for the component with this id, leaving its ancestor
+// chain (root first, element excluded) in `parents` - the shape the selector
+// matcher takes.
+fn find_with_parents<'b,'a>(parents:&mut Vec<&'b Component<'a>>, c:&'b Component<'a>, id:&str) -> Option<&'b Component<'a>> {
+    if c.id == Some(id) {
+        return Some(c);
+    }
+    parents.push(c);
+    for child in c.children.iter() {
+        if let Some(found) = find_with_parents(parents, child, id) {
+            return Some(found);
+        }
+    }
+    parents.pop();
+    None
+}
+
+
+fn parse_style_inner<'a>(selector:Selector<'a>, mut cursor:Cursor<'a>) -> Result<Vec<Style<'a>>> {
+    let mut properties = ArrayVec::<[StyleProperty;10]>::new();
+    let mut nested: Vec<Style<'a>> = vec![];
+    loop {
+        cursor = cursor.ignore_until( |t| t != Token::Semicolon );
+        let span = cursor.span();
+        if cursor.is_eof() {
+            break;
+        }
+        //SCSS-style nesting : a `{` ahead of the next `;` means this is a
+        //nested rule head, not a declaration (a declaration directly before
+        //a nested rule therefore needs its `;`). The body parses on the
+        //trimmed stream, so whitespace is not significant in the nested
+        //selector - compound and `>` forms are available, the descendant
+        //relation to the enclosing rule is implicit.
+        if nested_rule_ahead(cursor.fork()) {
+            let inner_sel;
+            (cursor, inner_sel) = SelectorParser::parse(cursor)
+                .map_err( |e| ParseError::not_selector(span, e) )?;
+            let flattened = Selector::descendant(selector.clone(), inner_sel);
+            let mut inner_styles;
+            (cursor, inner_styles) = parse_style_item(flattened, cursor)?;
+            nested.append(&mut inner_styles);
+        } else if let (mut new_cursor,[Token::Ident(key) | Token::VarName(key), Token::Colon]) = cursor.fork().consume() {
+            let important = std::cell::Cell::new(false);
+            let css_val;
+            (new_cursor,css_val) = new_cursor.consume_collect_until_arrayvec::<5,_,_>( |c| {
+                //`var(--name)` : kept as CssValue::Var, substituted after the parse
+                if let (n,[Token::Ident("var"), Token::LParen, Token::VarName(name), Token::RParen]) = c.fork().consume() {
+                    return Ok( (n,Some(CssValue::Var(name))) );
+                }
+                //trailing `!important` flags the declaration and ends its values
+                if let (n,[Token::Bang, Token::Ident("important")]) = c.fork().consume() {
+                    important.set(true);
+                    return Ok( (n,None) );
+                }
+                let span = c.span();
+                let (n,t) = c.consume_one();
+                Ok( (n,CssValue::try_from( (span,t) ).ok()) )
+            } )?;
+            if properties.len() < properties.capacity() {
+                properties.push( StyleProperty { key, values: css_val, important: important.get() } );
+            }
+            cursor = new_cursor;
+        } else {
+            return Err(ParseError::expect_ident(span));
+        }
+    }
+    //a rule holding only nested rules contributes no (empty) entry of its own
+    let mut styles = Vec::with_capacity(nested.len() + 1);
+    if !properties.is_empty() || nested.is_empty() {
+        styles.push( Style { selector, properties } );
+    }
+    styles.append(&mut nested);
+    Ok( styles )
+}
+
+//look ahead : does a `{` come before the next `;` (or the end of the body)?
+fn nested_rule_ahead(mut cursor:Cursor) -> bool {
+    while !cursor.is_eof() {
+        let t;
+        (cursor, t) = cursor.consume_one();
+        match t {
+            Token::LBrace => return true,
+            Token::Semicolon => return false,
+            _ => {}
+        }
+    }
+    false
+}
+
+// Custom property handling : `--name` declarations are pulled out of the rule
+// list into a document scoped table (last one wins, no per-selector cascade)
+// and every `var(--name)` reference is substituted in place. A declaration
+// whose reference cannot be resolved is dropped whole rather than applied with
+// a hole in it.
+fn resolve_css_vars<'a>(styles:&mut Vec<Style<'a>>) -> HashMap<&'a str, ArrayVec<[CssValue<'a>;5]>> {
+    let mut vars: HashMap<&'a str, ArrayVec<[CssValue<'a>;5]>> = HashMap::new();
+    for style in styles.iter_mut() {
+        let mut kept = ArrayVec::<[StyleProperty;10]>::new();
+        for prop in style.properties.iter() {
+            if prop.key.starts_with("--") {
+                vars.insert(prop.key, prop.values.clone());
+            } else {
+                kept.push(prop.clone());
+            }
+        }
+        style.properties = kept;
+    }
+    substitute_css_vars(styles, &vars);
+    vars
+}
+
+// Replace `var(--name)` references with the declared values; a declaration
+// referencing an unknown variable drops out entirely, like in resolve.
+fn substitute_css_vars<'a>(styles:&mut [Style<'a>], vars:&HashMap<&'a str, ArrayVec<[CssValue<'a>;5]>>) {
+    for style in styles.iter_mut() {
+        for prop in style.properties.iter_mut() {
+            if prop.values.iter().any( |v| matches!(v, CssValue::Var(_)) ) {
+                let mut resolved = ArrayVec::new();
+                let mut ok = true;
+                for v in prop.values.iter() {
+                    match v {
+                        CssValue::Var(name) => ok &= splice_css_var(vars, name, &mut resolved, 0),
+                        v => if resolved.len() < resolved.capacity() { resolved.push(*v) },
+                    }
+                }
+                prop.values = if ok { resolved } else { ArrayVec::new() };
+            }
+        }
+        style.properties.retain( |p| !p.values.is_empty() );
+    }
+}
+
+// Declarations may reference other variables; chase the chain with a small
+// depth guard so a reference cycle cannot loop forever.
+fn splice_css_var<'a>(vars:&HashMap<&'a str, ArrayVec<[CssValue<'a>;5]>>, name:&str, out:&mut ArrayVec<[CssValue<'a>;5]>, depth:usize) -> bool {
+    if depth > 4 { return false }
+    let Some(values) = vars.get(name) else { return false };
+    for v in values.iter() {
+        match v {
+            CssValue::Var(inner) => if !splice_css_var(vars, inner, out, depth + 1) { return false },
+            v => if out.len() < out.capacity() { out.push(*v) },
+        }
+    }
+    true
+}
+
+// `let` constants : declarations may reference earlier ones; references
+// resolve at insertion so the finished table holds only concrete values.
+fn resolve_consts<'a>(list:Vec<(&'a str, Value<'a>)>) -> HashMap<&'a str, Value<'a>> {
+    let mut consts = HashMap::new();
+    for (name, value) in list {
+        let value = match value {
+            Value::Ident(refname) => consts.get(refname).cloned().unwrap_or(Value::Ident(refname)),
+            v => v,
+        };
+        consts.insert(name, value);
+    }
+    consts
+}
+
+// Substituted after parsing - like custom properties, builders and style
+// consumers never see the constant names. An ident that matches no constant
+// stays an ident (enum-like values such as `axis=horizontal` keep working).
+fn substitute_consts<'a>(styles:&mut [Style<'a>], components:&mut [RootComponent<'a>], consts:&HashMap<&'a str, Value<'a>>) {
+    if consts.is_empty() { return }
+    for style in styles.iter_mut() {
+        for prop in style.properties.iter_mut() {
+            for v in prop.values.iter_mut() {
+                if let CssValue::Ident(name) = v {
+                    if let Some(c) = consts.get(name).and_then(const_css_value) {
+                        *v = c;
+                    }
+                }
+            }
+        }
+    }
+    for rc in components.iter_mut() {
+        substitute_consts_component(&mut rc.component, consts);
+    }
+}
+
+// The CssValue shape of a constant, where one exists : `let accent = #ff8800`
+// is usable as a style value, `let title = {..}` is not.
+fn const_css_value<'a>(v:&Value<'a>) -> Option<CssValue<'a>> {
+    Some( match v {
+        Value::Number(Number::I64(n)) => CssValue::Number(*n as f64),
+        Value::Number(Number::F64(n)) => CssValue::Number(*n),
+        Value::Unit(u) | Value::Color(u) => *u,
+        Value::String(s) => CssValue::Str(s),
+        _ => return None,
+    } )
+}
+
+fn substitute_consts_component<'a>(c:&mut Component<'a>, consts:&HashMap<&'a str, Value<'a>>) {
+    match &mut c.params {
+        Parameters::Map(map) => map.values_mut().for_each( |v| substitute_const_value(v, consts) ),
+        Parameters::Args(list) => list.iter_mut().for_each( |v| substitute_const_value(v, consts) ),
+    }
+    c.properties.values_mut().for_each( |v| substitute_const_value(v, consts) );
+    c.children.iter_mut().for_each( |ch| substitute_consts_component(ch, consts) );
+}
+
+fn substitute_const_value<'a>(v:&mut Value<'a>, consts:&HashMap<&'a str, Value<'a>>) {
+    match v {
+        Value::Ident(name) => {
+            if let Some(c) = consts.get(*name) {
+                *v = c.clone();
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each( |i| substitute_const_value(i, consts) ),
+        Value::Map(map) => map.values_mut().for_each( |i| substitute_const_value(i, consts) ),
+        Value::Component(c) => substitute_consts_component(c, consts),
+        Value::Tr(tr) => tr.args.values_mut().for_each( |i| substitute_const_value(i, consts) ),
+        _ => {}
+    }
+}
+
+// A rule and, flattened behind it, any rules nested in its body :
+// `.card { padding: 4px; .title { font-size: 18 } }` yields `.card` plus
+// `.card .title`.
+fn parse_style_item<'a>(selector: Selector<'a>, cursor:Cursor<'a>) -> CursorResult<'a, Vec<Style<'a>>> {
+    let span = cursor.span();
+    let SplitCursor{next:cursor, result:block} = cursor.consume_delimited_inner( Token::block_brace() ).ok_or_else(|| ParseError::expect_brace_block(span))?;
+    let styles = parse_style_inner( selector, block )?;
+    cursor.ok_with( styles )
+}
+
+fn parse_inner_map<'a>(mut cursor:Cursor<'a>, opts:&ParseOptions) -> Result<HashMap<&'a str, Value<'a>>> {
+    let mut map = HashMap::new();
+    while !cursor.is_eof() {
+        let span = cursor.span();
+        if let (next_cursor, [Token::Ident(key), Token::Equal]) = cursor.consume() {
+            cursor = next_cursor;
+            let value;
+            (cursor,value) = parse_value(cursor.fork(), opts)?;
+            map.insert(key, value);
+            //TODO : check flag?
+            (cursor,_) = cursor.ignore( [Token::Comma] );
+        } else {
+            return Err(ParseError::expect_kv(span));
+        }
+    }
+    Ok(map)
+}
+
+// `Icon("save.svg", color=#ff8800)` : positional values followed by named
+// ones, lowered to a Map with positional entries under their index keys
+// (see `POSITIONAL_KEYS`). Tried after the pure forms, so all-named and
+// all-positional lists keep their existing representations.
+fn parse_inner_mixed<'a>(mut cursor:Cursor<'a>, opts:&ParseOptions) -> Result<HashMap<&'a str, Value<'a>>> {
+    let mut map = HashMap::new();
+    let mut pos = 0usize;
+    let mut named = false;
+    while !cursor.is_eof() {
+        let span = cursor.span();
+        if let (next, [Token::Ident(key), Token::Equal]) = cursor.fork().consume() {
+            let value;
+            (cursor, value) = parse_value(next, opts)?;
+            map.insert(key, value);
+            named = true;
+        } else {
+            //positional entries must precede named ones
+            if named { return Err(ParseError::expect_kv(span)) }
+            let key = *POSITIONAL_KEYS.get(pos).ok_or( ParseError::expect_kv(span) )?;
+            let value;
+            (cursor, value) = parse_value(cursor, opts)?;
+            map.insert(key, value);
+            pos += 1;
+        }
+        (cursor,_) = cursor.ignore( [Token::Comma] );
+    }
+    Ok(map)
+}
+
+fn parse_inner_array<'a>(mut cursor:Cursor<'a>, opts:&ParseOptions) -> Result<Vec<Value<'a>>> {
+    let mut values = vec![];
+    while !cursor.is_eof() {
+        let (next_cursor, value) = parse_value(cursor, opts)?;
+        cursor = next_cursor;
+        values.push( value );
+        (cursor,_) = cursor.ignore( [Token::Comma] );
+    }
+    Ok(values)
+}
+
+
+// `tr("key")` : translation reference. must be checked before the component path
+// because it shares the `Ident(..)` call shape
+fn parse_tr<'a>(cursor:Cursor<'a>, opts:&ParseOptions) -> CursorResult<'a, Value<'a>> {
+    let span = cursor.span();
+    let (cursor, Token::Ident("tr")) = cursor.fork().consume_one()
+    else { return Err(ParseError::expect_value(span)) };
+    let Some( SplitCursor{next:cursor, result:block} ) = cursor.consume_delimited_inner( Token::block_paren() )
+    else { return Err(ParseError::expect_parent_block(span)) };
+    let span = block.span();
+    let (block, Token::Str(key)) = block.consume_one()
+    else { return Err(ParseError::expect_value(span)) };
+    let (block,_) = block.ignore( [Token::Comma] );
+    let args = if block.is_eof() { HashMap::new() } else { parse_inner_map(block, opts)? };
+    cursor.ok_with( Value::Tr( TrRef { key, args } ) )
+}
+
+// Literal fallback after `:-` : a quoted string, number, bool or bare ident.
+fn parse_fallback(s:&str) -> Option<Value<'_>> {
+    if s.len() >= 2 && s.starts_with('"') && s.ends_with('"') {
+        return Some( Value::String(&s[1..s.len()-1]) )
+    }
+    if let Ok(v) = s.parse::<i64>() { return Some( Value::Number(Number::I64(v)) ) }
+    if let Ok(v) = s.parse::<f64>() { return Some( Value::Number(Number::F64(v)) ) }
+    match s {
+        "true" => Some( Value::Bool(true) ),
+        "false" => Some( Value::Bool(false) ),
+        "" => None,
+        s if s.bytes().all( |b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-' ) => Some( Value::Ident(s) ),
+        _ => None,
+    }
+}
+
+// `${path | filter(..) | ..}` body : path then an optional filter chain.
+// `${path:-fallback}` gives an inline default instead (no filters with it).
+// Shared by whole-value bindings and string interpolation segments.
+fn parse_binding<'a>(s:&'a str, span:CursorSpan) -> Result<Value<'a>> {
+    let invalid = || ParseError::invalid_relative_value(span.clone());
+    if let Some(i) = s.find(":-") {
+        let vkeys = ValueKey::vec_from_str(s[..i].trim()).map_err( |_| invalid() )?;
+        let fallback = parse_fallback(s[i+2..].trim()).ok_or_else(invalid)?;
+        return Ok( Value::RelativeOr(vkeys, Box::new(fallback)) )
+    }
+    let mut parts = s.split('|');
+    let path = parts.next().unwrap_or(s);
+    let vkeys = ValueKey::vec_from_str(path).map_err( |_| invalid() )?;
+    let filters = parts
+        .map( FilterCall::from_str )
+        .collect::<Result<Vec<_>,_>>()
+        .map_err( |_| invalid() )?;
+    Ok( if filters.is_empty() {
+        Value::Relative( vkeys )
+    } else {
+        Value::Filtered( vkeys, filters )
+    } )
+}
+
+// `"Hello, ${0.user}!"` : split the literal into string and binding segments.
+// Strings without `${` stay plain `Value::String`.
+fn parse_interpolated<'a>(s:&'a str, span:CursorSpan) -> Result<Value<'a>> {
+    //`\${` is an escaped literal, not a binding (see `unescape`)
+    fn find_binding(s:&str) -> Option<usize> {
+        let mut from = 0;
+        while let Some(i) = s[from..].find("${").map( |i| i + from ) {
+            if i > 0 && s.as_bytes()[i-1] == b'\\' {
+                from = i + 2;
+                continue;
+            }
+            return Some(i)
+        }
+        None
+    }
+    if find_binding(s).is_none() {
+        return Ok( Value::String(s) );
+    }
+    let mut segments = Vec::new();
+    let mut rest = s;
+    while let Some(start) = find_binding(rest) {
+        let Some(end) = rest[start..].find('}')
+        else { return Err(ParseError::invalid_relative_value(span.clone())) };
+        if start > 0 {
+            segments.push( Value::String(&rest[..start]) );
+        }
+        segments.push( parse_binding(&rest[start+2 .. start+end], span.clone())? );
+        rest = &rest[start+end+1 ..];
+    }
+    if !rest.is_empty() {
+        segments.push( Value::String(rest) );
+    }
+    Ok( Value::Interpolated(segments) )
+}
+
+fn parse_value<'a>(cursor:Cursor<'a>, opts:&ParseOptions) -> CursorResult<'a, Value<'a>> {
+    //speculative parses below may bail out half way : restore the nesting depth
+    //and drop any diagnostics the abandoned attempt recorded
+    let depth = opts.depth.get();
+    let diag_mark = opts.diagnostics.borrow().len();
+    let tr_attempt = parse_tr(cursor.fork(), opts);
+    opts.depth.set(depth);
+    let comp_attempt = if tr_attempt.is_err() { parse_component(cursor.fork(), opts) } else { Err(ParseError::expect_value(cursor.span())) };
+    opts.depth.set(depth);
+    if tr_attempt.is_err() && comp_attempt.is_err() {
+        opts.diagnostics.borrow_mut().truncate(diag_mark);
+    }
+    let (cursor,value) = if let Ok( (cursor, tr) ) = tr_attempt {
+        (cursor, tr)
+    } else if let Ok( (cursor, comp) ) = comp_attempt {
+        (cursor, Value::Component(comp))
+    } else if let Some( SplitCursor{next:cursor,result:block} ) = cursor.fork().consume_delimited_inner(Token::block_brace()) {
+        let map = parse_inner_map(block, opts)?;
+        (cursor, Value::Map( map ))
+    } else if let Some( SplitCursor{next:cursor,result:block} ) = cursor.fork().consume_delimited_inner( Token::block_bracket() ) {
+        let arr = parse_inner_array(block, opts)?;
+        (cursor, Value::Array( arr ))
+    }
+    else {
+        let span = cursor.span();
+        let (cursor,value) = cursor.consume_one();
+        let v = match value {
+            Token::Str(s) => parse_interpolated(s, span)?,
+            Token::Ident(s) => Value::Ident(s),
+            Token::Integer(v) => Value::Number(Number::I64(v)),
+            Token::Float(v) => Value::Number(Number::F64(v)),
+            //unit-suffixed numbers double as parameter values :
+            //`SizedBox(width=200px)`, `FlexSpace(8px)`
+            Token::Px(v) => Value::Unit(CssValue::Px(v)),
+            Token::Em(v) => Value::Unit(CssValue::Em(v)),
+            Token::Pt(v) => Value::Unit(CssValue::Pt(v)),
+            Token::Percent(v) => Value::Unit(CssValue::Percent(v)),
+            //color literals double as parameter values : `color=#ff8800`.
+            //`#xxx` lexes as an Id token; only hex-digit ones are colors
+            Token::Rgb(rgb) => Value::Color(CssValue::Rgb(rgb)),
+            Token::Rgba(rgba) => Value::Color(CssValue::Rgba(rgba)),
+            Token::Id(s) if s.len() >= 3 && s.bytes().all( |b| b.is_ascii_hexdigit() ) => Value::Color(CssValue::HexColor(s)),
+            Token::True => Value::Bool(true),
+            Token::False => Value::Bool(false),
+            Token::Relative(s) => parse_binding(s, span)?,
+            _ => return Err(ParseError::expect_value(span))
+        };
+        (cursor, v)
+    };
+    cursor.ok_with(value)
+}
+
+
+fn parse_inner_parameters<'a>(cursor:Cursor<'a>, opts:&ParseOptions) -> Result<Parameters<'a>> {
+    if cursor.is_eof() {
+        Ok(Parameters::Args(Vec::new()))
+    } else if let Ok( map ) = parse_inner_map(cursor.fork(), opts) {
+        Ok( Parameters::Map(map) )
+    } else if let Ok( arr ) = parse_inner_array(cursor.fork(), opts) {
+        //bare flag idents read as `name=true` (Parameters::get); a repeated
+        //flag is at best redundant and at worst a typo, so flag it
+        for (i, v) in arr.iter().enumerate() {
+            if let Value::Ident(flag) = v {
+                if arr[..i].iter().any( |p| matches!(p, Value::Ident(f) if f == flag) ) {
+                    opts.push_diagnostic(ParseError::duplicate_flag(cursor.span()));
+                }
+            }
+        }
+        Ok( Parameters::Args( arr ) )
+    } else if let Ok( map ) = parse_inner_mixed(cursor.fork(), opts) {
+        Ok( Parameters::Map(map) )
+    } else {
+        Err( ParseError::not_parameter( cursor.span() ) )
+    }
+}
+
+fn parse_component<'a>(cursor:Cursor<'a>, opts:&ParseOptions) -> CursorResult<'a, Component<'a>> {
+    let span = cursor.span();
+    opts.depth.set( opts.depth.get() + 1 );
+    let depth = opts.depth.get();
+    if let Some(max) = opts.max_depth {
+        if depth > max {
+            return Err(ParseError::depth_limit_exceeded(span, max));
+        }
+    }
+    let (cursor, Token::Ident(name)) = cursor.consume_one()
+    else { return Err(ParseError::expect_ident(span)) };
+
+    let Some( SplitCursor{next:cursor,result:param_block} ) = cursor.fork().consume_delimited_inner( Token::block_paren() )
+    else { return Err(ParseError::expect_parent_block(cursor.span())) };
+    let params = parse_inner_parameters(param_block, opts)?;
+
+    let span = cursor.span();
+    let (mut cursor,selectors) = cursor.consume_collect_until( |cursor| {
+        let (c, token) = cursor.fork().consume_one();
+        match token {
+            Token::Id(id) => Ok( (c,Some( ComponentIdent::Id(id) ) ) ),
+            Token::Class(cls) => Ok( (c,Some( ComponentIdent::Class(cls) ) ) ),
+            _ => Ok( (cursor,None) )
+        }
+    })?;
+
+    let mut id = None;
+    let mut classes = ArrayVec::<[&str;5]>::new();
+    for s in selectors.into_iter() {
+        match s {
+            ComponentIdent::Id(identify) => {
+                if id.is_some() {
+                    return Err(ParseError::id_already_defined(span))
+                }
+                id = Some(identify)
+            },
+            ComponentIdent::Class(cls) => {
+                classes.push(cls)
+            },
+            _ => unreachable!()
+        }
+    }
+    //classes.dedup();
+
+    let mut properties = HashMap::new();
+    let mut children = Vec::new();
+    if let Some( SplitCursor{next,result:mut comp_block} ) = cursor.fork().consume_delimited_inner(Token::block_brace()) {
+        cursor = next;
+        let mut guard_depth = 0;
+        while !comp_block.is_eof() {
+            let span = comp_block.span();
+            //optional `;` statement separator
+            if let (next, true) = comp_block.fork().ignore( [Token::Semicolon] ) {
+                comp_block = next;
+                continue;
+            }
+            //Platform guard around children/properties
+            if let Some(next) = consume_guard(comp_block.fork(), &mut guard_depth, opts)? {
+                comp_block = next;
+                continue;
+            }
+            //`if <cond> { .. } else { .. }` conditional block (see parse_if)
+            if let (_,[Token::Ident("if")]) = comp_block.fork().consume() {
+                let node;
+                (comp_block, node) = parse_if(comp_block, opts)?;
+                children.push(node);
+                continue;
+            }
+            //`for <var> in <array> { .. }` repeated block (see parse_for)
+            if let (_,[Token::Ident("for")]) = comp_block.fork().consume() {
+                let node;
+                (comp_block, node) = parse_for(comp_block, opts)?;
+                children.push(node);
+                continue;
+            }
+            //Try child component block
+            if let (_,[Token::Ident(key), Token::LParen]) = comp_block.fork().consume() {
+                match parse_component(comp_block.fork(), opts) {
+                    Ok( (next, child) ) => {
+                        comp_block = next;
+                        children.push( child );
+                        if let Some(max) = opts.max_children {
+                            if children.len() > max {
+                                return Err(ParseError::children_limit_exceeded(span, max));
+                            }
+                        }
+                    }
+                    Err(e) if opts.lenient && !e.is_limit() => {
+                        opts.push_diagnostic(e);
+                        //the failed child bumped the depth counter on the way in
+                        opts.depth.set( depth );
+                        comp_block = skip_statement(comp_block);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            //Try property
+            else if let (next,[Token::Ident(key), Token::Colon]) = comp_block.fork().consume() {
+                match parse_value(next, opts) {
+                    Ok( (next, value) ) => {
+                        //`key: Comp()` binds the component as the value, which
+                        //reads exactly like a child on the next line. A trailing
+                        //`;` states the intent; flag the bare form.
+                        let (next, terminated) = next.ignore( [Token::Semicolon] );
+                        if !terminated && matches!(value, Value::Component(_)) {
+                            opts.push_diagnostic(ParseError::ambiguous_component_value(span));
+                        }
+                        comp_block = next;
+                        properties.insert( key, value );
+                    }
+                    Err(e) if opts.lenient => {
+                        opts.push_diagnostic(e);
+                        comp_block = skip_statement(comp_block);
+                    }
+                    Err(e) => return Err(e),
+                }
+            } else if opts.lenient {
+                opts.push_diagnostic(ParseError::expect_brace_block(span));
+                comp_block = skip_statement(comp_block);
+            } else {
+                return Err(ParseError::expect_brace_block(span));
+            }
+        }
+    }
+
+    opts.depth.set( opts.depth.get() - 1 );
+    cursor.ok_with(Component {
+        name,
+        params,
+        id,
+        classes,
+        children,
+        properties,
+    })
+}
+
+// `if <cond> { .. } else { .. }` lowered to a synthetic `if` component : the
+// condition is the single positional parameter and an optional trailing
+// synthetic `else` child holds the other branch. The integration layer picks
+// the live branch against its parameter stack at build time. This makes `if`
+// and `else` reserved names inside component bodies.
+fn parse_if<'a>(cursor:Cursor<'a>, opts:&ParseOptions) -> CursorResult<'a, Component<'a>> {
+    let span = cursor.span();
+    let (cursor, Token::Ident("if")) = cursor.consume_one()
+    else { return Err(ParseError::expect_ident(span)) };
+    let (cursor, cond) = parse_value(cursor, opts)?;
+
+    let span = cursor.span();
+    let Some( SplitCursor{next:cursor, result:then_block} ) = cursor.consume_delimited_inner( Token::block_brace() )
+    else { return Err(ParseError::expect_brace_block(span)) };
+    let mut if_children = parse_branch_children(then_block, opts)?;
+
+    let (cursor, has_else) = cursor.ignore( [Token::Ident("else")] );
+    let cursor = if has_else {
+        let span = cursor.span();
+        let Some( SplitCursor{next:cursor, result:else_block} ) = cursor.consume_delimited_inner( Token::block_brace() )
+        else { return Err(ParseError::expect_brace_block(span)) };
+        if_children.push( Component {
+            name: "else",
+            params: Parameters::Args(Vec::new()),
+            id: None,
+            classes: ArrayVec::new(),
+            children: parse_branch_children(else_block, opts)?,
+            properties: HashMap::new(),
+        });
+        cursor
+    } else { cursor };
+
+    cursor.ok_with( Component {
+        name: "if",
+        params: Parameters::Args(vec![cond]),
+        id: None,
+        classes: ArrayVec::new(),
+        children: if_children,
+        properties: HashMap::new(),
+    })
+}
+
+// `for item in ${0.items} { .. }` lowered to a synthetic `for` component :
+// the loop variable (as an ident) and the source array are the two positional
+// parameters and the body becomes the children. The integration layer expands
+// the body once per element when the array is known (build time). Like `if`
+// and `else`, `for` is a reserved name inside component bodies.
+fn parse_for<'a>(cursor:Cursor<'a>, opts:&ParseOptions) -> CursorResult<'a, Component<'a>> {
+    let span = cursor.span();
+    let (cursor, [Token::Ident("for"), Token::Ident(var)]) = cursor.consume()
+    else { return Err(ParseError::expect_ident(span)) };
+    let span = cursor.span();
+    let (cursor, Token::Ident("in")) = cursor.consume_one()
+    else { return Err(ParseError::expect_ident(span)) };
+    let (cursor, source) = parse_value(cursor, opts)?;
+
+    let span = cursor.span();
+    let Some( SplitCursor{next:cursor, result:body_block} ) = cursor.consume_delimited_inner( Token::block_brace() )
+    else { return Err(ParseError::expect_brace_block(span)) };
+
+    cursor.ok_with( Component {
+        name: "for",
+        params: Parameters::Args(vec![Value::Ident(var), source]),
+        id: None,
+        classes: ArrayVec::new(),
+        children: parse_branch_children(body_block, opts)?,
+        properties: HashMap::new(),
+    })
+}
+
+// A conditional/loop body holds child components (and nested blocks) only.
+fn parse_branch_children<'a>(mut cursor:Cursor<'a>, opts:&ParseOptions) -> Result<Vec<Component<'a>>> {
+    let mut children = Vec::new();
+    while !cursor.is_eof() {
+        if let (next, true) = cursor.fork().ignore( [Token::Semicolon] ) {
+            cursor = next;
+            continue;
+        }
+        let child;
+        if let (_,[Token::Ident("if")]) = cursor.fork().consume() {
+            (cursor, child) = parse_if(cursor, opts)?;
+        } else if let (_,[Token::Ident("for")]) = cursor.fork().consume() {
+            (cursor, child) = parse_for(cursor, opts)?;
+        } else {
+            (cursor, child) = parse_component(cursor, opts)?;
+        }
+        children.push(child);
+    }
+    Ok(children)
+}
+
+// Lenient recovery : drop tokens up to the next thing that can start a body
+// statement (child call / property key) or the end of the block. The trimmed
+// stream carries no newlines, so statement heads are the boundary. Balanced
+// brace/paren blocks inside the bad statement are skipped whole so we never
+// resynchronize in the middle of a nested child.
+fn skip_statement(mut cursor:Cursor<'_>) -> Cursor<'_> {
+    if !cursor.is_eof() {
+        (cursor, _) = cursor.consume_one();
+    }
+    while !cursor.is_eof() {
+        if let (_,[Token::Ident(_), Token::LParen]) = cursor.fork().consume() {
+            break;
+        }
+        if let (_,[Token::Ident(_), Token::Colon]) = cursor.fork().consume() {
+            break;
+        }
+        if let Some( SplitCursor{next,..} ) = cursor.fork().consume_delimited_inner( Token::block_brace() ) {
+            cursor = next;
+            continue;
+        }
+        if let Some( SplitCursor{next,..} ) = cursor.fork().consume_delimited_inner( Token::block_paren() ) {
+            cursor = next;
+            continue;
+        }
+        (cursor, _) = cursor.consume_one();
+    }
+    cursor
+}
+
+// pub fn parse_tokens<'a>( tokens: &'a [Token<'a>] ) -> Result<(Vec<Style<'a>>,Vec<RootComponent<'a>>)> {
+//     let mut cursor = Cursor::new( tokens );
+//     let mut styles = vec![];
+//     let mut root_components = vec![];
+//
+//     while !cursor.is_eof() {
+//         if let (_, [Token::Ident(name), Token::Colon, Token::Ident(_), Token::LParen], ) = cursor.fork().consume() {
+//             let component;
+//             (cursor, component) = parse_component( cursor.fork().skip(2), opts )?;
+//             root_components.push(RootComponent{name, component});
+//             continue;
+//         }
+//
+//         let mut check_fork = cursor.fork();
+//         let is_style_item = take_match!(check_fork,
+//             [Token::Id(_)] => true,
+//             [Token::Class(_)] => true,
+//             [Token::Ident(_), Token::LParen] => false,
+//             [Token::Ident(_)] => true,
+//             _ => false
+//         );
+//         if is_style_item {
+//             let style;
+//             (cursor, style) = parse_style_item(cursor)?;
+//             styles.push(style);
+//             continue;
+//         }
+//
+//         //Error
+//         return Err(ParseError::unknown_start(cursor.span()));
+//     }
+//     Ok( (styles, root_components) )
+// }
+
+// Conditional section guards :
+//   `@platform(windows|macos|linux)` matched against the current target
+//   `@if-feature("name")` matched against `ParseOptions::features`
+//   `@debug` included only when `ParseOptions::debug_ui` is set
+// Returns the cursor just past the condition and whether it matched.
+fn parse_guard<'a>(cursor:Cursor<'a>, opts:&ParseOptions) -> Option<(Cursor<'a>, bool)> {
+    let (cursor, [Token::At, Token::Ident(kind)]) = cursor.fork().consume()
+    else { return None };
+    // `@debug { .. }` has no condition list
+    if kind == "debug" {
+        return Some( (cursor, opts.debug_ui) );
+    }
+    let SplitCursor{next:cursor, result:mut cond} = cursor.consume_delimited_inner( Token::block_paren() )?;
+    let mut matched = false;
+    match kind {
+        "platform" => {
+            while !cond.is_eof() {
+                let t;
+                (cond, t) = cond.consume_one();
+                if let Token::Ident(name) = t {
+                    matched |= name == std::env::consts::OS;
+                }
+            }
+        }
+        "if-feature" => {
+            while !cond.is_eof() {
+                let t;
+                (cond, t) = cond.consume_one();
+                if let Token::Str(name) = t {
+                    matched |= opts.features.contains(name);
+                }
+            }
+        }
+        _ => return None
+    }
+    Some( (cursor, matched) )
+}
+
+// Handle a guard at the cursor. On an opened (included) guard the caller must
+// count the pending closing brace via `guard_depth`.
+fn consume_guard<'a>(cursor:Cursor<'a>, guard_depth:&mut usize, opts:&ParseOptions) -> Result<Option<Cursor<'a>>> {
+    if *guard_depth > 0 {
+        if let (next, true) = cursor.fork().ignore( [Token::RBrace] ) {
+            *guard_depth -= 1;
+            return Ok( Some(next) );
+        }
+    }
+    if let Some( (next, include) ) = parse_guard(cursor.fork(), opts) {
+        let span = next.span();
+        return if include {
+            let (next, opened) = next.ignore( [Token::LBrace] );
+            if !opened {
+                return Err(ParseError::expect_brace_block(span));
+            }
+            *guard_depth += 1;
+            Ok( Some(next) )
+        } else {
+            let SplitCursor{next,..} = next.consume_delimited_inner( Token::block_brace() )
+                .ok_or_else(|| ParseError::expect_brace_block(span))?;
+            Ok( Some(next) )
+        }
+    }
+    Ok(None)
+}
+
+pub fn parse_tokens<'a>( tks:&'a TokenAndSpan<'a> ) -> Result<(Option<(u32,u32)>,Vec<Style<'a>>,Vec<RootComponent<'a>>,Vec<TimerDef<'a>>,Vec<ThemeDef<'a>>,Vec<MediaStyle<'a>>,Vec<&'a str>,Vec<(&'a str, Value<'a>)>)> {
+    parse_tokens_with(tks, &ParseOptions::default())
+}
+
+pub fn parse_tokens_with<'a>( tks:&'a TokenAndSpan<'a>, opts:&ParseOptions ) -> Result<(Option<(u32,u32)>,Vec<Style<'a>>,Vec<RootComponent<'a>>,Vec<TimerDef<'a>>,Vec<ThemeDef<'a>>,Vec<MediaStyle<'a>>,Vec<&'a str>,Vec<(&'a str, Value<'a>)>)> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::info_span!("skui_parse", tokens = tks.tokens.len()).entered();
+    let cut_off = tks.tokens.len();
+    let mut cursor = tks.start_cursor();
+    let mut styles = vec![];
+    let mut root_components = vec![];
+    let mut timers = vec![];
+    let mut themes: Vec<ThemeDef> = vec![];
+    let mut media_styles = vec![];
+    let mut imports = vec![];
+    let mut consts = vec![];
+    let mut version = None;
+    let mut guard_depth = 0;
+
+    if let (Some(max), Some(span)) = (opts.max_source_len, tks.spans.last()) {
+        if span.end > max {
+            return Err(ParseError::source_too_large(cursor.span(), max));
+        }
+    }
+    opts.depth.set(0);
+
+    while !cursor.is_eof() {
+        if let Some(next) = consume_guard(cursor.fork(), &mut guard_depth, opts)? {
+            cursor = next;
+            continue;
+        }
+        //`#skui 0.4` : grammar version pragma (lexes as an Id token). A newer
+        //declared version than this parser implements is reported up front as
+        //a compatibility error instead of failing later on unknown syntax.
+        //A bare `#skui { .. }` stays a style rule - the pragma needs a number.
+        if let (next, [Token::Id("skui"), Token::Float(_) | Token::Integer(_)]) = cursor.fork().consume() {
+            let span = cursor.span();
+            let (_, ver_token) = cursor.fork().skip(1).consume_one();
+            let declared = match ver_token {
+                Token::Float(_) => {
+                    //`major.minor` comes from the pragma's source text - the
+                    //lexed f64 drops trailing zeros, so `0.10` would read
+                    //back as minor 1 instead of minor 10
+                    let ver_idx = cursor.fork().skip(1).span().idx();
+                    let text = &tks.src[ tks.span(ver_idx) ];
+                    let mut it = text.splitn(2, '.');
+                    ( it.next().and_then( |s| s.parse().ok() ).unwrap_or(0),
+                      it.next().and_then( |s| s.parse().ok() ).unwrap_or(0) )
+                }
+                Token::Integer(v) => (v as u32, 0),
+                _ => unreachable!(),
+            };
+            if declared > GRAMMAR_VERSION {
+                let e = ParseError::unsupported_version(span, declared, GRAMMAR_VERSION);
+                if opts.lenient {
+                    opts.push_diagnostic(e);
+                } else {
+                    return Err(e);
+                }
+            }
+            version = Some(declared);
+            cursor = next;
+            continue;
+        }
+        //`@import "other.skui"` : recorded here, resolved by parse_with_resolver
+        if let (next, [Token::At, Token::Ident("import")]) = cursor.fork().consume() {
+            let span = next.span();
+            let (next, Token::Str(path)) = next.consume_one()
+            else { return Err(ParseError::expect_value(span)) };
+            imports.push(path);
+            cursor = next;
+            continue;
+        }
+        //@media (min-width: 600px) { .title { font-size: 14 } }
+        if let (next, [Token::At, Token::Ident("media")]) = cursor.fork().consume() {
+            let (next, query) = parse_media_query(next)?;
+            let span = next.span();
+            let Some( SplitCursor{next, result:block} ) = next.consume_delimited_inner( Token::block_brace() )
+            else { return Err(ParseError::expect_brace_block(span)) };
+            media_styles.push( MediaStyle { query, styles: parse_rule_block(block)? } );
+            cursor = next;
+            continue;
+        }
+        //`let spacing = 8` : document-scope constant, substituted after parse
+        if let (next, [Token::Ident("let"), Token::Ident(name), Token::Equal]) = cursor.fork().consume() {
+            match parse_value(next, opts) {
+                Ok( (next, value) ) => {
+                    consts.push((name, value));
+                    cursor = next;
+                }
+                Err(e) if opts.lenient && !e.is_limit() => {
+                    opts.push_diagnostic(e);
+                    cursor = skip_statement(cursor);
+                }
+                Err(e) => return Err(e),
+            }
+            continue;
+        }
+        //timers { tick: 1s  poll: 500ms }
+        if let (_, [Token::Ident("timers"), Token::LBrace]) = cursor.fork().consume() {
+            let (next, _) = cursor.fork().consume_one();
+            let Some( SplitCursor{next, result:mut block} ) = next.consume_delimited_inner( Token::block_brace() )
+            else { return Err(ParseError::expect_brace_block(cursor.span())) };
+            while !block.is_eof() {
+                let span = block.span();
+                let (b, [Token::Ident(name), Token::Colon]) = block.fork().consume()
+                else { return Err(ParseError::expect_kv(span)) };
+                let span = b.span();
+                let (b, number) = b.consume_one();
+                let value = match number {
+                    Token::Integer(v) => v as f64,
+                    Token::Float(v) => v,
+                    _ => return Err(ParseError::invalid_timer_interval(span)),
+                };
+                let (b, Token::Ident(unit)) = b.consume_one()
+                else { return Err(ParseError::invalid_timer_interval(span)) };
+                let millis = match unit {
+                    "ms" => value,
+                    "s" => value * 1000.0,
+                    "m" => value * 60_000.0,
+                    _ => return Err(ParseError::invalid_timer_interval(span)),
+                };
+                let (b, _) = b.ignore( [Token::Comma] );
+                block = b;
+                timers.push( TimerDef { name, interval: std::time::Duration::from_millis(millis as u64) } );
+            }
+            cursor = next;
+            continue;
+        }
+        //theme dark { Label { color: #eee }  .card { background: #222 } }
+        if let (_, [Token::Ident("theme"), Token::Ident(name), Token::LBrace]) = cursor.fork().consume() {
+            let next = cursor.fork().skip(2);
+            let Some( SplitCursor{next, result:block} ) = next.consume_delimited_inner( Token::block_brace() )
+            else { return Err(ParseError::expect_brace_block(cursor.span())) };
+            let mut rules = parse_rule_block(block)?;
+            match themes.iter_mut().find( |t| t.name == name ) {
+                Some(t) => t.styles.append(&mut rules),
+                None => themes.push( ThemeDef { name, styles: rules } ),
+            }
+            cursor = next;
+            continue;
+        }
+
+        //`FancyButton extends MyButton:` : derived definition reusing a parent
+        if let (_, [Token::Ident(name), Token::Ident("extends"), Token::Ident(parent), Token::Colon]) = cursor.fork().consume() {
+            match parse_component( cursor.fork().skip(4), opts ) {
+                Ok( (next, component) ) => {
+                    root_components.push(RootComponent{name, extends:Some(parent), component});
+                    cursor = next;
+                }
+                Err(e) if opts.lenient && !e.is_limit() => {
+                    opts.push_diagnostic(e);
+                    cursor = skip_statement( cursor.fork().skip(4) );
+                }
+                Err(e) => return Err(e),
+            }
+            continue;
+        }
+
+        //raw to trimmed for Component
+        if let (_, [Token::Ident(name), Token::Colon, Token::Ident(_), Token::LParen], ) = cursor.fork().consume() {
+            match parse_component( cursor.fork().skip(2), opts ) {
+                Ok( (next, component) ) => {
+                    root_components.push(RootComponent{name, extends:None, component});
+                    cursor = next;
+                }
+                Err(e) if opts.lenient && !e.is_limit() => {
+                    opts.push_diagnostic(e);
+                    cursor = skip_statement( cursor.fork().skip(2) );
+                }
+                Err(e) => return Err(e),
+            }
+            continue;
+        }
+
+        //trimmed to raw for selector
+        cursor = tks.raw_cursor(cursor);
+        let span = cursor.span();
+        let selector = match SelectorParser::parse(cursor.fork()) {
+            Ok( (next, selector) ) => {
+                cursor = next;
+                selector
+            }
+            Err(e) => {
+                let e = ParseError::not_selector(span, e);
+                if opts.lenient {
+                    opts.push_diagnostic(e);
+                    cursor = skip_statement( tks.trimmed_cursor(cursor) );
+                    continue;
+                }
+                return Err(e);
+            }
+        };
+
+        //trimmed to raw for style item
+        cursor = tks.trimmed_cursor(cursor);
+        match parse_style_item(selector, cursor.fork()) {
+            Ok( (next, mut parsed) ) => {
+                //syntax introduced in 0.4 : a document pinning an older
+                //grammar gets a version error, not silent acceptance
+                if version.is_some_and( |v| v < (0,4) )
+                    && parsed.iter().any( |s| s.has_important() || s.selector.has_attr() ) {
+                    let e = ParseError::unsupported_version(span, (0,4), version.unwrap());
+                    if opts.lenient {
+                        opts.push_diagnostic(e);
+                    } else {
+                        return Err(e);
+                    }
+                }
+                styles.append(&mut parsed);
+                cursor = tks.trimmed_cursor(next);
+            }
+            Err(e) if opts.lenient && !e.is_limit() => {
+                opts.push_diagnostic(e);
+                cursor = skip_statement(cursor);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(styles = styles.len(), components = root_components.len(), "parse complete");
+    Ok( (version, styles, root_components, timers, themes, media_styles, imports, consts) )
+}
+
+// Shared body of `theme` and `@media` blocks : a brace-delimited run of style
+// rules. The block arrives as a trimmed sub-slice, so whitespace in its
+// selectors is not significant - compound and combinator forms are available.
+fn parse_rule_block<'a>(mut block:Cursor<'a>) -> Result<Vec<Style<'a>>> {
+    let mut rules = vec![];
+    while !block.is_eof() {
+        block = block.ignore_until( |t| t != Token::Semicolon );
+        if block.is_eof() { break }
+        let span = block.span();
+        let sel;
+        (block, sel) = SelectorParser::parse(block)
+            .map_err( |e| ParseError::not_selector(span, e) )?;
+        let mut parsed;
+        (block, parsed) = parse_style_item(sel, block)?;
+        rules.append(&mut parsed);
+    }
+    Ok( rules )
+}
+
+// The `(feature: value)` conditions after `@media`, joined by `and`. Values
+// read as px whether or not they carry the unit.
+fn parse_media_query<'a>(mut cursor:Cursor<'a>) -> CursorResult<'a, MediaQuery> {
+    let mut query = MediaQuery::default();
+    loop {
+        let span = cursor.span();
+        let Some( SplitCursor{next, result:cond} ) = cursor.consume_delimited_inner( Token::block_paren() )
+        else { return Err(ParseError::invalid_media_query(span)) };
+        let (cond, [Token::Ident(key), Token::Colon]) = cond.consume()
+        else { return Err(ParseError::invalid_media_query(span)) };
+        let (_, value) = cond.consume_one();
+        let value = match value {
+            Token::Px(v) => v,
+            Token::Integer(v) => v as f64,
+            Token::Float(v) => v,
+            _ => return Err(ParseError::invalid_media_query(span)),
+        };
+        let slot = match key {
+            "min-width" => &mut query.min_width,
+            "max-width" => &mut query.max_width,
+            "min-height" => &mut query.min_height,
+            "max-height" => &mut query.max_height,
+            _ => return Err(ParseError::invalid_media_query(span)),
+        };
+        *slot = Some(value);
+        cursor = next;
+        let (next, t) = cursor.fork().consume_one();
+        if t != Token::Ident("and") { break }
+        cursor = next;
+    }
+    Ok( (cursor, query) )
+}
+
+#[derive(Debug,Clone)]
+pub struct SKUIParseError {
+    pub kind: ParseError,
+    pub span: Span,
+    // 1-based position of `span` plus the source line it sits on, captured at
+    // parse time so reporting needs no further access to the source text.
+    pub line: usize,
+    pub column: usize,
+    pub snippet: String,
+}
+
+impl SKUIParseError {
+    fn new(kind: ParseError, tks:&TokenAndSpan) -> Self {
+        let span = tks.span( kind.span.idx() );
+        let (line, column, snippet) = tks.line_col( span.start );
+        Self { kind, span, line, column, snippet: snippet.to_string() }
+    }
+}
+
+impl std::fmt::Display for SKUIParseError {
+    fn fmt(&self, f:&mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "error at line {}, column {} : {}", self.line, self.column, self.kind)?;
+        writeln!(f, "{:>4} | {}", self.line, self.snippet)?;
+        let remaining = self.snippet.chars().count().saturating_sub(self.column - 1).max(1);
+        let width = (self.span.end - self.span.start).clamp(1, remaining);
+        write!(f, "     | {}{}", " ".repeat(self.column - 1), "^".repeat(width))
+    }
+}
+
+impl std::error::Error for SKUIParseError {}
+
+// Which trivia tokens survive into the filtered (parser-facing) stream. The
+// raw stream always keeps everything - the selector parser, formatter and
+// highlighter read that one. The default drops both, which is what the
+// component/style parsers expect.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenStreamOptions {
+    pub keep_whitespace: bool,
+    pub keep_comments: bool,
+}
+
+pub struct TokenAndSpan<'a> {
+    cut_off: usize,
+    src: &'a str,
+
+    tokens: Vec<Token<'a>>,
+    idxs: Vec<usize>,
+
+    trimmed_tokens: Vec<Token<'a>>,
+    trimmed_idxs: Vec<usize>,
+
+    spans: Vec<Span>
+}
+
+impl <'a> TokenAndSpan<'a> {
+    pub fn new(src:&'a str) -> Self {
+        Self::new_with_options(src, TokenStreamOptions::default())
+    }
+
+    pub fn new_with_options(src:&'a str, stream_opts:TokenStreamOptions) -> Self {
+        let mut trimmed_tokens = Vec::new();
+        let mut trimmed_idxs = Vec::new();
+        let mut idxs = Vec::new();
+        let mut tokens = Vec::new();
+        let mut spans = Vec::new();
+
+        let mut tidx = 0;
+        for (idx,(token,span)) in Token::lexer(src)
+            .spanned()
+            .filter_map(| (t,s) | t.map( |v| (v,s) ).ok() )
+            .enumerate() {
+            idxs.push( tidx );
+            tokens.push(token);
+            spans.push(span);
+            let keep = match token {
+                Token::Whitespace => stream_opts.keep_whitespace,
+                Token::Comment => stream_opts.keep_comments,
+                _ => true,
+            };
+            if keep {
+                trimmed_tokens.push(token);
+                trimmed_idxs.push(idx);
+                tidx += 1;
+            }
+        }
+        //cut_off + 1로 하여 두 커서가 공존할 수 없게 함
+        Self {
+            cut_off:tokens.len() + 1, src, tokens, idxs, trimmed_tokens, trimmed_idxs, spans
+        }
+    }
+
+    pub fn start_cursor(&self) -> Cursor {
+        Cursor::new_offset( &self.trimmed_tokens[..], self.cut_off )
+    }
+
+    pub fn trimmed_cursor<'b>(&'b self, cursor:Cursor<'b>) -> Cursor<'b> {
+        if cursor.idx() < self.cut_off && !cursor.is_eof() { //this cursor raw cursor
+            let mirror_trimmed_idx = self.idxs[cursor.idx()];
+            Cursor::new_offset( &self.trimmed_tokens[ mirror_trimmed_idx .. ], mirror_trimmed_idx + self.cut_off )
+        } else {
+            cursor
+        }
+    }
+
+    pub fn raw_cursor<'b>(&'b self, cursor:Cursor<'b>) -> Cursor<'b> {
+        if cursor.idx() >= self.cut_off && !cursor.is_eof() { //this cursor is trimmed cursor
+            let slice_idx = cursor.idx() - self.cut_off;
+            let mirror_raw_idx = self.trimmed_idxs[slice_idx];
+            Cursor::new_offset( &self.tokens[ mirror_raw_idx .. ], mirror_raw_idx )
+        } else {
+            cursor
+        }
+    }
+
+    pub fn span(&self, idx:usize) -> Span {
+        let idx = if idx >= self.cut_off {
+            //trimmed-cursor position : map back to the raw index the span table uses
+            self.trimmed_idxs.get(idx - self.cut_off).copied()
+                .unwrap_or_else(|| self.tokens.len().saturating_sub(1))
+        } else {
+            idx
+        };
+        self.spans.get(idx).cloned().unwrap_or_default()
+    }
+
+    // 1-based line/column (column in chars) of a byte position plus the full
+    // source line it falls on.
+    pub fn line_col(&self, pos:usize) -> (usize, usize, &'a str) {
+        let pos = pos.min(self.src.len());
+        let line_start = self.src[..pos].rfind('\n').map( |i| i + 1 ).unwrap_or(0);
+        let line_end = self.src[line_start..].find('\n').map( |i| line_start + i ).unwrap_or(self.src.len());
+        let line = self.src[..line_start].matches('\n').count() + 1;
+        let column = self.src[line_start..pos].chars().count() + 1;
+        (line, column, &self.src[line_start..line_end])
+    }
+
+    pub fn render_error(&self, input:&str, idx:usize, context_lines:usize) -> String {
+        self.render_error_from_span(input, self.span(idx), context_lines)
+    }
+
+    pub fn render_error_from_span( &self, input: &str, span: Span, context_lines: usize) -> String {
+        #[derive(Debug)]
+        struct LineInfo {
+            line_no: usize,      // 1-based
             line_start: usize,   // byte index
             line_end: usize,     // byte index (newline 제외)
         }
 
-        fn find_line(input: &str, pos: usize) -> LineInfo {
-            let mut line_no = 1;
-            let mut last_nl = 0;
+        fn find_line(input: &str, pos: usize) -> LineInfo {
+            let mut line_no = 1;
+            let mut last_nl = 0;
+
+            for (i, c) in input.char_indices() {
+                if i >= pos {
+                    break;
+                }
+                if c == '\n' {
+                    line_no += 1;
+                    last_nl = i + 1;
+                }
+            }
+
+            let line_end = input[last_nl..]
+                .find('\n')
+                .map(|i| last_nl + i)
+                .unwrap_or(input.len());
+
+            LineInfo {
+                line_no,
+                line_start: last_nl,
+                line_end,
+            }
+        }
+
+        fn byte_to_column(line: &str, byte_offset: usize) -> usize {
+            line[..byte_offset].chars().count()
+        }
+
+        let line = find_line(input, span.start);
+
+        let mut out = String::new();
+
+        // 이전 라인 출력
+        let mut current_line_start = line.line_start;
+        let mut current_line_no = line.line_no;
+
+        for _ in 0..context_lines {
+            if current_line_start == 0 {
+                break;
+            }
+            let prev = find_line(input, current_line_start - 1);
+            out = format!(
+                "{:>4} | {}\n{}",
+                prev.line_no,
+                &input[prev.line_start..prev.line_end],
+                out
+            );
+            current_line_start = prev.line_start;
+            current_line_no = prev.line_no;
+        }
+
+        // 현재 라인
+        let line_text = &input[line.line_start..line.line_end];
+        out.push_str(&format!(
+            "{:>4} | {}\n",
+            line.line_no,
+            line_text
+        ));
+
+        let col_start =
+            byte_to_column(line_text, span.start - line.line_start);
+        let col_end =
+            byte_to_column(line_text, span.end.min(line.line_end) - line.line_start)
+                .max(col_start + 1);
+
+        // caret 라인
+        out.push_str("     | ");
+        out.push_str(&" ".repeat(col_start));
+        out.push_str(&"^".repeat(col_end - col_start));
+        out.push('\n');
+
+        out
+    }
+}
+
+
+fn parse<'a>(tks: &'a TokenAndSpan, opts:&ParseOptions) -> Result<SKUI<'a>, SKUIParseError> {
+
+    match parse_tokens_with( &tks, opts ) {
+        Ok( (version, mut styles, mut components, timers, mut themes, mut media_styles, imports, consts) ) => {
+            let vars = resolve_css_vars(&mut styles);
+            let consts = resolve_consts(consts);
+            substitute_consts(&mut styles, &mut components, &consts);
+            //theme and media rules see the document-scope vars and consts,
+            //not their own
+            for theme in themes.iter_mut() {
+                substitute_css_vars(&mut theme.styles, &vars);
+                substitute_consts(&mut theme.styles, &mut [], &consts);
+            }
+            for media in media_styles.iter_mut() {
+                substitute_css_vars(&mut media.styles, &vars);
+                substitute_consts(&mut media.styles, &mut [], &consts);
+            }
+            Ok( SKUI { version, styles, components, timers, themes, active_theme: None, media_styles, viewport: None, vars, imports, consts } )
+        },
+        Err(e) => Err( SKUIParseError::new(e, tks) ),
+    }
+}
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tks() {
+        let str = " abcd 1234 { } ";
+        let tks = TokenAndSpan::new( str );
+        println!("initial : {:?}", TokenCursor::new(&tks.tokens));
+
+        let mut cursor = tks.start_cursor();
+
+        println!("start : {:?}", cursor);
+
+        (cursor,_) = cursor.consume::<1>();
+
+        cursor = tks.raw_cursor( cursor );
+        println!("consume 1 and raw : {:?}", cursor);
+
+        (cursor,_) = cursor.consume::<2>();
+        cursor = tks.trimmed_cursor(cursor);
+        println!("consume 2 and trimmed : {:?}", cursor);
+
+        cursor = tks.raw_cursor(cursor );
+        println!("just raw : {:?}", cursor);
+    }
+
+    #[test]
+    fn test_parse() {
+        let input = r#"
+            Flex { background-color: black; padding:1px }
+            #list { border: 1px solid yellow }
+            .myBtn { border: 2px }
+            #myFlex { border:2px }
+            .background_white { background-color: WHITE }
+
+            Main:
+            Flex(MainFill) #myFlex .background_white {
+                myProperty1 : "data"
+                propertyMap : {key=1, key2=true}
+                propertyAnother : [ 1,2,3 ]
+                FlexItem(1.0, Button("FlexItem1"))
+                FlexItem(2.0, Button("FlexItem2"))
+                Button()
+                Flex() {
+                    Label("1") Label("2")
+                }
+            }
+
+
+            Custom:
+            CustomWidget() {
+                Flex() {
+                    FlexItem( 0.5, Button("OK") )
+                    FlexItem( 0.5, Button("Cancel") )
+                }
+            }
+        "#;
+
+        let tks = TokenAndSpan::new(input);
+        match SKUI::parse(&tks) {
+            Ok(parsed) => {
+                println!("Parsed successfully!");
+
+                for style in parsed.styles.iter() {
+                    println!("{:#?}", style);
+                }
+                for comp in parsed.components.iter() {
+                    println!("{:#?}", comp);
+                }
+            }
+            Err(e) => {
+                println!("Parse error: {:?}", e);
+                panic!("Cause : \n{}", tks.render_error_from_span(input, e.span, 2));
+            }
+        }
+    }
+
+    #[test]
+    fn platform_guard() {
+        let os = std::env::consts::OS;
+        let input = format!(r#"
+            @platform({os}) {{ .guarded {{ padding: 1px }} }}
+            @platform(neverland) {{ .excluded {{ padding: 2px }} }}
 
-            for (i, c) in input.char_indices() {
-                if i >= pos {
-                    break;
+            Main:
+            Flex() {{
+                @platform({os}|neverland) {{ Label("shown") }}
+                @platform(neverland) {{ Label("hidden") }}
+            }}
+        "#);
+        let tks = TokenAndSpan::new(&input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.styles.len(), 1 );
+        let main = &parsed.get_main_component().unwrap().component;
+        assert_eq!( main.children.len(), 1 );
+    }
+
+    #[test]
+    fn feature_guard() {
+        let input = r#"
+            Main:
+            Flex() {
+                @if-feature("experimental_panel") { Label("experimental") }
+                Label("stable")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.get_main_component().unwrap().component.children.len(), 1 );
+
+        let opts = ParseOptions::new().with_feature("experimental_panel");
+        let parsed = SKUI::parse_with_options(&tks, &opts).unwrap();
+        assert_eq!( parsed.get_main_component().unwrap().component.children.len(), 2 );
+    }
+
+    #[test]
+    fn tr_value() {
+        let input = r#"
+            Main:
+            Flex() {
+                Button(tr("menu.save"))
+                Label(text=tr("menu.load"))
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let main = &parsed.get_main_component().unwrap().component;
+        let button = &main.children[0];
+        assert_eq!( button.params.get(0,"text").unwrap().as_tr().unwrap().key, "menu.save" );
+        let label = &main.children[1];
+        assert_eq!( label.params.get(0,"text").unwrap().as_tr().unwrap().key, "menu.load" );
+    }
+
+    #[test]
+    fn timers_block() {
+        let input = r#"
+            timers {
+                tick: 1s
+                poll: 500ms,
+                cleanup: 2m
+            }
+
+            Main:
+            Flex() { Label("clock") }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.timers.len(), 3 );
+        assert_eq!( parsed.timers[0], TimerDef { name: "tick", interval: std::time::Duration::from_secs(1) } );
+        assert_eq!( parsed.timers[1].interval, std::time::Duration::from_millis(500) );
+        assert_eq!( parsed.timers[2].interval, std::time::Duration::from_secs(120) );
+    }
+
+    #[test]
+    fn parse_limits() {
+        let input = r#"
+            Main:
+            Flex() {
+                Flex() {
+                    Flex() {
+                        Label("deep")
+                    }
                 }
-                if c == '\n' {
-                    line_no += 1;
-                    last_nl = i + 1;
+                Label("a") Label("b") Label("c")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+
+        assert!( SKUI::parse_with_options(&tks, &ParseOptions::new().with_max_depth(10)).is_ok() );
+        let err = SKUI::parse_with_options(&tks, &ParseOptions::new().with_max_depth(2)).unwrap_err();
+        assert!( matches!(err.kind.kind, ParseErrorKind::DepthLimitExceeded(2)) );
+
+        let err = SKUI::parse_with_options(&tks, &ParseOptions::new().with_max_children(3)).unwrap_err();
+        assert!( matches!(err.kind.kind, ParseErrorKind::ChildrenLimitExceeded(3)) );
+
+        let err = SKUI::parse_with_options(&tks, &ParseOptions::new().with_max_source_len(16)).unwrap_err();
+        assert!( matches!(err.kind.kind, ParseErrorKind::SourceTooLarge(16)) );
+    }
+
+    #[test]
+    fn lenient_recovery() {
+        let input = r#"
+            Main:
+            Flex() {
+                Label("ok")
+                gap: =
+                Button("still here")
+                123 456
+                Button(=)
+                count: 3
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+
+        //strict mode aborts on the first bad statement
+        assert!( SKUI::parse(&tks).is_err() );
+
+        let opts = ParseOptions::new().with_lenient(true);
+        let parsed = SKUI::parse_with_options(&tks, &opts).unwrap();
+        let flex = &parsed.components[0].component;
+        assert_eq!( flex.children.len(), 2 );
+        assert_eq!( flex.children[1].params.get(0, "text").and_then(|v| v.as_str()), Some("still here") );
+        assert!( !flex.properties.contains_key("gap") );
+        assert_eq!( flex.properties.get("count").and_then(|v| v.as_i64()), Some(3) );
+
+        let diags = opts.take_diagnostics();
+        assert_eq!( diags.len(), 3 );
+        assert!( diags[0].to_string().contains("expected a value") );
+    }
+
+    #[test]
+    fn parse_recovering_collects_errors() {
+        let input = r#"
+            .ok { color: #f00 }
+            .bad { 123 }
+            Main:
+            Flex() {
+                Label("kept")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+
+        //strict mode aborts on the bad rule
+        assert!( SKUI::parse(&tks).is_err() );
+
+        let (parsed, errors) = SKUI::parse_recovering(&tks);
+
+        //the bad rule is reported instead of aborting
+        assert_eq!( errors.len(), 1 );
+        assert!( matches!(errors[0].kind.kind, ParseErrorKind::ExpectIdent) );
+
+        //everything around it parsed cleanly
+        assert_eq!( parsed.styles.len(), 1 );
+        let main = parsed.get_main_component().unwrap();
+        assert_eq!( main.component.children.len(), 1 );
+    }
+
+    #[test]
+    fn reparse_reports_changed_ids() {
+        let src1 = r#"
+            Card:
+            Label("card") #card_label
+            Main:
+            Flex() {
+                Card()
+                Label("t") #title
+            }
+        "#;
+        let src2 = r#"
+            Card:
+            Label("CARD!") #card_label
+            Main:
+            Flex() {
+                Card()
+                Label("t") #title
+            }
+        "#;
+        let tks1 = TokenAndSpan::new(src1);
+        let tks2 = TokenAndSpan::new(src2);
+        let mut skui = SKUI::parse(&tks1).unwrap();
+
+        //only the edited definition is reported, and the document is swapped
+        let changed = skui.reparse(&tks2, 0..src2.len()).unwrap();
+        assert_eq!( changed, vec!["card_label"] );
+        let label = skui.find_by_id("card_label").unwrap();
+        assert_eq!( label.params.get(0, "text").and_then( |v| v.as_str() ), Some("CARD!") );
+
+        //identical source : nothing to rebuild
+        let tks3 = TokenAndSpan::new(src2);
+        assert!( skui.reparse(&tks3, 0..src2.len()).unwrap().is_empty() );
+
+        //a style edit can affect anything, so every definition goes dirty
+        let src4 = format!("Label {{ color: #f00 }}\n{src2}");
+        let tks4 = TokenAndSpan::new(&src4);
+        let changed = skui.reparse(&tks4, 0..22).unwrap();
+        assert_eq!( changed, vec!["card_label", "title"] );
+    }
+
+    #[test]
+    fn parse_error_position() {
+        let input = "Main:\nFlex() {\n    gap: =\n}\n";
+        let tks = TokenAndSpan::new(input);
+        let err = SKUI::parse(&tks).unwrap_err();
+
+        //position and line captured at parse time
+        assert_eq!( err.line, 3 );
+        assert_eq!( err.snippet, "    gap: =" );
+
+        //the Display impl is the full caret diagnostic
+        let rendered = err.to_string();
+        println!("{rendered}");
+        assert!( rendered.contains("line 3") );
+        assert!( rendered.contains("    gap: =") );
+        assert!( rendered.contains('^') );
+    }
+
+    #[test]
+    fn token_stream_options() {
+        let src = "Label(\"a\") //note\n";
+        fn collect<'a>(tks:&'a TokenAndSpan<'a>) -> Vec<Token<'a>> {
+            let mut kinds = Vec::new();
+            let mut c = tks.start_cursor();
+            while !c.is_eof() {
+                let t;
+                (c, t) = c.consume_one();
+                kinds.push(t);
+            }
+            kinds
+        }
+
+        //default : trivia filtered out of the parser-facing stream
+        let default = TokenAndSpan::new(src);
+        assert!( !collect(&default).iter().any( |t| matches!(t, Token::Whitespace | Token::Comment) ) );
+
+        //tooling can keep comments (or whitespace) without a second tokenizer
+        let kept = TokenAndSpan::new_with_options(src, TokenStreamOptions { keep_comments: true, keep_whitespace: false });
+        let kinds = collect(&kept);
+        assert!( kinds.contains(&Token::Comment) );
+        assert!( !kinds.contains(&Token::Whitespace) );
+
+        let raw = TokenAndSpan::new_with_options(src, TokenStreamOptions { keep_comments: true, keep_whitespace: true });
+        let kinds = collect(&raw);
+        assert!( kinds.contains(&Token::Comment) );
+        assert!( kinds.contains(&Token::Whitespace) );
+    }
+
+    #[test]
+    fn calc_values() {
+        let input = r#"
+            .panel { width: calc(100% - 20px); padding: calc(2 * 4px) }
+            Main:
+            Label("x")
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let props = &parsed.styles[0].properties;
+        assert_eq!( props[0].values[0], CssValue::Calc("100% - 20px") );
+
+        //mixed units resolve against the parent dimension at layout time
+        assert_eq!( props[0].values[0].resolve(300.0), Some(280.0) );
+        assert_eq!( props[1].values[0].resolve(0.0), Some(8.0) );
+        assert_eq!( CssValue::Percent(50.0).resolve(200.0), Some(100.0) );
+
+        //precedence, division and malformed input
+        assert_eq!( CssValue::Calc("10px + 50% / 2").resolve(100.0), Some(35.0) );
+        assert_eq!( CssValue::Calc("100% / 0").resolve(100.0), None );
+        assert_eq!( CssValue::Calc("10px 20px").resolve(100.0), None );
+        assert_eq!( CssValue::Calc("30em").resolve(100.0), None );
+    }
+
+    #[test]
+    fn boolean_flags() {
+        let input = r#"
+            Main:
+            Flex() {
+                TextArea(editable)
+                Checkbox("Done", checked)
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+        assert_eq!( flex.children[0].params.get(0, "editable").and_then(|v| v.as_bool()), Some(true) );
+        assert_eq!( flex.children[1].params.get(1, "checked").and_then(|v| v.as_bool()), Some(true) );
+        assert_eq!( flex.children[1].params.get(0, "text").and_then(|v| v.as_str()), Some("Done") );
+
+        //a repeated flag is flagged but still parses
+        let input = r#"
+            Main:
+            TextArea(editable, editable)
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let opts = ParseOptions::new();
+        assert!( SKUI::parse_with_options(&tks, &opts).is_ok() );
+        let diags = opts.take_diagnostics();
+        assert_eq!( diags.len(), 1 );
+        assert!( diags[0].to_string().contains("duplicate") );
+    }
+
+    #[test]
+    fn unit_parameters() {
+        let input = r#"
+            Main:
+            Flex() {
+                SizedBox(width=200px, height=50%) { Label("x") }
+                FlexSpace(8px)
+                Label("y", size=1.5em)
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+        assert_eq!( flex.children[0].params.get(0, "width").and_then(|v| v.as_unit()), Some(&CssValue::Px(200.0)) );
+        assert_eq!( flex.children[0].params.get(1, "height").and_then(|v| v.as_unit()), Some(&CssValue::Percent(50.0)) );
+        assert_eq!( flex.children[1].params.get(0, "value").and_then(|v| v.as_unit()), Some(&CssValue::Px(8.0)) );
+        assert_eq!( flex.children[2].params.get(1, "size").and_then(|v| v.as_unit()), Some(&CssValue::Em(1.5)) );
+
+        //units stay distinct from plain numbers
+        assert!( flex.children[1].params.get(0, "value").unwrap().as_f64().is_none() );
+    }
+
+    #[test]
+    fn stylesheet_selector_errors() {
+        //dangling combinator : a selector error, not an odd match
+        let tks = TokenAndSpan::new(".panel > { color: #fff }");
+        let err = SKUI::parse(&tks).unwrap_err();
+        assert!( matches!(err.kind.kind, ParseErrorKind::InvalidCssSelector(_)) );
+
+        //unknown pseudo-class
+        let tks = TokenAndSpan::new("Button:hovering { color: #fff }");
+        let err = SKUI::parse(&tks).unwrap_err();
+        assert!( matches!(err.kind.kind, ParseErrorKind::InvalidCssSelector(_)) );
+
+        //a bad rule after valid content still surfaces the selector error
+        let tks = TokenAndSpan::new(".ok { color: #fff }\n.bad > > Label { color: #000 }\nMain:\nLabel(\"x\")");
+        assert!( SKUI::parse(&tks).is_err() );
+
+        //complex but well-formed selectors keep parsing
+        let tks = TokenAndSpan::new("#root > Flex .item:hover, Button.primary { color: #fff }\nMain:\nLabel(\"x\")");
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.styles.len(), 1 );
+        assert!( matches!(parsed.styles[0].selector, Selector::Group(_)) );
+    }
+
+    #[test]
+    fn escapes_and_multiline_strings() {
+        let input = "Main:\nFlex() {\n    Label(\"line\\nbreak\")\n    Prose(`multi\n\"line\"\ncontent`)\n}\n";
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+
+        //the AST keeps the source slice; decoding happens at read time
+        let Some(Value::String(raw)) = flex.children[0].params.get(0, "")
+        else { panic!("expected a string value") };
+        assert_eq!( *raw, "line\\nbreak" );
+        assert_eq!( unescape(raw), "line\nbreak" );
+        assert!( matches!(unescape("plain"), std::borrow::Cow::Borrowed(_)) );
+
+        //backtick strings admit raw quotes and newlines
+        let prose = flex.children[1].params.get(0, "").unwrap();
+        assert_eq!( prose.as_text().as_deref(), Some("multi\n\"line\"\ncontent") );
+
+        //`\${` suppresses interpolation and decodes to a literal `${`
+        let tks = TokenAndSpan::new("Main:\nLabel(\"costs \\${0}\")");
+        let parsed = SKUI::parse(&tks).unwrap();
+        let v = parsed.components[0].component.params.get(0, "").unwrap();
+        assert_eq!( v.as_text().as_deref(), Some("costs ${0}") );
+
+        //multi-line content survives the emitter round-trip
+        let src = SKUI::parse(&TokenAndSpan::new(input)).unwrap().to_source();
+        assert!( src.contains("`multi\n\"line\"\ncontent`") );
+    }
+
+    #[test]
+    fn string_interpolation() {
+        let input = r#"
+            Main:
+            Label("Hello, ${0.user}!")
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let label = &parsed.components[0].component;
+        let Some(Value::Interpolated(segments)) = label.params.get(0, "text")
+        else { panic!("expected an interpolated value") };
+        assert_eq!( segments.len(), 3 );
+        assert!( matches!(segments[0], Value::String("Hello, ")) );
+        assert!( matches!(&segments[1], Value::Relative(_)) );
+        assert!( matches!(segments[2], Value::String("!")) );
+
+        //plain strings stay plain and the emitter reassembles the quoted form
+        let src = parsed.to_source();
+        assert!( src.contains(r#""Hello, ${0.user}!""#) );
+        let tks = TokenAndSpan::new("Main:\nLabel(\"plain\")");
+        let plain = SKUI::parse(&tks).unwrap();
+        assert!( matches!(plain.components[0].component.params.get(0, ""), Some(Value::String("plain"))) );
+
+        //an unterminated binding is a parse error
+        let tks = TokenAndSpan::new("Main:\nLabel(\"bad ${0.user\")");
+        assert!( SKUI::parse(&tks).is_err() );
+    }
+
+    #[test]
+    fn color_parameters() {
+        let input = r#"
+            Main:
+            Flex() {
+                Icon("save.svg", color=#ff8800)
+                Canvas(fill=rgb(1,2,3), stroke=rgba(4,5,6,255))
+                Label("x", color=red)
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+        let icon = &flex.children[0];
+        //mixed positional + named lists lower to an index-keyed map
+        assert_eq!( icon.params.get(0, "path").and_then(|v| v.as_str()), Some("save.svg") );
+        assert_eq!( icon.params.get(1, "color").and_then(|v| v.as_color()), Some(&CssValue::HexColor("ff8800")) );
+        let canvas = &flex.children[1];
+        assert_eq!( canvas.params.get(0, "fill").and_then(|v| v.as_color()), Some(&CssValue::Rgb((1,2,3))) );
+        assert_eq!( canvas.params.get(1, "stroke").and_then(|v| v.as_color()), Some(&CssValue::Rgba((4,5,6,255))) );
+        //named colors stay idents; the integration's color parser decides
+        assert_eq!( flex.children[2].params.get(1, "color").and_then(|v| v.as_str()), Some("red") );
+
+        //mixed parameters survive a source round-trip
+        let src = parsed.to_source();
+        let tks2 = TokenAndSpan::new(&src);
+        let reparsed = SKUI::parse(&tks2).unwrap();
+        let icon = &reparsed.components[0].component.children[0];
+        assert_eq!( icon.params.get(0, "path").and_then(|v| v.as_str()), Some("save.svg") );
+        assert_eq!( icon.params.get(1, "color").and_then(|v| v.as_color()), Some(&CssValue::HexColor("ff8800")) );
+    }
+
+    #[test]
+    fn loop_blocks() {
+        let input = r#"
+            Main:
+            Flex() {
+                for item in ${0.items} {
+                    Label(${item.name})
                 }
+                Label("tail")
             }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+        let node = &flex.children[0];
+        assert_eq!( node.name, "for" );
+        assert_eq!( node.params.get(0, "").and_then(|v| v.as_str()), Some("item") );
+        assert!( matches!(node.params.get(1, ""), Some(Value::Relative(_))) );
+        assert_eq!( node.children.len(), 1 );
+        assert_eq!( node.children[0].name, "Label" );
+        assert_eq!( flex.children[1].name, "Label" );
+
+        //round-trips through the emitter
+        let src = parsed.to_source();
+        assert!( src.contains("for item in ${0.items}") );
+        let tks2 = TokenAndSpan::new(&src);
+        assert!( SKUI::parse(&tks2).is_ok() );
+    }
 
-            let line_end = input[last_nl..]
-                .find('\n')
-                .map(|i| last_nl + i)
-                .unwrap_or(input.len());
+    #[test]
+    fn extends_definitions() {
+        let input = r#"
+            MyButton:
+            Button(${label}, kind="plain")
 
-            LineInfo {
-                line_no,
-                line_start: last_nl,
-                line_end,
-            }
-        }
+            FancyButton extends MyButton:
+            Button(${label}, kind=${kind}) .fancy
 
-        fn byte_to_column(line: &str, byte_offset: usize) -> usize {
-            line[..byte_offset].chars().count()
-        }
+            Main:
+            FancyButton(label="go")
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.components.len(), 3 );
+        assert_eq!( parsed.get_root_component("MyButton").unwrap().extends, None );
+        assert_eq!( parsed.get_root_component("FancyButton").unwrap().extends, Some("MyButton") );
+
+        //resolution order : the definition itself, then its ancestors
+        let chain: Vec<&str> = parsed.extends_chain("FancyButton").iter().map( |rc| rc.name ).collect();
+        assert_eq!( chain, ["FancyButton", "MyButton"] );
+        //an unknown parent just ends the chain
+        assert_eq!( parsed.extends_chain("MyButton").len(), 1 );
+
+        //the header survives a source round-trip
+        let src = parsed.to_source();
+        assert!( src.contains("FancyButton extends MyButton:") );
+        assert!( SKUI::parse(&TokenAndSpan::new(&src)).is_ok() );
+
+        //a cycle terminates instead of looping
+        let cyc = "A extends B:\nLabel(\"a\")\n\nB extends A:\nLabel(\"b\")\n\nMain:\nA()";
+        let tks = TokenAndSpan::new(cyc);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let chain: Vec<&str> = parsed.extends_chain("A").iter().map( |rc| rc.name ).collect();
+        assert_eq!( chain, ["A", "B"] );
+    }
 
-        let line = find_line(input, span.start);
+    #[test]
+    fn pseudo_state_provider() {
+        use crate::selector::PseudoState;
+        let input = r#"
+            Button { color: #00f }
+            Button:hover { color: #f00 }
+            Main:
+            Flex() {
+                Button("ok") #save
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+        let button = &flex.children[0];
+        let parents: Vec<&Component> = vec![flex];
+
+        //build-time matching collects pseudo rules unconditionally
+        assert_eq!( parsed.get_styles(&parents, button).count(), 2 );
+
+        //live re-resolution matches `:hover` only while the provider says so
+        let mut states = std::collections::HashMap::<String, PseudoState>::new();
+        assert_eq!( parsed.get_styles_with_state(&parents, button, &states).count(), 1 );
+        states.insert("save".to_string(), PseudoState { hovered:true, ..Default::default() });
+        assert_eq!( parsed.get_styles_with_state(&parents, button, &states).count(), 2 );
+    }
 
-        let mut out = String::new();
+    #[test]
+    fn cascade_order() {
+        //document order : tag rules last, but `.title` is more specific and
+        //`#head` beats both; only the `!important` declaration beats the id
+        //rule - the normal `color` sharing its rule must not ride along
+        let input = r#"
+            .title { color: #00f }
+            #head { color: #0f0 }
+            Label { color: #f00 }
+            Label { font-size: 12 !important; color: #aaa }
+            Main:
+            Flex() {
+                Label("x") #head .title
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+        let label = &flex.children[0];
+        let parents: Vec<&Component> = vec![flex];
+
+        let order: Vec<String> = parsed.get_styles(&parents, label)
+            .map( |s| s.to_string() )
+            .collect();
+        //weakest first, so sequential application ends on the strongest;
+        //the mixed rule splits into a normal and an important emission
+        assert_eq!( order, [
+            "Label { color: #f00 }",
+            "Label { color: #aaa }",
+            ".title { color: #00f }",
+            "#head { color: #0f0 }",
+            "Label { font-size: 12 !important }",
+        ]);
+
+        //per declaration : `font-size` wins as important, `color` still
+        //resolves against the id rule
+        let resolved = parsed.styles_for("head");
+        let by_key = |key:&str| resolved.iter().find( |d| d.key == key ).unwrap();
+        assert_eq!( by_key("color").selector, "#head" );
+        assert!( by_key("font-size").important );
+
+        //`!important` is recorded on the declaration and round-trips
+        let important = parsed.styles.iter().find( |s| s.has_important() ).unwrap();
+        assert!( important.properties[0].important );
+        assert_eq!( important.to_string(), "Label { font-size: 12 !important; color: #aaa }" );
+        assert!( SKUI::parse(&TokenAndSpan::new(&parsed.to_source())).is_ok() );
+    }
 
-        // 이전 라인 출력
-        let mut current_line_start = line.line_start;
-        let mut current_line_no = line.line_no;
+    #[test]
+    fn nested_style_rules() {
+        let input = r#"
+            .card {
+                padding: 4px;
+                .title { font-size: 18 }
+                Button:hover { color: #fff }
+            }
+            Main:
+            Label("x")
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+
+        //nested rules flatten to descendants of the enclosing selector, in
+        //source order after the enclosing rule's own declarations
+        assert_eq!( parsed.styles.len(), 3 );
+        assert_eq!( parsed.styles[0].to_string(), ".card { padding: 4px }" );
+        assert_eq!( parsed.styles[1].selector.to_css_string(), ".card .title" );
+        assert_eq!( parsed.styles[2].selector.to_css_string(), ".card Button:hover" );
+
+        //a rule holding only nested rules leaves no empty entry behind
+        let tks = TokenAndSpan::new("#root { .a { padding: 1px; .b { margin: 1px } } }\nMain:\nLabel(\"y\")");
+        let parsed = SKUI::parse(&tks).unwrap();
+        let selectors: Vec<String> = parsed.styles.iter().map( |s| s.selector.to_css_string() ).collect();
+        assert_eq!( selectors, ["#root .a", "#root .a .b"] );
+    }
 
-        for _ in 0..context_lines {
-            if current_line_start == 0 {
-                break;
+    #[test]
+    fn styles_for_introspection() {
+        let input = r#"
+            Label { color: #111111; padding: 2px }
+            .title { color: #ff0000 }
+            #big.title { font-size: 24 }
+            Label:hover { color: #00ff00 }
+            Main:
+            Flex() {
+                Label("hello") #big .title
             }
-            let prev = find_line(input, current_line_start - 1);
-            out = format!(
-                "{:>4} | {}\n{}",
-                prev.line_no,
-                &input[prev.line_start..prev.line_end],
-                out
-            );
-            current_line_start = prev.line_start;
-            current_line_no = prev.line_no;
-        }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+
+        let resolved = parsed.styles_for("#big");
+        let by_key = |key:&str| resolved.iter().find( |d| d.key == key ).unwrap();
+
+        //each key resolves to the winning declaration, with its provenance
+        assert_eq!( resolved.len(), 3 );
+        let color = by_key("color");
+        assert_eq!( color.selector, ".title" );
+        assert_eq!( color.rule_index, 1 );
+        assert_eq!( color.values.as_slice(), &[CssValue::HexColor("ff0000")] );
+        assert_eq!( by_key("padding").selector, "Label" );
+        assert_eq!( by_key("font-size").selector, "#big.title" );
+
+        //default pseudo state : the `:hover` rule does not contribute,
+        //and an unknown id resolves to nothing
+        assert!( resolved.iter().all( |d| !d.important ) );
+        assert!( parsed.styles_for("#missing").is_empty() );
+
+        //the leading `#` is optional in the query
+        assert_eq!( parsed.styles_for("big"), resolved );
+    }
 
-        // 현재 라인
-        let line_text = &input[line.line_start..line.line_end];
-        out.push_str(&format!(
-            "{:>4} | {}\n",
-            line.line_no,
-            line_text
-        ));
+    #[test]
+    fn theme_blocks() {
+        let input = r#"
+            Label { color: #111111 }
+            theme dark {
+                Label { color: #eeeeee }
+                .card { background: #222222 }
+            }
+            theme dark { .accent { color: #ff8800 } }
+            Main:
+            Label("x") #msg
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let mut parsed = SKUI::parse(&tks).unwrap();
+
+        //theme rules stay out of the base cascade; repeated blocks append
+        assert_eq!( parsed.styles.len(), 1 );
+        assert_eq!( parsed.themes.len(), 1 );
+        assert_eq!( parsed.themes[0].name, "dark" );
+        assert_eq!( parsed.themes[0].styles.len(), 3 );
+
+        //the themed rule set layers after the base rules, unknown names do not
+        let dark = parsed.styles_for_theme("dark");
+        assert_eq!( dark.len(), 4 );
+        assert_eq!( dark[1].to_string(), "Label { color: #eeeeee }" );
+        assert_eq!( parsed.styles_for_theme("light").len(), 1 );
+
+        //runtime switch : same document, no reparse, the matchers see the
+        //theme's rules win ties against the base rule
+        assert_eq!( parsed.styles_for("#msg")[0].values.as_slice(), &[CssValue::HexColor("111111")] );
+        parsed.set_active_theme(Some("dark"));
+        let resolved = parsed.styles_for("#msg");
+        assert_eq!( resolved[0].values.as_slice(), &[CssValue::HexColor("eeeeee")] );
+        assert_eq!( resolved[0].rule_index, 1 );
+        parsed.set_active_theme(None);
+        assert_eq!( parsed.styles_for("#msg")[0].values.as_slice(), &[CssValue::HexColor("111111")] );
+
+        //theme blocks survive the emitter
+        assert!( parsed.to_source().contains("theme dark {") );
+    }
 
-        let col_start =
-            byte_to_column(line_text, span.start - line.line_start);
-        let col_end =
-            byte_to_column(line_text, span.end.min(line.line_end) - line.line_start)
-                .max(col_start + 1);
+    #[test]
+    fn media_queries() {
+        let input = r#"
+            .title { font-size: 20 }
+            @media (max-width: 600px) {
+                .title { font-size: 14 }
+            }
+            @media (min-width: 900px) and (min-height: 600) {
+                .title { font-size: 28 }
+            }
+            Main:
+            Label("t") #t .title
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let mut parsed = SKUI::parse(&tks).unwrap();
+
+        //media groups stay out of the base cascade; `and` combines features,
+        //the px unit is optional
+        assert_eq!( parsed.styles.len(), 1 );
+        assert_eq!( parsed.media_styles.len(), 2 );
+        assert_eq!( parsed.media_styles[0].query, MediaQuery { max_width: Some(600.0), ..Default::default() } );
+        assert_eq!( parsed.media_styles[1].query.min_width, Some(900.0) );
+        assert_eq!( parsed.media_styles[1].query.min_height, Some(600.0) );
+
+        //the effective rule set per window size
+        assert_eq!( parsed.get_styles_for_viewport(500.0, 400.0).len(), 2 );
+        assert_eq!( parsed.get_styles_for_viewport(700.0, 400.0).len(), 1 );
+        assert_eq!( parsed.get_styles_for_viewport(1000.0, 700.0).len(), 2 );
+
+        //without a viewport the matchers resolve base rules only; with one,
+        //the satisfied group wins the cascade
+        assert_eq!( parsed.styles_for("#t")[0].values.as_slice(), &[CssValue::Number(20.0)] );
+        parsed.set_viewport(Some((500.0, 400.0)));
+        let resolved = parsed.styles_for("#t");
+        assert_eq!( resolved[0].values.as_slice(), &[CssValue::Number(14.0)] );
+        assert_eq!( resolved[0].rule_index, 1 );
+        parsed.set_viewport(Some((1000.0, 700.0)));
+        assert_eq!( parsed.styles_for("#t")[0].values.as_slice(), &[CssValue::Number(28.0)] );
+
+        //media blocks survive the emitter
+        assert!( parsed.to_source().contains("@media (max-width: 600px) {") );
+
+        //an unknown feature is reported up front
+        let tks = TokenAndSpan::new("@media (foo: 1) { }\nMain:\nLabel(\"x\")");
+        let err = SKUI::parse(&tks).unwrap_err();
+        assert!( matches!( err.kind.kind(), ParseErrorKind::InvalidMediaQuery ) );
+    }
 
-        // caret 라인
-        out.push_str("     | ");
-        out.push_str(&" ".repeat(col_start));
-        out.push_str(&"^".repeat(col_end - col_start));
-        out.push('\n');
+    #[test]
+    fn version_pragma() {
+        //declared version is recorded and survives to_source
+        let tks = TokenAndSpan::new("#skui 0.4\nMain:\nLabel(\"x\")");
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.version, Some((0,4)) );
+        assert!( parsed.to_source().starts_with("#skui 0.4\n") );
+
+        //a newer grammar than this parser implements is a compatibility
+        //error, not a cryptic parse failure further down
+        let tks = TokenAndSpan::new("#skui 9.1\nMain:\nLabel(\"x\")");
+        let err = SKUI::parse(&tks).unwrap_err();
+        assert!( matches!( err.kind.kind(), ParseErrorKind::UnsupportedVersion { required:(9,1), .. } ) );
+        assert!( err.kind.to_string().contains("requires skui >= 9.1") );
+
+        //the minor comes from the source text, so a trailing zero does not
+        //collapse - `0.10` declares future minor ten, not 0.1
+        let tks = TokenAndSpan::new("#skui 0.10\nMain:\nLabel(\"x\")");
+        let err = SKUI::parse(&tks).unwrap_err();
+        assert!( matches!( err.kind.kind(), ParseErrorKind::UnsupportedVersion { required:(0,10), .. } ) );
+
+        //0.4 syntax under an older pinned grammar is refused the same way
+        let tks = TokenAndSpan::new("#skui 0.3\nLabel { color: #f00 !important }\nMain:\nLabel(\"x\")");
+        let err = SKUI::parse(&tks).unwrap_err();
+        assert!( matches!( err.kind.kind(), ParseErrorKind::UnsupportedVersion { required:(0,4), .. } ) );
+
+        //an id named `skui` still reads as a style rule, not a pragma
+        let tks = TokenAndSpan::new("#skui { color: #0f0 }\nMain:\nLabel(\"x\") #skui");
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.version, None );
+        assert_eq!( parsed.styles.len(), 1 );
+    }
 
-        out
+    #[test]
+    fn relative_fallback_values() {
+        let input = r#"
+            Main:
+            Flex() {
+                Label(${0.title:-"Untitled"})
+                Badge(count=${0.count:-0}, active=${0.active:-false})
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+        let Some(Value::RelativeOr(vkeys, fallback)) = flex.children[0].params.get(0, "")
+        else { panic!("expected a fallback binding") };
+        assert_eq!( vkeys.as_slice(), &[ValueKey::Index(0), ValueKey::Name("title")] );
+        assert!( matches!(**fallback, Value::String("Untitled")) );
+        assert!( matches!(flex.children[1].params.get(0, "count"), Some(Value::RelativeOr(_, f)) if matches!(**f, Value::Number(Number::I64(0)))) );
+        assert!( matches!(flex.children[1].params.get(1, "active"), Some(Value::RelativeOr(_, f)) if matches!(**f, Value::Bool(false))) );
+
+        //round-trips through the emitter
+        let src = parsed.to_source();
+        assert!( src.contains(r#"${0.title:-"Untitled"}"#) );
+        assert!( SKUI::parse(&TokenAndSpan::new(&src)).is_ok() );
+
+        //an empty fallback is a parse error
+        let tks = TokenAndSpan::new("Main:\nLabel(${0.x:-})");
+        assert!( SKUI::parse(&tks).is_err() );
     }
-}
 
+    #[test]
+    fn let_constants() {
+        let input = r#"
+            let spacing = 8
+            let accent = #ff8800
+            let alias = accent
 
-fn parse<'a>(tks: &'a TokenAndSpan) -> Result<SKUI<'a>, SKUIParseError> {
+            .panel { padding: spacing; color: alias }
 
-    match parse_tokens( &tks ) {
-        Ok( (styles, components) ) => Ok( SKUI { styles, components } ),
-        Err(e) => {
-            Err( SKUIParseError {
-                span : tks.span( e.span.idx() ).clone(),
-                kind : e,
-            })
-        },
-    }
-}
+            Main:
+            Flex(gap=spacing) {
+                Label("x", color=accent)
+                Label("y", axis=horizontal)
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.consts.len(), 3 );
 
+        //substituted into style values, through the alias
+        let props = &parsed.styles[0].properties;
+        assert_eq!( props[0].values[0], CssValue::Number(8.0) );
+        assert_eq!( props[1].values[0], CssValue::HexColor("ff8800") );
 
+        //substituted into component parameters
+        let flex = &parsed.components[0].component;
+        assert_eq!( flex.params.get(0, "gap").and_then(|v| v.as_i64()), Some(8) );
+        assert_eq!( flex.children[0].params.get(1, "color").and_then(|v| v.as_color()), Some(&CssValue::HexColor("ff8800")) );
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        //idents that name no constant stay idents (enum-like values)
+        assert_eq!( flex.children[1].params.get(1, "axis").and_then(|v| v.as_str()), Some("horizontal") );
+    }
 
     #[test]
-    fn tks() {
-        let str = " abcd 1234 { } ";
-        let tks = TokenAndSpan::new( str );
-        println!("initial : {:?}", TokenCursor::new(&tks.tokens));
+    fn conditional_blocks() {
+        let input = r#"
+            Main:
+            Flex() {
+                if ${show_advanced} {
+                    Flex() { Label("advanced") }
+                } else {
+                    Label("basic")
+                }
+                Label("tail")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let flex = &parsed.components[0].component;
+        assert_eq!( flex.children.len(), 2 );
+
+        let node = &flex.children[0];
+        assert_eq!( node.name, "if" );
+        assert!( matches!(node.params.get(0, ""), Some(Value::Relative(_))) );
+        //then-branch child plus the trailing synthetic else node
+        assert_eq!( node.children.len(), 2 );
+        assert_eq!( node.children[1].name, "else" );
+        assert_eq!( node.children[1].children[0].name, "Label" );
+
+        //the surface form survives source emission
+        let src = parsed.to_source();
+        assert!( src.contains("if ${show_advanced} {") );
+        assert!( src.contains("} else {") );
+        let tks2 = TokenAndSpan::new(&src);
+        assert!( SKUI::parse(&tks2).is_ok() );
+    }
 
-        let mut cursor = tks.start_cursor();
+    #[test]
+    fn whitespace_variants() {
+        //tabs and CRLF endings lex into the same trimmed stream as spaces/LF;
+        //regression guard for the parser-facing whitespace filtering
+        let input = "\t.pad\t{ padding:\t4px }\r\nMain:\r\n\tFlex(axis=vertical)\t{\r\n\t\tLabel(\"a\")\r\n\t\tgap:\t2\r\n\t}\r\n";
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.styles.len(), 1 );
+        let flex = &parsed.components[0].component;
+        assert_eq!( flex.children.len(), 1 );
+        assert_eq!( flex.properties.get("gap").and_then(|v| v.as_i64()), Some(2) );
+
+        //same document with spaces/LF parses to the same shape
+        let lf = input.replace("\r\n", "\n").replace('\t', " ");
+        let tks_lf = TokenAndSpan::new(&lf);
+        let parsed_lf = SKUI::parse(&tks_lf).unwrap();
+        assert_eq!( parsed_lf.stats().component_count, parsed.stats().component_count );
+        assert_eq!( parsed_lf.stats().style_declaration_count, parsed.stats().style_declaration_count );
+    }
 
-        println!("start : {:?}", cursor);
+    #[test]
+    fn statement_separators() {
+        //`;` is an optional statement separator inside component bodies, so the
+        //value/child split does not hinge on line breaks
+        let input = r#"
+            Main:
+            Flex() {
+                text: "a"; Label("b");
+                gap: 3
+                slot: Label("inline");
+                Label("tail")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let opts = ParseOptions::new();
+        let parsed = SKUI::parse_with_options(&tks, &opts).unwrap();
+        let flex = &parsed.components[0].component;
+
+        assert_eq!( flex.children.len(), 2 );
+        assert_eq!( flex.properties.get("text").and_then(|v| v.as_str()), Some("a") );
+        assert_eq!( flex.properties.get("gap").and_then(|v| v.as_i64()), Some(3) );
+        assert!( matches!(flex.properties.get("slot"), Some(Value::Component(c)) if c.name == "Label") );
+        //every component-valued property above is `;` terminated
+        assert!( opts.take_diagnostics().is_empty() );
+
+        //the bare form parses the same but gets flagged
+        let input = r#"
+            Main:
+            Flex() {
+                slot: Label("inline")
+                Label("tail")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let opts = ParseOptions::new();
+        let parsed = SKUI::parse_with_options(&tks, &opts).unwrap();
+        let flex = &parsed.components[0].component;
+        assert_eq!( flex.children.len(), 1 );
+        assert!( matches!(flex.properties.get("slot"), Some(Value::Component(_))) );
+        let diags = opts.take_diagnostics();
+        assert_eq!( diags.len(), 1 );
+        assert!( diags[0].to_string().contains("';'") );
+    }
 
-        (cursor,_) = cursor.consume::<1>();
+    #[test]
+    fn doc_stats() {
+        let input = r#"
+            .myclass { background-color: black; padding:1px }
+            Main:
+            Flex() {
+                Label("hello")
+                Label("world")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let stats = parsed.stats();
+        println!("{:?}", stats);
+        assert_eq!( stats.style_rule_count, 1 );
+        assert_eq!( stats.style_declaration_count, 2 );
+        assert_eq!( stats.component_count, 3 ); //Flex + 2 Label
+        assert!( stats.string_bytes > 0 );
+        assert!( stats.estimated_heap_bytes > 0 );
+    }
 
-        cursor = tks.raw_cursor( cursor );
-        println!("consume 1 and raw : {:?}", cursor);
+    #[test]
+    fn find_and_query() {
+        let input = r#"
+            Main:
+            Flex() #root {
+                Button("ok").primary #submit
+                Flex() {
+                    Button("no").primary
+                    Label("deep").primary
+                }
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+
+        assert_eq!( parsed.find_by_id("submit").map(|c| c.name), Some("Button") );
+        assert!( parsed.find_by_id("nope").is_none() );
+        assert_eq!( parsed.find_all_by_class("primary").len(), 3 );
+        assert_eq!( parsed.find_all_by_name("Button").len(), 2 );
+
+        //combinators behave exactly like stylesheet matching
+        assert_eq!( parsed.query("Button.primary").unwrap().len(), 2 );
+        assert_eq!( parsed.query("#root > Button").unwrap().len(), 1 );
+        assert_eq!( parsed.query("Flex Label").unwrap().len(), 1 );
+        assert!( parsed.query("{").is_err() );
+    }
 
-        (cursor,_) = cursor.consume::<2>();
-        cursor = tks.trimmed_cursor(cursor);
-        println!("consume 2 and trimmed : {:?}", cursor);
+    #[test]
+    fn comments() {
+        let input = r#"
+            // stylesheet section
+            .title { color: #ff0000 } /* trailing */
+            Main:
+            Flex() { // row container
+                /* multi
+                   line */
+                Label("hello").title
+                Label("star edge") /* tricky **/
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        assert_eq!( parsed.styles.len(), 1 );
+        assert_eq!( parsed.find_all_by_name("Label").len(), 2 );
 
-        cursor = tks.raw_cursor(cursor );
-        println!("just raw : {:?}", cursor);
+        //comments survive in the raw stream for tooling
+        let classes = crate::highlight::classify(input);
+        assert!( classes.iter().any( |(_,c)| *c == crate::highlight::TokenClass::Comment ) );
     }
 
     #[test]
-    fn test_parse() {
+    fn css_variables() {
         let input = r#"
-            Flex { background-color: black; padding:1px }
-            #list { border: 1px solid yellow }
-            .myBtn { border: 2px }
-            #myFlex { border:2px }
-            .background_white { background-color: WHITE }
-
+            .theme { --primary: #3377ff; --pad: 4px; --accent: var(--primary) }
+            Button { background-color: var(--primary); padding: var(--pad) }
+            Label { color: var(--accent) }
+            .broken { color: var(--missing) }
             Main:
-            Flex(MainFill) #myFlex .background_white {
-                myProperty1 : "data"
-                propertyMap : {key=1, key2=true}
-                propertyAnother : [ 1,2,3 ]
-                FlexItem(1.0, Button("FlexItem1"))
-                FlexItem(2.0, Button("FlexItem2"))
-                Button()
-                Flex() {
-                    Label("1") Label("2")
-                }
+            Flex() {
+                Button("ok")
             }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+
+        //declarations move out of the rule into the document table
+        assert!( parsed.styles[0].properties.is_empty() );
+        assert_eq!( parsed.vars.get("--pad").map( |v| v[0] ), Some(CssValue::Px(4.0)) );
+
+        //references (including the chained --accent) come out substituted
+        assert_eq!( parsed.styles[1].properties[0].values[0], CssValue::HexColor("3377ff") );
+        assert_eq!( parsed.styles[1].properties[1].values[0], CssValue::Px(4.0) );
+        assert_eq!( parsed.styles[2].properties[0].values[0], CssValue::HexColor("3377ff") );
+
+        //an unresolvable reference drops the whole declaration
+        assert!( parsed.styles[3].properties.is_empty() );
+        assert!( parsed.styles.iter()
+            .flat_map( |s| s.properties.iter() )
+            .all( |p| !p.values.iter().any( |v| matches!(v, CssValue::Var(_)) ) ) );
+    }
 
+    #[test]
+    fn merge_overrides() {
+        let base = r#"
+            .brand { color: #111111 }
+            Main:
+            Flex() {
+                Label("Acme") #logo .brand
+                Button("ok")
+            }
+            Footer:
+            Label("footer")
+        "#;
+        let overrides = r#"
+            .brand { color: #ff8800 }
+            Logo:
+            Label("Globex") #logo
+            Extra:
+            Label("extra")
+        "#;
+        let base_tks = TokenAndSpan::new(base);
+        let base = SKUI::parse(&base_tks).unwrap();
+        let over_tks = TokenAndSpan::new(overrides);
+        let overrides = SKUI::parse(&over_tks).unwrap();
+
+        let merged = SKUI::merge(&base, &overrides);
+        //override styles appended after the base ones
+        assert_eq!( merged.styles.len(), 2 );
+        //#logo subtree replaced in place, no new `Logo` root appended
+        let logo = merged.find_by_id("logo").unwrap();
+        assert_eq!( logo.params.get(0, "").and_then(|v| v.as_str()), Some("Globex") );
+        assert!( merged.get_root_component("Logo").is_none() );
+        //unmatched override root appended, base roots kept
+        assert!( merged.get_root_component("Extra").is_some() );
+        assert!( merged.get_root_component("Footer").is_some() );
+        assert_eq!( merged.components.len(), 3 );
+    }
 
-            Custom:
-            CustomWidget() {
-                Flex() {
-                    FlexItem( 0.5, Button("OK") )
-                    FlexItem( 0.5, Button("Cancel") )
-                }
+    #[test]
+    fn component_builder() {
+        let built = Component::builder("Flex")
+            .named_param("axis", Value::Ident("horizontal"))
+            .id("toolbar")
+            .class("wide")
+            .class("wide") //deduplicated like add_class
+            .property("gap", Value::Number(Number::I64(8)))
+            .child( Component::builder("Label").param(Value::String("hi")) )
+            .child( Component::builder("Button").param(Value::String("ok")).class("primary") )
+            .build();
+
+        //the same tree written as source parses to an equal component
+        let src = r#"
+            Main:
+            Flex(axis=horizontal) #toolbar .wide {
+                gap: 8
+                Label("hi")
+                Button("ok") .primary
             }
         "#;
+        let tks = TokenAndSpan::new(src);
+        let skui = SKUI::parse(&tks).unwrap();
+        assert_eq!( built, skui.components[0].component );
+
+        //positional after named has no source form and is refused
+        let mixed = Component::builder("Label")
+            .named_param("text", Value::String("x"))
+            .param(Value::Bool(true))
+            .build();
+        assert_eq!( mixed.params.get(0, "text"), Some(&Value::String("x")) );
+        assert_eq!( mixed.params.get(1, ""), None );
+    }
 
+    #[test]
+    fn visitor_walk() {
+        let input = r#"
+            .title { font-size: 20 }
+            Button .title { color: #fff }
+            Main:
+            Flex() {
+                Label("top") #header
+                Item( Label("nested") #inner )
+                Button(text=${action:-"Go"})
+            }
+        "#;
         let tks = TokenAndSpan::new(input);
-        match SKUI::parse(&tks) {
-            Ok(parsed) => {
-                println!("Parsed successfully!");
+        let skui = SKUI::parse(&tks).unwrap();
 
-                for style in parsed.styles.iter() {
-                    println!("{:#?}", style);
-                }
-                for comp in parsed.components.iter() {
-                    println!("{:#?}", comp);
+        #[derive(Default)]
+        struct Collector<'b> {
+            ids: Vec<&'b str>,
+            style_rules: usize,
+            strings: Vec<String>,
+        }
+        impl <'a,'b> Visit<'a,'b> for Collector<'b> {
+            fn visit_component(&mut self, c:&'b Component<'a>) {
+                if let Some(id) = c.id {
+                    self.ids.push(id);
                 }
             }
-            Err(e) => {
-                println!("Parse error: {:?}", e);
-                panic!("Cause : \n{}", tks.render_error_from_span(input, e.span, 2));
+            fn visit_style(&mut self, _style:&'b Style<'a>) {
+                self.style_rules += 1;
+            }
+            fn visit_value(&mut self, value:&'b Value<'a>) {
+                if let Value::String(s) = value {
+                    self.strings.push(s.to_string());
+                }
             }
         }
+
+        let mut collector = Collector::default();
+        skui.walk(&mut collector);
+        assert_eq!( collector.style_rules, 2 );
+        //`#inner` lives in a `Component` parameter value, not in `children`
+        assert_eq!( collector.ids, ["header", "inner"] );
+        //string literals from params, nested components and `${..:-fallback}`s
+        assert!( collector.strings.contains(&"top".to_string()) );
+        assert!( collector.strings.contains(&"nested".to_string()) );
+        assert!( collector.strings.contains(&"Go".to_string()) );
     }
 
     #[test]
     fn narr() {
         let token = vec![ Token::Ident("MainFill") ];
         let cursor = Cursor::new(&token);
-        println!("{:?}", parse_inner_array(cursor).unwrap());
+        println!("{:?}", parse_inner_array(cursor, &ParseOptions::default()).unwrap());
     }
 
     #[test]
     fn style_item() {
-        let input = r#".myclass { background-color: black; padding:1px }"#;
+        let input = r#".myclass { background-color: black; padding:1px; .inner { color: red } }"#;
         let tks = TokenAndSpan::new(input);
         let cursor = tks.start_cursor();
 
@@ -869,4 +3948,43 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let input = r#"
+            let gap = 8
+            .title { font-size: 20; color: #ff8800 }
+            Main:
+            Flex(Vertical) {
+                Label("Hello") #greeting .title
+                Button("Ok") { action: "ok" }
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let skui = SKUI::parse(&tks).unwrap();
+
+        let json = serde_json::to_string(&skui).unwrap();
+        //zero-copy : the deserialized document borrows from `json`
+        let back: SKUI = serde_json::from_str(&json).unwrap();
+
+        assert_eq!( back.styles, skui.styles );
+        assert_eq!( back.components, skui.components );
+        assert_eq!( back.consts, skui.consts );
+    }
+
+    #[test]
+    fn value_roundtrip() {
+        let value = Value::Map( HashMap::from([
+            ("label", Value::String("Save")),
+            ("count", Value::Number(Number::I64(3))),
+            ("width", Value::Unit(CssValue::Px(200.0))),
+        ]) );
+        let json = serde_json::to_string(&value).unwrap();
+        let back: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!( back, value );
+    }
+}
@@ -2,18 +2,42 @@ use std::collections::HashMap;
 use tinyvec::ArrayVec;
 use crate::{Value, ValueKey};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Parameters<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Map(HashMap<&'a str,Value<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Args(Vec<Value<'a>>),
 }
 
+// Mixed parameter lists (`Icon("save.svg", color=#ff8800)`) lower to a Map
+// with the positional entries stored under their index. `get` falls back to
+// the index key, so arg structs read both forms the same way. Idents can't
+// start with a digit, so these keys never collide with user keys.
+pub(crate) const POSITIONAL_KEYS: [&str; 10] = ["0","1","2","3","4","5","6","7","8","9"];
+
 impl <'a> Parameters<'a> {
     pub fn empty() -> Self { Parameters::Args( Vec::new() ) }
     pub fn get(&self, idx:usize, key:&'a str) -> Option<&Value> {
+        //a bare flag ident matching the requested key reads as `key=true`, so
+        //`TextArea(editable)` needs no `editable=true` noise
+        const FLAG: &Value<'static> = &Value::Bool(true);
         match self {
-            Parameters::Map(map) => map.get(key),
-            Parameters::Args(list) => list.get(idx),
+            Parameters::Map(map) => map.get(key)
+                //mixed form : the positional slot is keyed by its index
+                .or_else( || POSITIONAL_KEYS.get(idx).and_then( |k| map.get(k) ) )
+                .or_else( || map.iter()
+                    .any( |(k,v)| k.as_bytes()[0].is_ascii_digit() && matches!(v, Value::Ident(f) if *f == key) )
+                    .then_some(FLAG) ),
+            Parameters::Args(list) => match list.get(idx) {
+                Some(Value::Ident(flag)) if *flag == key => Some(FLAG),
+                v @ Some(_) => v,
+                //flags are position independent : scan the rest of the list
+                None => list.iter()
+                    .any( |v| matches!(v, Value::Ident(flag) if *flag == key) )
+                    .then_some(FLAG),
+            },
         }
     }
     pub fn get_as_rk(&self, key: &'a [ValueKey]) -> Option<&Value> {
@@ -21,9 +45,11 @@ impl <'a> Parameters<'a> {
         let first = &key[0];
         let find = match first {
             ValueKey::Index(idx) => {
-                if let Parameters::Args(list) = self {
-                    list.get(*idx)
-                } else { None }
+                match self {
+                    Parameters::Args(list) => list.get(*idx),
+                    //mixed form keeps positional entries under index keys
+                    Parameters::Map(map) => POSITIONAL_KEYS.get(*idx).and_then( |k| map.get(k) ),
+                }
             }
             ValueKey::Name(name) => {
                 if let Parameters::Map(map) = self {
@@ -90,4 +116,16 @@ mod test {
         let vkey = ValueKey::vec_from_str("0.key").unwrap();
         println!("0.key : {:?}", params.get_as_rk(vkey.as_slice()).unwrap());
     }
+
+    #[test]
+    fn flag_idents() {
+        let params = Parameters::Args( vec![Value::String("Done"), Value::Ident("checked")] );
+        //flag at its positional slot and out of position
+        assert_eq!( params.get(1, "checked").and_then( |v| v.as_bool() ), Some(true) );
+        assert_eq!( params.get(5, "checked").and_then( |v| v.as_bool() ), Some(true) );
+        //non-flag positional reads behave as before
+        assert_eq!( params.get(0, "text").and_then( |v| v.as_str() ), Some("Done") );
+        assert!( matches!(params.get(1, "editable"), Some(Value::Ident("checked"))) );
+        assert!( params.get(5, "editable").is_none() );
+    }
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use thiserror::Error;
+use crate::{walk_components_mut, Component, Parameters, RootComponent, Value, ValueKey, SKUI};
+
+// Refactoring passes over the parsed AST. They mutate the document in place;
+// serialize with `to_source()` afterwards to write the result back out.
+
+// Generated parameter slot names. The AST borrows every string from the source
+// text, so hoisted parameters draw from this static pool instead of allocating.
+const PARAM_NAMES: [&str; 10] = ["p0","p1","p2","p3","p4","p5","p6","p7","p8","p9"];
+
+#[derive(Debug, Error)]
+pub enum RefactorError {
+    #[error("invalid node path : {0:?}")]
+    InvalidPath(Vec<usize>),
+    #[error("root component `{0}` already exists")]
+    NameInUse(String),
+}
+
+// What `rename` operates on. Component renames cover the root definition,
+// every invocation site and tag selectors; ids/classes cover component
+// attribute lists and every stylesheet selector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symbol<'a> {
+    Class(&'a str),
+    Id(&'a str),
+    Component(&'a str),
+}
+
+// Rename a symbol everywhere it appears and return the edited source text.
+// The document is mutated in place, so follow-up refactorings compose.
+pub fn rename<'a>(doc:&mut SKUI<'a>, symbol:Symbol<'_>, new:&'a str) -> String {
+    match symbol {
+        Symbol::Class(old) => { doc.rename_class(old, new); }
+        Symbol::Id(old) => {
+            for style in doc.styles.iter_mut() {
+                style.selector.rename_id(old, new);
+            }
+            for rc in doc.components.iter_mut() {
+                walk_components_mut(&mut rc.component, &mut |c| {
+                    if c.id == Some(old) { c.id = Some(new); }
+                });
+            }
+        }
+        Symbol::Component(old) => {
+            for style in doc.styles.iter_mut() {
+                style.selector.rename_tag(old, new);
+            }
+            for rc in doc.components.iter_mut() {
+                if rc.name == old { rc.name = new; }
+                walk_components_mut(&mut rc.component, &mut |c| {
+                    if c.name == old { c.name = new; }
+                });
+            }
+        }
+    }
+    doc.to_source()
+}
+
+// Lift the subtree at `node_path` into a new root component `new_name` and
+// replace the original site with a call to it. String literals inside the
+// subtree are hoisted into named parameters (`p0`, `p1`, ..) so further call
+// sites can pass their own values; identical literals share one slot.
+//
+// `node_path` addresses the subtree : first index picks the root component,
+// the rest walk `children` (e.g. `[0, 1, 0]` = first child of second child
+// of the first root).
+pub fn extract_component<'a>(doc:&mut SKUI<'a>, node_path:&[usize], new_name:&'a str) -> Result<(), RefactorError> {
+    if doc.get_root_component(new_name).is_some() {
+        return Err(RefactorError::NameInUse(new_name.to_string()));
+    }
+    let Some((&root_idx, rest)) = node_path.split_first()
+    else { return Err(RefactorError::InvalidPath(node_path.to_vec())) };
+    let Some(rc) = doc.components.get_mut(root_idx)
+    else { return Err(RefactorError::InvalidPath(node_path.to_vec())) };
+
+    let mut target: &mut Component<'a> = &mut rc.component;
+    for &idx in rest {
+        target = match target.children.get_mut(idx) {
+            Some(child) => child,
+            None => return Err(RefactorError::InvalidPath(node_path.to_vec())),
+        };
+    }
+
+    //swap the subtree out, leave the call site in its place
+    let call_site = Component {
+        name: new_name,
+        params: Parameters::empty(),
+        id: None,
+        classes: Default::default(),
+        children: vec![],
+        properties: Default::default(),
+    };
+    let mut extracted = std::mem::replace(target, call_site);
+
+    let mut hoisted: Vec<(&'a str, Value<'a>)> = Vec::new();
+    hoist_literals(&mut extracted, &mut hoisted);
+    if !hoisted.is_empty() {
+        let map: HashMap<&'a str, Value<'a>> = hoisted.into_iter().collect();
+        target.params = Parameters::Map(map);
+    }
+
+    doc.components.push( RootComponent { name: new_name, extends: None, component: extracted } );
+    Ok(())
+}
+
+fn hoist_literals<'a>(c:&mut Component<'a>, hoisted:&mut Vec<(&'a str, Value<'a>)>) {
+    match &mut c.params {
+        Parameters::Map(map) => {
+            for v in map.values_mut() {
+                hoist_value(v, hoisted);
+            }
+        }
+        Parameters::Args(args) => {
+            for v in args.iter_mut() {
+                hoist_value(v, hoisted);
+            }
+        }
+    }
+    c.children.iter_mut().for_each( |child| hoist_literals(child, hoisted) );
+}
+
+fn hoist_value<'a>(v:&mut Value<'a>, hoisted:&mut Vec<(&'a str, Value<'a>)>) {
+    let Value::String(s) = *v else { return };
+    let existing = hoisted.iter()
+        .find( |(_,hv)| matches!(hv, Value::String(h) if *h == s) )
+        .map( |(name,_)| *name );
+    let name = match existing {
+        Some(name) => name,
+        None => {
+            //slot pool exhausted : leave the literal inline
+            let Some(&name) = PARAM_NAMES.get(hoisted.len()) else { return };
+            hoisted.push( (name, v.clone()) );
+            name
+        }
+    };
+    *v = Value::Relative( vec![ ValueKey::Name(name) ] );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenAndSpan;
+
+    #[test]
+    fn extract() {
+        let input = r#"
+            Main:
+            Flex() {
+                Flex() .card {
+                    Label("title")
+                    Button("ok")
+                    Button("ok")
+                }
+                Label("other")
+            }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let mut skui = SKUI::parse(&tks).unwrap();
+
+        extract_component(&mut skui, &[0, 0], "Card").unwrap();
+
+        //new root holds the subtree with literals turned into bindings
+        let card = skui.get_root_component("Card").unwrap();
+        assert!( card.component.classes.contains(&"card") );
+        assert_eq!( card.component.children.len(), 3 );
+        assert!( matches!( card.component.children[0].params.get(0, ""), Some(Value::Relative(_)) ) );
+
+        //the original site is now a parameterized call; identical "ok" shares a slot
+        let main = skui.get_main_component().unwrap();
+        let call = &main.component.children[0];
+        assert_eq!( call.name, "Card" );
+        let Parameters::Map(map) = &call.params else { panic!("expected named params") };
+        assert_eq!( map.len(), 2 );
+        assert!( map.values().any( |v| v.as_str() == Some("title") ) );
+        assert!( map.values().any( |v| v.as_str() == Some("ok") ) );
+
+        //round-trips through the formatter
+        let src = skui.to_source();
+        println!("{src}");
+        let tks2 = TokenAndSpan::new(&src);
+        let reparsed = SKUI::parse(&tks2).unwrap();
+        assert!( reparsed.get_root_component("Card").is_some() );
+    }
+
+    #[test]
+    fn rename_symbols() {
+        let input = r#"
+            .accent { color: #f00 }
+            Card .accent { padding: 2px }
+            #header { margin: 1px }
+            Main:
+            Flex() {
+                Card() #header .accent
+                Card()
+            }
+            Card:
+            Label("card")
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let mut skui = SKUI::parse(&tks).unwrap();
+
+        let src = rename(&mut skui, Symbol::Class("accent"), "hot");
+        assert!( !src.contains(".accent") );
+        assert_eq!( src.matches(".hot").count(), 3 );
+
+        let src = rename(&mut skui, Symbol::Id("header"), "top");
+        assert!( !src.contains("#header") );
+        assert_eq!( src.matches("#top").count(), 2 );
+
+        let src = rename(&mut skui, Symbol::Component("Card"), "Panel");
+        println!("{src}");
+        assert!( !src.contains("Card") );
+        assert_eq!( src.matches("Panel").count(), 4 ); //root def, tag selector, 2 calls
+
+        //result still parses and resolves
+        let tks2 = TokenAndSpan::new(&src);
+        let reparsed = SKUI::parse(&tks2).unwrap();
+        assert!( reparsed.get_root_component("Panel").is_some() );
+        assert_eq!( reparsed.find_all_by_name("Panel").len(), 2 ); //both call sites
+    }
+
+    #[test]
+    fn extract_errors() {
+        let input = r#"
+            Main:
+            Flex() { Label("a") }
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let mut skui = SKUI::parse(&tks).unwrap();
+        assert!( matches!( extract_component(&mut skui, &[], "X"), Err(RefactorError::InvalidPath(_)) ) );
+        assert!( matches!( extract_component(&mut skui, &[0, 5], "X"), Err(RefactorError::InvalidPath(_)) ) );
+        assert!( matches!( extract_component(&mut skui, &[0], "Main"), Err(RefactorError::NameInUse(_)) ) );
+    }
+}
@@ -4,6 +4,7 @@ use crate::cursor::TokenCursor;
 use crate::token::Token;
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PseudoClass {
     Hover,
     Active,
@@ -13,36 +14,94 @@ pub enum PseudoClass {
 
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Selector<'a> {
     // 단일 선택자
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Simple(SimpleSelector<'a>),
 
     // 복합 선택자 (쉼표) - OR 조건
     // .button, .link
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Group(Vec<Selector<'a>>),
 
     // 자손 선택자 (공백)
     // .container .button
+    // No `serde(borrow)` on the two-field variants : serde rejects the
+    // variant-level attribute there, and inference already borrows.
     Descendant(Box<Selector<'a>>, Box<Selector<'a>>),
 
     // 자식 선택자 (>)
     // .container > .button
     Child(Box<Selector<'a>>, Box<Selector<'a>>),
+
+    // `.label + .input` : the element directly after a `.label` sibling.
+    // Matching reads the preceding siblings off the parent chain, so it only
+    // fires when the element was reached by walking the tree.
+    Adjacent(Box<Selector<'a>>, Box<Selector<'a>>),
+
+    // `.label ~ .input` : any later sibling of a `.label`, adjacent or not
+    Sibling(Box<Selector<'a>>, Box<Selector<'a>>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SimpleSelector<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub kinds: Vec<SelectorKind<'a>>,
     pub pseudo_class: Option<PseudoClass>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SelectorKind<'a> {
     Id(&'a str),
     Class(&'a str),
     Tag(&'a str),
+    // `[text="OK"]` / `[disabled]` : matches on the component's declared
+    // parameters and properties. The bare form tests presence (a `false`
+    // value reads as absent, mirroring the flag convention in `Parameters`).
+    Attr(&'a str, #[cfg_attr(feature = "serde", serde(borrow))] Option<AttrValue<'a>>),
+    // `:not(.primary)` : negated simple selector. The argument may be
+    // compound (`:not(Button.primary)`) and contributes its own specificity,
+    // as in CSS; `:not` itself adds nothing.
+    Not(#[cfg_attr(feature = "serde", serde(borrow))] SimpleSelector<'a>),
+    // `*` : matches every element and, as in CSS, adds no specificity -
+    // document-wide defaults (`* { padding: 2px }`) lose to any real rule
+    Any,
+}
+
+// Expected value of an attribute test. Only literal forms the selector
+// grammar can carry - relative/interpolated values have no meaning here.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttrValue<'a> {
+    Str(&'a str),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+
+// CSS specificity (ids, classes, tags). Ordering is lexicographic : one id
+// outweighs any number of classes, one class outweighs any number of tags.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Specificity {
+    pub ids: u32,
+    pub classes: u32,
+    pub tags: u32,
 }
 
+impl std::ops::Add for Specificity {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            ids: self.ids + rhs.ids,
+            classes: self.classes + rhs.classes,
+            tags: self.tags + rhs.tags,
+        }
+    }
+}
 
 #[derive(Default,Clone,Copy)]
 pub struct PseudoState {
@@ -52,6 +111,82 @@ pub struct PseudoState {
     pub disabled: bool,
 }
 
+impl PseudoState {
+    // Every pseudo-class holds : build-time matching uses this so `:hover`/..
+    // rules are collected and compiled into the integration's state-aware
+    // properties. Live re-resolution passes the provider-reported state
+    // instead.
+    pub const ANY: PseudoState = PseudoState { hovered:true, active:true, focused:true, disabled:true };
+}
+
+// Live widget state, reported per widget id by the app or the integration's
+// event shim. `SKUI::get_styles_with_state` queries it during style
+// re-resolution so `:hover`/`:focus`/.. rules track the real widget state
+// instead of the all-false build-time default.
+pub trait PseudoStateProvider {
+    fn pseudo_state(&self, id:&str) -> PseudoState;
+}
+
+// A plain map works as a provider (tests, simple event shims). Ids not in the
+// map report the default state.
+impl PseudoStateProvider for std::collections::HashMap<String, PseudoState> {
+    fn pseudo_state(&self, id:&str) -> PseudoState {
+        self.get(id).copied().unwrap_or_default()
+    }
+}
+
+// Bloom-filter hash of every id/class/tag on an ancestor chain (the trick
+// browsers use for descendant matching). Built once per element, it lets
+// `fast_reject` discard rules whose ancestor part cannot possibly match
+// without walking the parent chain. False positives only : a hit still goes
+// through the exact match.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AncestorFilter(u64);
+
+impl AncestorFilter {
+    pub fn from_parents(parents:&[&Component]) -> Self {
+        let mut filter = Self::default();
+        for p in parents.iter() {
+            filter.insert(SelectorKind::Tag(p.name));
+            if let Some(id) = p.id {
+                filter.insert(SelectorKind::Id(id));
+            }
+            for cls in p.classes.iter() {
+                filter.insert(SelectorKind::Class(cls));
+            }
+        }
+        filter
+    }
+
+    fn insert(&mut self, kind:SelectorKind) {
+        self.0 |= Self::bits(&kind);
+    }
+
+    fn may_contain(&self, kind:&SelectorKind) -> bool {
+        let bits = Self::bits(kind);
+        self.0 & bits == bits
+    }
+
+    // FNV-1a over a kind-discriminating prefix + name, two bits per entry
+    fn bits(kind:&SelectorKind) -> u64 {
+        let (prefix, name) = match kind {
+            SelectorKind::Tag(name) => (0x01u8, *name),
+            SelectorKind::Id(name) => (0x02, *name),
+            SelectorKind::Class(name) => (0x03, *name),
+            // attribute tests, negations and the universal selector are not
+            // hashed into the filter : zero bits, so `may_contain` never
+            // rejects on them
+            SelectorKind::Attr(..) | SelectorKind::Not(_) | SelectorKind::Any => return 0,
+        };
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for b in std::iter::once(prefix).chain( name.bytes() ) {
+            hash ^= b as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        (1 << (hash % 64)) | (1 << ((hash >> 32) % 64))
+    }
+}
+
 impl<'a> SimpleSelector<'a> {
     pub fn new() -> Self {
         Self {
@@ -75,6 +210,21 @@ impl<'a> SimpleSelector<'a> {
         self
     }
 
+    pub fn attr(mut self, key: &'a str, value: Option<AttrValue<'a>>) -> Self {
+        self.kinds.push(SelectorKind::Attr(key, value));
+        self
+    }
+
+    pub fn not(mut self, inner: SimpleSelector<'a>) -> Self {
+        self.kinds.push(SelectorKind::Not(inner));
+        self
+    }
+
+    pub fn any(mut self) -> Self {
+        self.kinds.push(SelectorKind::Any);
+        self
+    }
+
     pub fn hover(mut self) -> Self {
         self.pseudo_class = Some(PseudoClass::Hover);
         self
@@ -99,6 +249,25 @@ impl<'a> SimpleSelector<'a> {
         self.pseudo_class.as_ref()
     }
 
+    // This selector's specificity contribution; pseudo-classes and attribute
+    // tests count as classes, as in CSS.
+    pub fn specificity(&self) -> Specificity {
+        let mut s = Specificity::default();
+        for kind in self.kinds.iter() {
+            match kind {
+                SelectorKind::Id(_) => s.ids += 1,
+                SelectorKind::Class(_) | SelectorKind::Attr(..) => s.classes += 1,
+                SelectorKind::Tag(_) => s.tags += 1,
+                SelectorKind::Not(inner) => s = s + inner.specificity(),
+                SelectorKind::Any => {}
+            }
+        }
+        if self.pseudo_class.is_some() {
+            s.classes += 1;
+        }
+        s
+    }
+
     pub fn has_pseudo_class(&self) -> bool {
         self.pseudo_class.is_some()
     }
@@ -110,6 +279,23 @@ impl<'a> SimpleSelector<'a> {
                 SelectorKind::Id(id) => element.id == Some(id),
                 SelectorKind::Class(class) => element.classes.contains(class),
                 SelectorKind::Tag(tag) => element.name == *tag,
+                SelectorKind::Attr(key, expected) => {
+                    // properties win over named parameters; the out-of-range
+                    // index keeps `Parameters::get` off the positional slots
+                    // while still reading bare flags (`TextArea(editable)`)
+                    let found = element.properties.get(key)
+                        .or_else( || element.params.get(usize::MAX, *key) );
+                    match (found, expected) {
+                        (Some(v), None) => v.as_bool() != Some(false),
+                        (Some(v), Some(AttrValue::Str(s))) => v.as_str() == Some(*s),
+                        (Some(v), Some(AttrValue::Int(n))) => v.as_i64() == Some(*n),
+                        (Some(v), Some(AttrValue::Float(n))) => v.as_f64() == Some(*n),
+                        (Some(v), Some(AttrValue::Bool(b))) => v.as_bool() == Some(*b),
+                        (None, _) => false,
+                    }
+                }
+                SelectorKind::Not(inner) => !inner.is_matches(element, state),
+                SelectorKind::Any => true,
             };
 
             if !matches {
@@ -118,20 +304,25 @@ impl<'a> SimpleSelector<'a> {
         }
 
         // pseudo_class 체크
-        // if let Some(pseudo) = &self.pseudo_class {
-        //     match pseudo {
-        //         PseudoClass::Hover => state.hovered,
-        //         PseudoClass::Active => state.active,
-        //         PseudoClass::Focus => state.focused,
-        //         PseudoClass::Disabled => state.disabled,
-        //     }
-        // } else {
-        //     true
-        // }
+        if let Some(pseudo) = &self.pseudo_class {
+            return match pseudo {
+                PseudoClass::Hover => state.hovered,
+                PseudoClass::Active => state.active,
+                PseudoClass::Focus => state.focused,
+                PseudoClass::Disabled => state.disabled,
+            };
+        }
         true
     }
 
-
+    // `rename_class` / `rename_id` / `rename_tag` support, descending into
+    // `:not(..)` arguments
+    fn rename_kinds(&mut self, rename:&mut impl FnMut(&mut SelectorKind<'a>) -> bool) -> usize {
+        self.kinds.iter_mut().map( |kind| match kind {
+            SelectorKind::Not(inner) => inner.rename_kinds(rename),
+            kind @ _ => rename(kind) as usize,
+        }).sum()
+    }
 }
 
 impl<'a> Selector<'a> {
@@ -150,7 +341,8 @@ impl<'a> Selector<'a> {
 
             // 자손/자식 선택자: 오른쪽(마지막) 선택자의 pseudo_class 반환
             // 예: .container .button:hover -> :hover 반환
-            Selector::Descendant(_, right) | Selector::Child(_, right) => {
+            Selector::Descendant(_, right) | Selector::Child(_, right)
+            | Selector::Adjacent(_, right) | Selector::Sibling(_, right) => {
                 right.get_pseudo_class()
             }
         }
@@ -170,7 +362,8 @@ impl<'a> Selector<'a> {
                     .collect()
             }
 
-            Selector::Descendant(left, right) | Selector::Child(left, right) => {
+            Selector::Descendant(left, right) | Selector::Child(left, right)
+            | Selector::Adjacent(left, right) | Selector::Sibling(left, right) => {
                 let mut result = left.collect_pseudo_classes();
                 result.extend(right.collect_pseudo_classes());
                 result
@@ -189,7 +382,8 @@ impl<'a> Selector<'a> {
                 selectors.iter().any(|s| s.has_pseudo_class(target))
             }
 
-            Selector::Descendant(left, right) | Selector::Child(left, right) => {
+            Selector::Descendant(left, right) | Selector::Child(left, right)
+            | Selector::Adjacent(left, right) | Selector::Sibling(left, right) => {
                 left.has_pseudo_class(target) || right.has_pseudo_class(target)
             }
         }
@@ -197,6 +391,29 @@ impl<'a> Selector<'a> {
 
 
 
+    // Whether any simple selector carries an attribute test (`[..]`).
+    // 0.4 grammar - the version pragma check in the parser reads this.
+    pub fn has_attr(&self) -> bool {
+        match self {
+            Selector::Simple(simple) => {
+                simple.kinds.iter().any( |k| matches!(k, SelectorKind::Attr(..)) )
+            }
+            Selector::Group(selectors) => {
+                selectors.iter().any( |s| s.has_attr() )
+            }
+            Selector::Descendant(left, right) | Selector::Child(left, right)
+            | Selector::Adjacent(left, right) | Selector::Sibling(left, right) => {
+                left.has_attr() || right.has_attr()
+            }
+        }
+    }
+
+    // The selector in source syntax - the same text `to_source` emits and the
+    // stylesheet parser accepts. Equivalent to `to_string()` via `Display`.
+    pub fn to_css_string(&self) -> String {
+        crate::fmt::selector_source(self)
+    }
+
     pub fn is_matches(&self, parents:&[&Component<'a>], element: &Component<'a>, state:PseudoState) -> bool {
         match self {
             Selector::Simple(simple) => simple.is_matches(element, state),
@@ -230,6 +447,80 @@ impl<'a> Selector<'a> {
 
                 parents.iter().rev().next().map_or(false, |p| parent_sel.is_matches(parents, p, state))
             }
+
+            // `+` : the directly preceding sibling. The sibling context comes
+            // off the parent chain (the parent owns its children), so a root
+            // or an element matched outside its tree has no siblings.
+            Selector::Adjacent(prev_sel, sel) => {
+                if !sel.is_matches(parents, element, state) {
+                    return false;
+                }
+
+                preceding_siblings(parents, element).last()
+                    .map_or(false, |p| prev_sel.is_matches(parents, p, state))
+            }
+
+            // `~` : any earlier sibling
+            Selector::Sibling(prev_sel, sel) => {
+                if !sel.is_matches(parents, element, state) {
+                    return false;
+                }
+
+                preceding_siblings(parents, element).iter()
+                    .any( |p| prev_sel.is_matches(parents, p, state) )
+            }
+        }
+    }
+
+    // `is_matches` plus the specificity of the match. The cascade and the
+    // inspector share this one scoring implementation; `None` means no match.
+    pub fn match_score(&self, parents:&[&Component<'a>], element: &Component<'a>, state:PseudoState) -> Option<Specificity> {
+        match self {
+            Selector::Simple(simple) => {
+                if simple.is_matches(element, state) {
+                    Some( simple.specificity() )
+                } else { None }
+            }
+
+            // a group matches through its most specific matching member
+            Selector::Group(selectors) => {
+                selectors.iter()
+                    .filter_map( |sel| sel.match_score(parents, element, state) )
+                    .max()
+            }
+
+            // combinators add both sides, as in CSS. The ancestor walk mirrors
+            // `is_matches` but keeps the best-scoring matching ancestor.
+            Selector::Descendant(ancestor_sel, descendant_sel) => {
+                let right = descendant_sel.match_score(parents, element, state)?;
+                let left = (1..parents.len()).rev()
+                    .filter_map( |i| ancestor_sel.match_score(&parents[..i], &parents[i], state) )
+                    .max()?;
+                Some( left + right )
+            }
+
+            Selector::Child(parent_sel, child_sel) => {
+                let right = child_sel.match_score(parents, element, state)?;
+                let p = parents.iter().rev().next()?;
+                let left = parent_sel.match_score(parents, p, state)?;
+                Some( left + right )
+            }
+
+            Selector::Adjacent(prev_sel, sel) => {
+                let right = sel.match_score(parents, element, state)?;
+                let p = preceding_siblings(parents, element).last()?;
+                let left = prev_sel.match_score(parents, p, state)?;
+                Some( left + right )
+            }
+
+            // like `Descendant`, the best-scoring matching sibling counts
+            Selector::Sibling(prev_sel, sel) => {
+                let right = sel.match_score(parents, element, state)?;
+                let left = preceding_siblings(parents, element).iter()
+                    .filter_map( |p| prev_sel.match_score(parents, p, state) )
+                    .max()?;
+                Some( left + right )
+            }
         }
     }
 }
@@ -248,12 +539,112 @@ impl<'a> Selector<'a> {
         Selector::Child(Box::new(parent), Box::new(child))
     }
 
+    pub fn adjacent(prev: Selector<'a>, next: Selector<'a>) -> Self {
+        Selector::Adjacent(Box::new(prev), Box::new(next))
+    }
+
+    pub fn sibling(prev: Selector<'a>, next: Selector<'a>) -> Self {
+        Selector::Sibling(Box::new(prev), Box::new(next))
+    }
+
     pub fn parse_from_token(tks:&'a crate::TokenAndSpan) -> Result<Selector<'a> , SelectorParseError> {
         //let tks = crate::TokenAndSpan::new(selector_str).tokens;
         let cursor = TokenCursor::new( &tks.tokens );
         //Self::parse_from_token( cursor ).map(|(_,sel)| sel)
         SelectorParser::parse( cursor ).map( move |(_,sel)| sel)
     }
+
+    // 셀렉터 전체에서 클래스 이름을 교체. 교체한 횟수를 반환
+    pub fn rename_class(&mut self, old:&str, new:&'a str) -> usize {
+        self.rename_kinds( &mut |kind| match kind {
+            SelectorKind::Class(cls) if *cls == old => { *kind = SelectorKind::Class(new); true }
+            _ => false,
+        })
+    }
+
+    pub fn rename_id(&mut self, old:&str, new:&'a str) -> usize {
+        self.rename_kinds( &mut |kind| match kind {
+            SelectorKind::Id(id) if *id == old => { *kind = SelectorKind::Id(new); true }
+            _ => false,
+        })
+    }
+
+    pub fn rename_tag(&mut self, old:&str, new:&'a str) -> usize {
+        self.rename_kinds( &mut |kind| match kind {
+            SelectorKind::Tag(tag) if *tag == old => { *kind = SelectorKind::Tag(new); true }
+            _ => false,
+        })
+    }
+
+    // O(1) pre-check against the element's ancestor filter. `true` means the
+    // rule cannot match here and the exact walk can be skipped entirely.
+    pub fn fast_reject(&self, filter:&AncestorFilter) -> bool {
+        match self {
+            Selector::Simple(_) => false,
+            // OR : only rejectable when every branch is
+            Selector::Group(selectors) => {
+                selectors.iter().all( |sel| sel.fast_reject(filter) )
+            }
+            // the left side only ever matches ancestors (a direct parent is
+            // also in the filter), so all of its kinds must be present
+            Selector::Descendant(ancestor, descendant)
+            | Selector::Child(ancestor, descendant) => {
+                ancestor.ancestor_missing(filter) || descendant.fast_reject(filter)
+            }
+            // the filter knows nothing about siblings, only the right side counts
+            Selector::Adjacent(_, next) | Selector::Sibling(_, next) => {
+                next.fast_reject(filter)
+            }
+        }
+    }
+
+    fn ancestor_missing(&self, filter:&AncestorFilter) -> bool {
+        match self {
+            Selector::Simple(simple) => {
+                simple.kinds.iter().any( |kind| !filter.may_contain(kind) )
+            }
+            Selector::Group(selectors) => {
+                selectors.iter().all( |sel| sel.ancestor_missing(filter) )
+            }
+            Selector::Descendant(left, right) | Selector::Child(left, right) => {
+                left.ancestor_missing(filter) || right.ancestor_missing(filter)
+            }
+            // only the right side names the ancestor itself; the left side
+            // names one of its siblings, which the filter does not cover
+            Selector::Adjacent(_, right) | Selector::Sibling(_, right) => {
+                right.ancestor_missing(filter)
+            }
+        }
+    }
+
+    fn rename_kinds(&mut self, rename:&mut impl FnMut(&mut SelectorKind<'a>) -> bool) -> usize {
+        match self {
+            Selector::Simple(simple) => {
+                simple.rename_kinds(rename)
+            }
+            Selector::Group(selectors) => {
+                selectors.iter_mut().map( |sel| sel.rename_kinds(rename) ).sum()
+            }
+            Selector::Descendant(left, right) | Selector::Child(left, right)
+            | Selector::Adjacent(left, right) | Selector::Sibling(left, right) => {
+                left.rename_kinds(rename) + right.rename_kinds(rename)
+            }
+        }
+    }
+}
+
+// The element's earlier siblings, in document order. Identity (not equality)
+// locates the element among its parent's children - two identical siblings
+// must not be confused. A root, or an element passed in without its real
+// parent chain, has no sibling context and yields the empty slice.
+fn preceding_siblings<'b,'a>(parents:&[&'b Component<'a>], element:&Component<'a>) -> &'b [Component<'a>] {
+    parents.last()
+        .and_then( |p| {
+            p.children.iter()
+                .position( |c| std::ptr::eq(c, element) )
+                .map( |i| &p.children[..i] )
+        })
+        .unwrap_or(&[])
 }
 
 #[derive(Debug,Clone)]
@@ -333,7 +724,21 @@ impl SelectorParser {
                     cursor = next_cursor;
                     left = Selector::Child(Box::new(left), Box::new(right));
                 }
-                Token::Id(_) | Token::Class(_) | Token::Ident(_) | Token::Colon => {
+                Token::Plus => {
+                    cursor = next_cursor;
+                    cursor = Self::skip_whitespace(cursor);
+                    let (next_cursor, right) = Self::parse_simple_selector(cursor)?;
+                    cursor = next_cursor;
+                    left = Selector::Adjacent(Box::new(left), Box::new(right));
+                }
+                Token::Tilde => {
+                    cursor = next_cursor;
+                    cursor = Self::skip_whitespace(cursor);
+                    let (next_cursor, right) = Self::parse_simple_selector(cursor)?;
+                    cursor = next_cursor;
+                    left = Selector::Sibling(Box::new(left), Box::new(right));
+                }
+                Token::Id(_) | Token::Class(_) | Token::Ident(_) | Token::Colon | Token::LBracket | Token::Star => {
                     // 공백으로 구분된 descendant (implicit)
                     let (next_cursor, right) = Self::parse_simple_selector(cursor)?;
                     cursor = next_cursor;
@@ -372,10 +777,77 @@ impl SelectorParser {
                     cursor = next_cursor;
                     has_any = true;
                 }
+                Token::Star => {
+                    simple = simple.any();
+                    cursor = next_cursor;
+                    has_any = true;
+                }
+                Token::LBracket => {
+                    cursor = Self::skip_whitespace(next_cursor);
+                    let (next_cursor, key_token) = cursor.consume_one();
+                    let Token::Ident(key) = key_token else {
+                        return Err(SelectorParseError::UnexpectedToken(
+                            format!("Expected attribute name, found {:?}", key_token)
+                        ));
+                    };
+                    cursor = Self::skip_whitespace(next_cursor);
+
+                    let (next_cursor, token) = cursor.fork().consume_one();
+                    let expected = if token == Token::Equal {
+                        cursor = Self::skip_whitespace(next_cursor);
+                        let (next_cursor, value_token) = cursor.consume_one();
+                        cursor = Self::skip_whitespace(next_cursor);
+                        Some( match value_token {
+                            Token::Str(s) => AttrValue::Str(s),
+                            Token::Integer(n) => AttrValue::Int(n),
+                            Token::Float(n) => AttrValue::Float(n),
+                            Token::True => AttrValue::Bool(true),
+                            Token::False => AttrValue::Bool(false),
+                            t @ _ => return Err(SelectorParseError::UnexpectedToken(
+                                format!("Expected attribute value, found {:?}", t)
+                            )),
+                        })
+                    } else { None };
+
+                    let (next_cursor, token) = cursor.consume_one();
+                    if token != Token::RBracket {
+                        return Err(SelectorParseError::UnexpectedToken(
+                            format!("Expected RBracket, found {:?}", token)
+                        ));
+                    }
+                    cursor = next_cursor;
+                    simple = simple.attr(key, expected);
+                    has_any = true;
+                }
                 Token::Colon => {
                     cursor = next_cursor;
                     let (next_cursor, pseudo_token) = cursor.consume_one();
                     if let Token::Ident(pseudo) = pseudo_token {
+                        if pseudo == "not" {
+                            // `:not( .primary )` : a (possibly compound) simple
+                            // selector between the parens, negated
+                            let c = Self::skip_whitespace(next_cursor);
+                            let (c, token) = c.consume_one();
+                            if token != Token::LParen {
+                                return Err(SelectorParseError::UnexpectedToken(
+                                    format!("Expected LParen after :not, found {:?}", token)
+                                ));
+                            }
+                            let c = Self::skip_whitespace(c);
+                            let (c, inner) = Self::parse_simple_selector(c)?;
+                            let Selector::Simple(inner) = inner else { unreachable!() };
+                            let c = Self::skip_whitespace(c);
+                            let (c, token) = c.consume_one();
+                            if token != Token::RParen {
+                                return Err(SelectorParseError::UnexpectedToken(
+                                    format!("Expected RParen, found {:?}", token)
+                                ));
+                            }
+                            simple = simple.not(inner);
+                            cursor = c;
+                            has_any = true;
+                            continue;
+                        }
                         simple = match pseudo {
                             "hover" => simple.hover(),
                             "active" => simple.active(),
@@ -406,7 +878,7 @@ impl SelectorParser {
         let mut cursor = cursor;
         loop {
             let (next_cursor, token) = cursor.fork().consume_one();
-            if token == Token::Whitespace {
+            if matches!(token, Token::Whitespace | Token::Comment) {
                 cursor = next_cursor;
             } else {
                 break;
@@ -432,6 +904,265 @@ mod tests {
         assert_eq!(selector, expected);
     }
     
+    #[test]
+    fn bloom_fast_reject() {
+        fn comp(name:&'static str, id:Option<&'static str>, classes:&[&'static str]) -> Component<'static> {
+            let mut cls = ArrayVec::default();
+            classes.iter().for_each( |c| cls.push(*c) );
+            Component {
+                name, id, classes: cls,
+                params: Parameters::empty(),
+                children: vec![],
+                properties: Default::default(),
+            }
+        }
+
+        let root = comp("Flex", Some("root"), &["card"]);
+        let mid = comp("Flex", None, &["inner"]);
+        let leaf = comp("Label", None, &["title"]);
+        let parents: Vec<&Component> = vec![&root, &mid];
+        let filter = AncestorFilter::from_parents(parents.as_slice());
+
+        fn sel<'a>(src:&'a TokenAndSpan<'a>) -> Selector<'a> {
+            Selector::parse_from_token(src).unwrap()
+        }
+
+        // present ancestors never reject
+        let hit = TokenAndSpan::new(".card Label {");
+        assert!( !sel(&hit).fast_reject(&filter) );
+        let hit = TokenAndSpan::new("#root > .title {");
+        assert!( !sel(&hit).fast_reject(&filter) );
+        // unknown ancestor class rejects in O(1)
+        let miss = TokenAndSpan::new(".sidebar Label {");
+        assert!( sel(&miss).fast_reject(&filter) );
+        // group rejects only when every branch does
+        let group = TokenAndSpan::new(".sidebar Label, .card Label {");
+        assert!( !sel(&group).fast_reject(&filter) );
+        // simple selectors have no ancestor part to reject on
+        let simple = TokenAndSpan::new(".nothing {");
+        assert!( !sel(&simple).fast_reject(&filter) );
+
+        // a rejected rule really does not match
+        let miss_tks = TokenAndSpan::new(".sidebar Label {");
+        let miss = sel(&miss_tks);
+        assert!( !miss.is_matches(parents.as_slice(), &leaf, PseudoState::default()) );
+    }
+
+    #[test]
+    fn match_scores() {
+        fn comp(name:&'static str, id:Option<&'static str>, classes:&[&'static str]) -> Component<'static> {
+            let mut cls = ArrayVec::default();
+            classes.iter().for_each( |c| cls.push(*c) );
+            Component {
+                name, id, classes: cls,
+                params: Parameters::empty(),
+                children: vec![],
+                properties: Default::default(),
+            }
+        }
+        //the parse borrows the token stream, so this has to be a macro : the
+        //temporary `TokenAndSpan` then lives to the end of the statement
+        macro_rules! sel {
+            ($src:expr) => { Selector::parse_from_token(&TokenAndSpan::new($src)).unwrap() }
+        }
+        fn score(ids:u32, classes:u32, tags:u32) -> Specificity {
+            Specificity { ids, classes, tags }
+        }
+
+        let root = comp("Flex", Some("root"), &["card"]);
+        let mid = comp("Flex", None, &["inner"]);
+        let leaf = comp("Label", None, &["title"]);
+        let parents: Vec<&Component> = vec![&root, &mid];
+        let state = PseudoState::default();
+
+        //simple selectors score their own kinds; pseudo counts as a class
+        assert_eq!( sel!("Label {").match_score(&parents, &leaf, state), Some(score(0,0,1)) );
+        assert_eq!( sel!(".title {").match_score(&parents, &leaf, state), Some(score(0,1,0)) );
+        assert_eq!( sel!("Label.title:hover {").match_score(&parents, &leaf, PseudoState{hovered:true, ..Default::default()}), Some(score(0,2,1)) );
+        assert_eq!( sel!("Button {").match_score(&parents, &leaf, state), None );
+
+        //combinators add both sides
+        assert_eq!( sel!(".inner > Label {").match_score(&parents, &leaf, state), Some(score(0,1,1)) );
+
+        //a group scores through its most specific matching member
+        assert_eq!( sel!("Label, .title {").match_score(&parents, &leaf, state), Some(score(0,1,0)) );
+
+        //ids outrank any number of classes in the cascade ordering
+        assert!( score(1,0,0) > score(0,5,5) );
+        assert!( score(0,1,0) > score(0,0,9) );
+
+        //match_score and is_matches agree
+        let tks = TokenAndSpan::new("#root Label {");
+        let s = Selector::parse_from_token(&tks).unwrap();
+        assert_eq!( s.match_score(&parents, &leaf, state).is_some(), s.is_matches(&parents, &leaf, state) );
+    }
+
+    #[test]
+    fn attribute_selectors() {
+        use std::collections::HashMap;
+        use crate::{Number, Value};
+
+        macro_rules! sel {
+            ($src:expr) => { Selector::parse_from_token(&TokenAndSpan::new($src)).unwrap() }
+        }
+
+        let mut params = HashMap::new();
+        params.insert("text", Value::String("OK"));
+        params.insert("width", Value::Number(Number::I64(200)));
+        let mut properties = HashMap::new();
+        properties.insert("editable", Value::Bool(true));
+        let button = Component {
+            name: "Button",
+            id: None,
+            classes: ArrayVec::default(),
+            params: Parameters::Map(params),
+            children: vec![],
+            properties,
+        };
+        let parents: Vec<&Component> = vec![];
+        let state = PseudoState::default();
+
+        //parsed shape : key plus optional literal
+        let tks = TokenAndSpan::new(r#"Button[text="OK"] {"#);
+        assert_eq!( Selector::parse_from_token(&tks).unwrap(),
+            Selector::Simple( SimpleSelector::new().tag("Button").attr("text", Some(AttrValue::Str("OK"))) ) );
+
+        //value tests read named parameters and properties alike
+        assert!( sel!(r#"[text="OK"] {"#).is_matches(&parents, &button, state) );
+        assert!( !sel!(r#"[text="NO"] {"#).is_matches(&parents, &button, state) );
+        assert!( sel!("[width=200] {").is_matches(&parents, &button, state) );
+        assert!( sel!("[editable=true] {").is_matches(&parents, &button, state) );
+        //bare form tests presence
+        assert!( sel!("[editable] {").is_matches(&parents, &button, state) );
+        assert!( !sel!("[missing] {").is_matches(&parents, &button, state) );
+
+        //an attribute test weighs like a class
+        assert_eq!( sel!(r#"Button[text="OK"] {"#).match_score(&parents, &button, state),
+            Some(Specificity{ ids:0, classes:1, tags:1 }) );
+
+        //emitted source round-trips through `to_css_string`
+        assert_eq!( sel!(r#"Button[text="OK"][editable] {"#).to_css_string(), r#"Button[text="OK"][editable]"# );
+    }
+
+    #[test]
+    fn not_pseudo_class() {
+        macro_rules! sel {
+            ($src:expr) => { Selector::parse_from_token(&TokenAndSpan::new($src)).unwrap() }
+        }
+        fn comp(name:&'static str, id:Option<&'static str>, classes:&[&'static str]) -> Component<'static> {
+            let mut cls = ArrayVec::default();
+            classes.iter().for_each( |c| cls.push(*c) );
+            Component {
+                name, id, classes: cls,
+                params: Parameters::empty(),
+                children: vec![],
+                properties: Default::default(),
+            }
+        }
+
+        let plain = comp("Button", None, &[]);
+        let primary = comp("Button", None, &["primary"]);
+        let parents: Vec<&Component> = vec![];
+        let state = PseudoState::default();
+
+        //negation excludes the matching element, nothing else
+        assert!( sel!("Button:not(.primary) {").is_matches(&parents, &plain, state) );
+        assert!( !sel!("Button:not(.primary) {").is_matches(&parents, &primary, state) );
+        assert!( !sel!("Button:not(Button) {").is_matches(&parents, &plain, state) );
+
+        //the negated argument may itself be compound
+        assert!( sel!(":not(Button.primary) {").is_matches(&parents, &plain, state) );
+        assert!( !sel!(":not(Button.primary) {").is_matches(&parents, &primary, state) );
+
+        //`:not` weighs what its argument weighs
+        assert_eq!( sel!("Button:not(.primary) {").match_score(&parents, &plain, state),
+            Some(Specificity{ ids:0, classes:1, tags:1 }) );
+
+        //a state pseudo-class may still follow the negation
+        assert_eq!( sel!("Button:not(.primary):hover {").to_css_string(), "Button:not(.primary):hover" );
+        assert!( sel!("Button:not(.primary):hover {").is_matches(&parents, &plain, PseudoState{ hovered:true, ..Default::default() }) );
+    }
+
+    #[test]
+    fn universal_selector() {
+        macro_rules! sel {
+            ($src:expr) => { Selector::parse_from_token(&TokenAndSpan::new($src)).unwrap() }
+        }
+        let mut cls = ArrayVec::default();
+        cls.push("title");
+        let label = Component {
+            name: "Label", id: None, classes: cls,
+            params: Parameters::empty(),
+            children: vec![],
+            properties: Default::default(),
+        };
+        let flex = Component {
+            name: "Flex", id: None, classes: ArrayVec::default(),
+            params: Parameters::empty(),
+            children: vec![],
+            properties: Default::default(),
+        };
+        let state = PseudoState::default();
+
+        //`*` matches anything, alone or as the right side of a combinator
+        assert_eq!( sel!("* {"), Selector::Simple( SimpleSelector::new().any() ) );
+        assert!( sel!("* {").is_matches(&[], &label, state) );
+        assert!( sel!("Flex > * {").is_matches(&[&flex], &label, state) );
+        assert!( !sel!("Grid > * {").is_matches(&[&flex], &label, state) );
+
+        //zero specificity : a document-wide default loses to any real rule
+        assert_eq!( sel!("* {").match_score(&[], &label, state), Some(Specificity::default()) );
+        assert!( sel!(".title {").match_score(&[], &label, state) > sel!("* {").match_score(&[], &label, state) );
+
+        assert_eq!( sel!("Flex > * {").to_css_string(), "Flex > *" );
+    }
+
+    #[test]
+    fn sibling_combinators() {
+        macro_rules! sel {
+            ($src:expr) => { Selector::parse_from_token(&TokenAndSpan::new($src)).unwrap() }
+        }
+        fn comp(name:&'static str, classes:&[&'static str], children:Vec<Component<'static>>) -> Component<'static> {
+            let mut cls = ArrayVec::default();
+            classes.iter().for_each( |c| cls.push(*c) );
+            Component {
+                name, id: None, classes: cls,
+                params: Parameters::empty(),
+                children,
+                properties: Default::default(),
+            }
+        }
+
+        let flex = comp("Flex", &[], vec![
+            comp("Label", &["label"], vec![]),
+            comp("TextInput", &[], vec![]),
+            comp("Button", &[], vec![]),
+        ]);
+        let parents: Vec<&Component> = vec![&flex];
+        let state = PseudoState::default();
+        let input = &flex.children[1];
+        let button = &flex.children[2];
+
+        //`+` matches only the element directly after the left side
+        assert!( sel!(".label + TextInput {").is_matches(&parents, input, state) );
+        assert!( !sel!(".label + Button {").is_matches(&parents, button, state) );
+
+        //`~` reaches any later sibling
+        assert!( sel!(".label ~ Button {").is_matches(&parents, button, state) );
+        //.. but never an earlier one
+        assert!( !sel!("Button ~ TextInput {").is_matches(&parents, input, state) );
+
+        //an element without its parent chain has no sibling context
+        assert!( !sel!(".label + TextInput {").is_matches(&[], input, state) );
+
+        //both sides count toward specificity, as with the other combinators
+        assert_eq!( sel!(".label + TextInput {").match_score(&parents, input, state),
+            Some(Specificity{ ids:0, classes:1, tags:1 }) );
+
+        //round-trips through the formatter
+        assert_eq!( sel!(".label ~ Button {").to_css_string(), ".label ~ Button" );
+    }
+
     #[test]
     fn test_selectors() {
         fn simple(kinds: Vec<SelectorKind>, pseudo: Option<PseudoClass>) -> Selector {
@@ -26,6 +26,14 @@ pub enum Token<'a> {
     )]
     Rgb((u8, u8, u8)),
 
+    // `calc(..)` with the inner expression kept verbatim; evaluated lazily by
+    // `CssValue::resolve` against a parent dimension. No nested parentheses.
+    #[regex(r"calc\([^)]*\)", |lex| {
+        let s = lex.slice();
+        &s[5..s.len()-1]
+    })]
+    Calc(&'a str),
+
     #[regex(r"[0-9]+(\.[0-9]+)?em", |lex| {
         let s = lex.slice();
         s[..s.len()-2].parse::<f64>().ok()
@@ -59,10 +67,23 @@ pub enum Token<'a> {
     #[regex(r"\.[A-Za-z_][A-Za-z0-9_-]*", |lex| &lex.slice()[1..])]
     Class(&'a str),
 
+    // `--name` CSS custom property, as a declaration key or inside `var(..)`.
+    // The leading dashes are kept : they are part of the name in CSS.
+    #[regex(r"--[A-Za-z_][A-Za-z0-9_-]*", |lex| lex.slice())]
+    VarName(&'a str),
+
+    // Quoted strings. Escapes (`\n`, `\"`, ..) are kept verbatim in the slice
+    // and decoded by `unescape` at read time. The backtick form reads the same
+    // way but admits unescaped `"` and literal newlines, for long Prose /
+    // TextArea content.
     #[regex(r#""([^"\\]|\\.)*""#, |lex| {
         let s = lex.slice();
         &s[1..s.len()-1]
     })]
+    #[regex(r"`([^`\\]|\\.)*`", |lex| {
+        let s = lex.slice();
+        &s[1..s.len()-1]
+    })]
     Str(&'a str),
 
     #[regex(r"-?\d+\.\d+", |lex| lex.slice().parse().ok())]
@@ -116,13 +137,42 @@ pub enum Token<'a> {
     #[token("=")]
     Equal,
 
+    #[token("@")]
+    At,
+
     #[token("|")]
     Pipe,
 
+    // only used by the sibling combinators in selectors
+    #[token("+")]
+    Plus,
+
+    #[token("~")]
+    Tilde,
+
+    // the universal selector
+    #[token("*")]
+    Star,
+
+    // only used by `!important` on style declarations
+    #[token("!")]
+    Bang,
+
+    // Deliberately NOT `logos::skip` : the selector parser is whitespace
+    // sensitive (descendant combinator) and editor tooling wants the spans.
+    // Everything else parses the trimmed stream `TokenAndSpan` maintains, so
+    // the main parser never sees this token.
     // #[regex(r"[ \t\r\n]+", logos::skip)]
     #[regex(r"[ \t\r\n]+")]
     Whitespace,
 
+    // `// ..` to end of line and `/* .. */` blocks. Kept in the raw stream
+    // (editor tooling wants the spans) but filtered out like whitespace.
+    //logos flags `[^\n]*` as unbounded-greedy; the newline bound makes it safe
+    #[regex(r"//[^\n]*", allow_greedy = true)]
+    #[regex(r"/\*[^*]*\*+([^/*][^*]*\*+)*/")]
+    Comment,
+
     None,
 }
 
@@ -0,0 +1,137 @@
+use tinyvec::ArrayVec;
+use crate::fmt::{css_value_source, value_source, FmtOptions};
+use crate::{splice_css_var, CssValue, Number, Value, SKUI};
+
+// Design-token export : dumps the document's resolved variable table
+// (`--name` declarations, after last-one-wins resolution and `var(..)`
+// substitution) and `let` constants as one JSON object, so external
+// pipelines (token sync, documentation sites) read the colors and spacing
+// actually in effect without parsing SKUI themselves. The table keeps
+// chained declarations (`--a: var(--b)`) as declared, so those resolve here;
+// a reference the table cannot satisfy keeps its `var(..)` text. Keys are
+// emitted sorted for diff-friendly output.
+
+pub fn export_tokens_json(skui:&SKUI) -> String {
+    let mut out = String::from("{\"vars\":{");
+    let mut keys: Vec<_> = skui.vars.keys().copied().collect();
+    keys.sort_unstable();
+    for (i,key) in keys.iter().enumerate() {
+        if i > 0 { out.push(','); }
+        let values = &skui.vars[key];
+        let mut resolved: ArrayVec<[CssValue;5]> = ArrayVec::new();
+        let mut ok = true;
+        for v in values.iter() {
+            match v {
+                CssValue::Var(name) => ok &= splice_css_var(&skui.vars, name, &mut resolved, 0),
+                v => if resolved.len() < resolved.capacity() { resolved.push(*v) },
+            }
+        }
+        //multi-value declarations (`--pad: 4px 8px`) keep their source form
+        let text = if ok { &resolved } else { values }.iter()
+            .map(css_value_source)
+            .collect::<Vec<_>>()
+            .join(" ");
+        out.push_str( &json_str(key) );
+        out.push(':');
+        out.push_str( &json_str(&text) );
+    }
+    out.push_str("},\"consts\":{");
+    let mut keys: Vec<_> = skui.consts.keys().copied().collect();
+    keys.sort_unstable();
+    for (i,key) in keys.iter().enumerate() {
+        if i > 0 { out.push(','); }
+        out.push_str( &json_str(key) );
+        out.push(':');
+        out.push_str( &const_json(&skui.consts[key]) );
+    }
+    out.push_str("}}");
+    out
+}
+
+// Scalar constants keep their JSON type; units and colors become their CSS
+// text. Anything structured JSON has no equivalent for (closures, bindings)
+// is emitted as its SKUI source text inside a string.
+fn const_json(v:&Value) -> String {
+    match v {
+        Value::Bool(b) => b.to_string(),
+        Value::Number(Number::I64(n)) => n.to_string(),
+        Value::Number(Number::F64(n)) => n.to_string(),
+        Value::String(s) | Value::Ident(s) => json_str(s),
+        Value::Unit(u) | Value::Color(u) => json_str( &css_value_source(u) ),
+        Value::Array(items) => {
+            let inner = items.iter().map(const_json).collect::<Vec<_>>().join(",");
+            format!("[{inner}]")
+        }
+        Value::Map(map) => {
+            let mut keys: Vec<_> = map.keys().copied().collect();
+            keys.sort_unstable();
+            let inner = keys.iter()
+                .map( |k| format!("{}:{}", json_str(k), const_json(&map[k])) )
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("{{{inner}}}")
+        }
+        v @ _ => json_str( &value_source(v, 0, &FmtOptions::default()) ),
+    }
+}
+
+fn json_str(s:&str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str( &format!("\\u{:04x}", c as u32) ),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl SKUI<'_> {
+    pub fn export_tokens_json(&self) -> String {
+        export_tokens_json(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenAndSpan;
+
+    #[test]
+    fn token_export() {
+        let input = r#"
+            let spacing = 8
+            let title = "Hello"
+            let scale = 1.5
+            .theme { --accent: #ff8800; --pad: 4px 8px; --hl: var(--accent) }
+            .title { color: var(--accent) }
+            Main:
+            Label("x")
+        "#;
+        let tks = TokenAndSpan::new(input);
+        let parsed = SKUI::parse(&tks).unwrap();
+        let json = parsed.export_tokens_json();
+        println!("{json}");
+
+        //resolved vars keep their CSS text, consts keep their JSON type
+        assert!( json.starts_with(r#"{"vars":{"#) );
+        assert!( json.contains(r##""--accent":"#ff8800""## ) );
+        assert!( json.contains(r#""--pad":"4px 8px""#) );
+        //a chained declaration exports its resolved value
+        assert!( json.contains(r##""--hl":"#ff8800""## ) );
+        assert!( json.contains(r#""spacing":8"#) );
+        assert!( json.contains(r#""title":"Hello""#) );
+        assert!( json.contains(r#""scale":1.5"#) );
+
+        //keys come out sorted, so repeated exports diff cleanly
+        assert_eq!( json, parsed.export_tokens_json() );
+        assert!( json.find(r#""--accent""#).unwrap() < json.find(r#""--pad""#).unwrap() );
+    }
+}
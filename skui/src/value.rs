@@ -1,8 +1,10 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::str::FromStr;
-use crate::{Component, Parameters};
+use crate::{Component, CssValue, Parameters};
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Number {
     I64(i64),
     F64(f64),
@@ -51,6 +53,7 @@ pub enum InvalidValueKey {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ValueKey<'a> {
     Index(usize),
     Name(&'a str),
@@ -80,22 +83,99 @@ impl <'a> ValueKey <'a> {
     }
 
     pub fn vec_from_str(s: &'a str) -> Result<Vec<Self>, InvalidValueKey> {
-        s.split('.').map( Self::from_str ).collect()
+        s.split('.').map( |p| Self::from_str(p.trim()) ).collect()
     }
 }
 
+// One `| name("arg", ..)` step of an interpolation filter chain.
+// e.g. `${0.price | currency("USD")}` , `${0.when | date("%Y-%m-%d")}`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilterCall<'a> {
+    pub name: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub args: Vec<&'a str>,
+}
 
-#[derive(Debug, Clone)]
+impl <'a> FilterCall<'a> {
+    pub fn from_str(s: &'a str) -> Result<Self, InvalidValueKey> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(InvalidValueKey::Empty)
+        }
+        let (name, args) = match s.find('(') {
+            Some(open) => {
+                let Some(close) = s.rfind(')')
+                else { return Err(InvalidValueKey::Invalid(s.to_string())) };
+                let args = s[open+1 .. close].split(',')
+                    .map( |a| a.trim().trim_matches('"') )
+                    .filter( |a| !a.is_empty() )
+                    .collect();
+                (s[..open].trim(), args)
+            }
+            None => (s, Vec::new())
+        };
+        if name.is_empty() {
+            Err(InvalidValueKey::Invalid(s.to_string()))
+        } else {
+            Ok( Self { name, args } )
+        }
+    }
+}
+
+
+// `tr("menu.save")` / `tr("items.count", count=${n})` : resolved through the
+// application translator at build time. args may be Relative values looked up
+// on the caller parameter stack before translation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrRef<'a> {
+    pub key: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub args: HashMap<&'a str, Value<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value<'a> {
     Ident(&'a str),
     Bool(bool),
     Number(Number),
     String(&'a str),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Array(Vec<Value<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Map(HashMap<&'a str, Value<'a>>),
     Closure(&'a str),
+    // Unit-suffixed number in parameter position : `SizedBox(width=200px)`.
+    // The core crate keeps the unit as-is; integrations convert through their
+    // own length helpers (em/pt/% need font and parent context).
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Unit(CssValue<'a>),
+    // Color literal in parameter position : `Icon("save.svg", color=#ff8800)`.
+    // Holds the HexColor/Rgb/Rgba form; integrations convert to their color
+    // type. Named colors stay plain `Ident`s - only the integration knows
+    // which names its color parser accepts.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Color(CssValue<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Component(Component<'a>),
-    Relative(Vec<ValueKey<'a>>)
+    // `"Hello, ${0.user}!"` : literal and binding segments, concatenated
+    // against the parameter stack at build time. Segments are only ever
+    // `String`, `Relative`, `RelativeOr` or `Filtered`.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Interpolated(Vec<Value<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Relative(Vec<ValueKey<'a>>),
+    // `${0.title:-"Untitled"}` : relative lookup with an inline literal
+    // fallback used when the path does not resolve on the caller stack.
+    // No `serde(borrow)` : the attribute is rejected on multi-field variants
+    // and default lifetime inference already borrows here.
+    RelativeOr(Vec<ValueKey<'a>>, Box<Value<'a>>),
+    // Relative lookup followed by a display filter chain
+    Filtered(Vec<ValueKey<'a>>, Vec<FilterCall<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Tr(TrRef<'a>),
 }
 
 impl <'a> Default for Value<'a> {
@@ -150,6 +230,13 @@ impl <'a> Value<'a> {
         self.as_str().is_some()
     }
 
+    pub fn as_tr(&self) -> Option<&TrRef<'a>> {
+        match self {
+            Value::Tr(tr) => Some(tr),
+            _ => None,
+        }
+    }
+
     pub fn as_str(&self) -> Option<&str> {
         match self {
             Value::Ident(s) => Some(s),
@@ -158,6 +245,16 @@ impl <'a> Value<'a> {
         }
     }
 
+    // `as_str` plus escape decoding for string literals. Borrowed unless the
+    // literal actually contains an escape.
+    pub fn as_text(&self) -> Option<Cow<'a, str>> {
+        match self {
+            Value::Ident(s) => Some(Cow::Borrowed(s)),
+            Value::String(s) => Some(unescape(s)),
+            _ => None,
+        }
+    }
+
     pub fn is_number(&self) -> bool {
         match *self {
             Value::Number(_) => true,
@@ -165,6 +262,20 @@ impl <'a> Value<'a> {
         }
     }
 
+    pub fn as_unit(&self) -> Option<&CssValue<'a>> {
+        match self {
+            Value::Unit(unit) => Some(unit),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<&CssValue<'a>> {
+        match self {
+            Value::Color(color) => Some(color),
+            _ => None,
+        }
+    }
+
     pub fn as_number(&self) -> Option<&Number> {
         match self {
             Value::Number(number) => Some(number),
@@ -221,6 +332,32 @@ impl <'a> Value<'a> {
 }
 
 
+// Decode `\n`, `\t`, `\r` and pass-through escapes (`\"`, `\\`, `` \` ``,
+// `\$`). String tokens keep their source slice, so decoding happens where the
+// text is actually consumed; the common escape-free case stays borrowed.
+pub fn unescape(s:&str) -> Cow<'_, str> {
+    if !s.contains('\\') {
+        return Cow::Borrowed(s)
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(c) => out.push(c),
+            //trailing lone backslash : keep it
+            None => out.push('\\'),
+        }
+    }
+    Cow::Owned(out)
+}
+
 pub enum ValueError {
     NotNumber,
     NotString
@@ -0,0 +1,81 @@
+use crate::{ParseOptions, TokenAndSpan, SKUI};
+
+// JS-friendly validation entry for web-based editors. The parser itself is
+// plain safe Rust (no fs/threads), so the crate compiles to
+// `wasm32-unknown-unknown` as-is; this module only adds a stable JSON
+// diagnostics shape so the result can cross the JS boundary as a string.
+
+pub fn validate_to_json(src:&str) -> String {
+    validate_to_json_with(src, &ParseOptions::default())
+}
+
+pub fn validate_to_json_with(src:&str, opts:&ParseOptions) -> String {
+    let tks = TokenAndSpan::new(src);
+    match SKUI::parse_with_options(&tks, opts) {
+        Ok(skui) => {
+            let stats = skui.stats();
+            format!(
+                r#"{{"ok":true,"components":{},"styles":{}}}"#,
+                stats.component_count, stats.style_rule_count
+            )
+        }
+        Err(e) => {
+            let (line,col) = line_col(src, e.span.start);
+            format!(
+                r#"{{"ok":false,"error":{{"message":{},"start":{},"end":{},"line":{},"col":{}}}}}"#,
+                json_str( &e.kind.to_string() ), e.span.start, e.span.end, line, col
+            )
+        }
+    }
+}
+
+// 1-based line/column from a byte offset
+fn line_col(src:&str, pos:usize) -> (usize,usize) {
+    let mut line = 1;
+    let mut col = 1;
+    for (i,c) in src.char_indices() {
+        if i >= pos { break }
+        if c == '\n' {
+            line += 1;
+            col = 1;
+        } else {
+            col += 1;
+        }
+    }
+    (line, col)
+}
+
+fn json_str(s:&str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str( &format!("\\u{:04x}", c as u32) ),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_diagnostics() {
+        let ok = validate_to_json("Main:\nFlex() { Label(\"hi\") }");
+        println!("{ok}");
+        assert!( ok.starts_with(r#"{"ok":true"#) );
+
+        let err = validate_to_json("Main:\nFlex() { Label( }");
+        println!("{err}");
+        assert!( err.starts_with(r#"{"ok":false"#) );
+        assert!( err.contains(r#""line":2"#) );
+    }
+}
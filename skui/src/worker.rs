@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use crate::{DocumentStats, ParseOptions, SKUIParseError, TokenAndSpan, SKUI};
+
+// Background parsing for the editor workflow. Keystrokes submit the whole
+// buffer; a worker thread parses off the UI thread and superseded jobs are
+// cancelled : a job is skipped when a newer submission exists before it
+// starts, and its result is discarded when one arrived while it parsed.
+//
+// The AST borrows its source text, so it cannot be sent between threads.
+// The outcome therefore carries the owned source plus either the document
+// stats (proof of a clean parse — the UI can re-parse or `parse_in` an arena
+// locally, which is cheap) or the parse error for diagnostics.
+
+#[derive(Debug)]
+pub struct ParseOutcome {
+    pub generation: u64,
+    pub source: String,
+    pub result: Result<DocumentStats, SKUIParseError>,
+}
+
+pub struct ParseWorker {
+    tx: Option<mpsc::Sender<(u64, String)>>,
+    rx: mpsc::Receiver<ParseOutcome>,
+    latest: Arc<AtomicU64>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl ParseWorker {
+    pub fn new() -> Self {
+        Self::with_options(ParseOptions::default())
+    }
+
+    pub fn with_options(opts:ParseOptions) -> Self {
+        let (job_tx, job_rx) = mpsc::channel::<(u64, String)>();
+        let (out_tx, out_rx) = mpsc::channel::<ParseOutcome>();
+        let latest = Arc::new( AtomicU64::new(0) );
+        let latest_worker = latest.clone();
+
+        let handle = std::thread::spawn( move || {
+            while let Ok( (generation, source) ) = job_rx.recv() {
+                //superseded before it started
+                if generation < latest_worker.load(Ordering::Acquire) { continue }
+                let tks = TokenAndSpan::new(&source);
+                let result = SKUI::parse_with_options(&tks, &opts).map( |doc| doc.stats() );
+                //superseded while parsing : the UI only wants the newest
+                if generation < latest_worker.load(Ordering::Acquire) { continue }
+                if out_tx.send( ParseOutcome { generation, source, result } ).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { tx: Some(job_tx), rx: out_rx, latest, handle: Some(handle) }
+    }
+
+    // Queue the buffer for parsing, superseding everything queued before it.
+    // Returns this submission's generation.
+    pub fn submit(&self, src:&str) -> u64 {
+        let generation = self.latest.fetch_add(1, Ordering::AcqRel) + 1;
+        if let Some(tx) = self.tx.as_ref() {
+            let _ = tx.send( (generation, src.to_string()) );
+        }
+        generation
+    }
+
+    // Non-blocking : newest outcome delivered so far, stale ones discarded.
+    pub fn try_latest(&self) -> Option<ParseOutcome> {
+        let mut newest: Option<ParseOutcome> = None;
+        while let Ok(outcome) = self.rx.try_recv() {
+            if newest.as_ref().is_none_or( |n| outcome.generation > n.generation ) {
+                newest = Some(outcome);
+            }
+        }
+        newest
+    }
+
+    // Block until the outcome of the newest submission arrives. `None` only
+    // when the worker is gone.
+    pub fn recv_latest(&self) -> Option<ParseOutcome> {
+        loop {
+            let outcome = self.rx.recv().ok()?;
+            if outcome.generation >= self.latest.load(Ordering::Acquire) {
+                return Some(outcome);
+            }
+        }
+    }
+}
+
+impl Default for ParseWorker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ParseWorker {
+    fn drop(&mut self) {
+        //disconnect the job channel so the worker loop exits, then join
+        self.tx.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_wins() {
+        let worker = ParseWorker::new();
+        //rapid keystrokes : only the newest buffer matters
+        worker.submit("Main:\nLabel(\"a\"");
+        worker.submit("Main:\nLabel(\"ab\"");
+        let last = worker.submit("Main:\nLabel(\"abc\")");
+
+        let outcome = worker.recv_latest().unwrap();
+        assert_eq!( outcome.generation, last );
+        assert!( outcome.source.contains("abc") );
+        let stats = outcome.result.unwrap();
+        assert_eq!( stats.component_count, 1 );
+        //everything older was dropped or discarded
+        assert!( worker.try_latest().is_none() );
+    }
+
+    #[test]
+    fn errors_are_delivered() {
+        let worker = ParseWorker::new();
+        worker.submit("Main:\nFlex() {");
+        let outcome = worker.recv_latest().unwrap();
+        assert!( outcome.result.is_err() );
+    }
+}